@@ -5,11 +5,18 @@
 use rustc_infer::infer::{DefiningAnchor, TyCtxtInferExt};
 use rustc_middle::mir::Body;
 use rustc_middle::ty::{self, TyCtxt};
+use std::io::{self, Write};
+
+use super::facts_json::FactsJson;
 
 pub use super::{
+    borrow_set::{BorrowData, BorrowSet},
+    dataflow::BorrowIndex,
     facts::{AllFacts as PoloniusInput, RustcFacts},
+    facts_json::BorrowckDiagnostic,
     location::{LocationTable, RichLocation},
     nll::PoloniusOutput,
+    region_infer::RegionInferenceContext,
     BodyWithBorrowckFacts,
 };
 
@@ -26,6 +33,10 @@
 ///     that shows how to do this at `src/test/run-make/obtain-borrowck/`.
 ///
 /// *   Polonius is highly unstable, so expect regular changes in its signature or other details.
+///
+/// *   The facts can be computed for every body that has not been stolen, whether or not
+///     `-Z nll-facts` was passed. They can be written in the JSON format of
+///     `-Z nll-facts-format=json` with `write_borrowck_facts_json`.
 pub fn get_body_with_borrowck_facts<'tcx>(
     tcx: TyCtxt<'tcx>,
     def: ty::WithOptConstParam<LocalDefId>,
@@ -37,3 +48,22 @@ pub fn get_body_with_borrowck_facts<'tcx>(
         *super::do_mir_borrowck(&infcx, input_body, promoted, true).1.unwrap()
     })
 }
+
+/// Writes the facts of a body in the JSON format of `-Z nll-facts-format=json`, documented in the
+/// unstable book. Unlike the Polonius facts, it describes the points, loans and regions with their
+/// source spans, and includes the region values inferred by NLL and the errors of the body.
+pub fn write_borrowck_facts_json<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body_with_facts: &BodyWithBorrowckFacts<'tcx>,
+    w: &mut dyn Write,
+) -> io::Result<()> {
+    let facts_json = FactsJson {
+        body: &body_with_facts.body,
+        location_table: &body_with_facts.location_table,
+        borrow_set: &body_with_facts.borrow_set,
+        regioncx: &body_with_facts.region_inference_context,
+        input_facts: &body_with_facts.input_facts,
+        errors: &body_with_facts.errors,
+    };
+    facts_json.write(tcx, w)
+}
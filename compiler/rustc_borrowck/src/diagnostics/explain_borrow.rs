@@ -307,7 +307,7 @@ fn free_region_constraint_info(
         outlived_region: RegionVid,
     ) -> (ConstraintCategory<'tcx>, bool, Span, Option<RegionName>) {
         let BlameConstraint { category, from_closure, cause, variance_info: _ } =
-            self.regioncx.best_blame_constraint(
+            self.regioncx.best_blame_constraint_for_error(
                 &self.body,
                 borrow_region,
                 NllRegionVariableOrigin::FreeRegion,
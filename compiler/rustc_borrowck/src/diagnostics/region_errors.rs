@@ -228,12 +228,13 @@ pub(crate) fn report_region_errors(&mut self, nll_errors: RegionErrors<'tcx>) {
                     let error_vid = self.regioncx.region_from_element(longer_fr, &error_element);
 
                     // Find the code to blame for the fact that `longer_fr` outlives `error_fr`.
-                    let (_, cause) = self.regioncx.find_outlives_blame_span(
-                        &self.body,
-                        longer_fr,
-                        NllRegionVariableOrigin::Placeholder(placeholder),
-                        error_vid,
-                    );
+                    let BlameConstraint { cause, .. } =
+                        self.regioncx.best_blame_constraint_for_error(
+                            &self.body,
+                            longer_fr,
+                            NllRegionVariableOrigin::Placeholder(placeholder),
+                            |r| self.regioncx.provides_universal_region(r, longer_fr, error_vid),
+                        );
 
                     let universe = placeholder.universe;
                     let universe_info = self.regioncx.universe_info(universe);
@@ -351,7 +352,7 @@ pub(crate) fn report_region_error(
         debug!("report_region_error(fr={:?}, outlived_fr={:?})", fr, outlived_fr);
 
         let BlameConstraint { category, cause, variance_info, from_closure: _ } =
-            self.regioncx.best_blame_constraint(&self.body, fr, fr_origin, |r| {
+            self.regioncx.best_blame_constraint_for_error(&self.body, fr, fr_origin, |r| {
                 self.regioncx.provides_universal_region(r, fr, outlived_fr)
             });
 
@@ -0,0 +1,341 @@
+//! Writes the borrowck facts of a body as a single JSON document, for
+//! `-Z nll-facts-format=json` and for the consumers of `BodyWithBorrowckFacts`.
+//!
+//! Unlike the `.facts` files, the document can be mapped back to the source:
+//! it describes every point, loan and region the facts refer to by index,
+//! with its source span, and includes the region values inferred by NLL and
+//! the errors reported by the borrow checker. The schema is documented in the
+//! unstable book, in the page of the `nll-facts-format` flag; bump
+//! `FORMAT_VERSION` when changing it.
+
+use crate::borrow_set::BorrowSet;
+use crate::facts::AllFacts;
+use crate::location::{LocationTable, RichLocation};
+use crate::region_infer::RegionInferenceContext;
+use polonius_engine::Atom;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_errors::{Diagnostic, DiagnosticId, DiagnosticMessage};
+use rustc_infer::infer::NllRegionVariableOrigin;
+use rustc_middle::mir::json::span_to_json;
+use rustc_middle::mir::{Body, BorrowKind, Location, VarDebugInfoContents};
+use rustc_middle::ty::{self, RegionVid, TyCtxt};
+use rustc_span::Span;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const FORMAT_VERSION: u32 = 1;
+
+/// A diagnostic reported by the borrow checker on a body.
+#[derive(Clone, Debug)]
+pub struct BorrowckDiagnostic {
+    /// The level of the diagnostic, e.g. `error` or `warning`.
+    pub level: &'static str,
+    /// The error code or the lint name, if any.
+    pub code: Option<String>,
+    pub message: String,
+    pub primary_spans: Vec<Span>,
+    /// The spans with a label, primary or not.
+    pub labels: Vec<(Span, String)>,
+}
+
+impl BorrowckDiagnostic {
+    pub(crate) fn new(diag: &Diagnostic) -> Self {
+        let code = diag.code.as_ref().map(|code| match code {
+            DiagnosticId::Error(code) => code.clone(),
+            DiagnosticId::Lint { name, .. } => name.clone(),
+        });
+        let message = diag.message.iter().map(|(message, _)| message_text(message)).collect();
+        let labels = diag
+            .span
+            .span_labels()
+            .into_iter()
+            .filter_map(|label| Some((label.span, message_text(&label.label?))))
+            .collect();
+        BorrowckDiagnostic {
+            level: diag.level().to_str(),
+            code,
+            message,
+            primary_spans: diag.span.primary_spans().to_vec(),
+            labels,
+        }
+    }
+}
+
+fn message_text(message: &DiagnosticMessage) -> String {
+    match message {
+        DiagnosticMessage::Str(text) => text.clone(),
+        // Translating the message needs an emitter, its identifier is enough to recognize it.
+        DiagnosticMessage::FluentIdentifier(id, None) => id.to_string(),
+        DiagnosticMessage::FluentIdentifier(id, Some(attr)) => format!("{id}.{attr}"),
+    }
+}
+
+/// Everything the JSON document of a body is made of.
+pub(crate) struct FactsJson<'a, 'tcx> {
+    pub(crate) body: &'a Body<'tcx>,
+    pub(crate) location_table: &'a LocationTable,
+    pub(crate) borrow_set: &'a BorrowSet<'tcx>,
+    pub(crate) regioncx: &'a RegionInferenceContext<'tcx>,
+    pub(crate) input_facts: &'a AllFacts,
+    pub(crate) errors: &'a [BorrowckDiagnostic],
+}
+
+impl<'tcx> FactsJson<'_, 'tcx> {
+    /// Writes the document to `facts.json` in `dir`, creating the directory if needed.
+    pub(crate) fn write_to_dir(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        dir: impl AsRef<Path>,
+    ) -> Result<(), Box<dyn Error>> {
+        let dir: &Path = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let mut file = BufWriter::new(File::create(dir.join("facts.json"))?);
+        self.write(tcx, &mut file)?;
+        Ok(())
+    }
+
+    pub(crate) fn write(&self, tcx: TyCtxt<'tcx>, w: &mut dyn Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *w, &self.to_json(tcx))?;
+        writeln!(w)
+    }
+
+    fn to_json(&self, tcx: TyCtxt<'tcx>) -> Value {
+        let body = self.body;
+        let location_span = |location: Location| span_to_json(tcx, body.source_info(location).span);
+
+        let points: Vec<_> = self
+            .location_table
+            .all_points()
+            .map(|point| {
+                let (kind, location) = match self.location_table.to_location(point) {
+                    RichLocation::Start(location) => ("start", location),
+                    RichLocation::Mid(location) => ("mid", location),
+                };
+                json!({
+                    "point": point.index(),
+                    "kind": kind,
+                    "location": format!("{:?}", location),
+                    "span": location_span(location),
+                })
+            })
+            .collect();
+
+        let loans: Vec<_> = self
+            .borrow_set
+            .location_map
+            .values()
+            .enumerate()
+            .map(|(loan, borrow)| {
+                let kind = match borrow.kind {
+                    BorrowKind::Shared => "shared",
+                    BorrowKind::Shallow => "shallow",
+                    BorrowKind::Unique => "unique",
+                    BorrowKind::Mut { .. } => "mut",
+                };
+                json!({
+                    "loan": loan,
+                    "region": borrow.region.index(),
+                    "kind": kind,
+                    "borrowed_place": format!("{:?}", borrow.borrowed_place),
+                    "assigned_place": format!("{:?}", borrow.assigned_place),
+                    "location": format!("{:?}", borrow.reserve_location),
+                    "span": location_span(borrow.reserve_location),
+                })
+            })
+            .collect();
+
+        let region_spans = self.region_spans(tcx);
+        let regions: Vec<_> = self
+            .regioncx
+            .regions()
+            .map(|region| {
+                let definition = self.regioncx.region_definition(region);
+                let origin = match definition.origin {
+                    NllRegionVariableOrigin::FreeRegion => "free",
+                    NllRegionVariableOrigin::Placeholder(_) => "placeholder",
+                    NllRegionVariableOrigin::Existential { .. } => "existential",
+                };
+                let locations: Vec<_> = self
+                    .regioncx
+                    .locations_outlived_by(region)
+                    .map(|location| format!("{:?}", location))
+                    .collect();
+                let universal_regions: Vec<_> = self
+                    .regioncx
+                    .universal_regions_outlived_by(region)
+                    .map(|region| region.index())
+                    .collect();
+                let placeholders: Vec<_> = self
+                    .regioncx
+                    .placeholders_contained_in(region)
+                    .map(|placeholder| format!("{:?}", placeholder))
+                    .collect();
+                json!({
+                    "region": region.index(),
+                    "origin": origin,
+                    "name": definition.external_name.map(|name| name.to_string()),
+                    "span": region_spans.get(&region).map(|&span| span_to_json(tcx, span)),
+                    "locations": locations,
+                    "universal_regions": universal_regions,
+                    "placeholders": placeholders,
+                })
+            })
+            .collect();
+
+        let mut local_names = FxHashMap::default();
+        for var_debug_info in &body.var_debug_info {
+            if let VarDebugInfoContents::Place(place) = var_debug_info.value
+                && let Some(local) = place.as_local()
+            {
+                local_names.entry(local).or_insert(var_debug_info.name);
+            }
+        }
+        let locals: Vec<_> = body
+            .local_decls
+            .iter_enumerated()
+            .map(|(local, decl)| {
+                json!({
+                    "local": local.index(),
+                    "name": local_names.get(&local).map(|name| name.as_str()),
+                    "ty": decl.ty.to_string(),
+                    "span": span_to_json(tcx, decl.source_info.span),
+                })
+            })
+            .collect();
+
+        let errors: Vec<_> = self
+            .errors
+            .iter()
+            .map(|error| {
+                let primary_spans: Vec<_> =
+                    error.primary_spans.iter().map(|&span| span_to_json(tcx, span)).collect();
+                let labels: Vec<_> = error
+                    .labels
+                    .iter()
+                    .map(|(span, label)| {
+                        json!({
+                            "span": span_to_json(tcx, *span),
+                            "label": label,
+                        })
+                    })
+                    .collect();
+                json!({
+                    "level": error.level,
+                    "code": error.code,
+                    "message": error.message,
+                    "primary_spans": primary_spans,
+                    "labels": labels,
+                })
+            })
+            .collect();
+
+        json!({
+            "version": FORMAT_VERSION,
+            "def_path": tcx.def_path_str(body.source.def_id()),
+            "span": span_to_json(tcx, body.span),
+            "points": points,
+            "loans": loans,
+            "regions": regions,
+            "locals": locals,
+            "input_facts": input_facts_to_json(self.input_facts),
+            "errors": errors,
+        })
+    }
+
+    /// Finds a source span for the regions that have one: the declaration of the named universal
+    /// regions, the borrow expression of the regions of loans, and otherwise the declaration of
+    /// the first local whose type contains the region.
+    fn region_spans(&self, tcx: TyCtxt<'tcx>) -> FxHashMap<RegionVid, Span> {
+        let mut spans = FxHashMap::default();
+        for region in self.regioncx.regions() {
+            let Some(name) = self.regioncx.region_definition(region).external_name else {
+                continue;
+            };
+            let def_id = match *name {
+                ty::ReEarlyBound(region) => region.def_id,
+                ty::ReFree(ty::FreeRegion {
+                    bound_region: ty::BoundRegionKind::BrNamed(def_id, _),
+                    ..
+                }) => def_id,
+                _ => continue,
+            };
+            spans.insert(region, tcx.def_span(def_id));
+        }
+
+        for borrow in self.borrow_set.location_map.values() {
+            spans
+                .entry(borrow.region)
+                .or_insert(self.body.source_info(borrow.reserve_location).span);
+        }
+
+        for decl in &self.body.local_decls {
+            tcx.for_each_free_region(&decl.ty, |region| {
+                if let ty::ReVar(region) = *region {
+                    spans.entry(region).or_insert(decl.source_info.span);
+                }
+            });
+        }
+        spans
+    }
+}
+
+/// Writes each relation of the facts as an array of rows, each row being an array of the indices
+/// of its atoms.
+fn input_facts_to_json(facts: &AllFacts) -> Value {
+    macro_rules! relations_to_json {
+        ($facts:ident . [$($field:ident,)*]) => {
+            json!({
+                $(
+                    stringify!($field): $facts.$field.iter().map(FactRow::to_json).collect::<Vec<_>>(),
+                )*
+            })
+        }
+    }
+    relations_to_json! {
+        facts.[
+            loan_issued_at,
+            universal_region,
+            cfg_edge,
+            loan_killed_at,
+            subset_base,
+            loan_invalidated_at,
+            var_used_at,
+            var_defined_at,
+            var_dropped_at,
+            use_of_var_derefs_origin,
+            drop_of_var_derefs_origin,
+            child_path,
+            path_is_var,
+            path_assigned_at_base,
+            path_moved_at_base,
+            path_accessed_at_base,
+            known_placeholder_subset,
+            placeholder,
+        ]
+    }
+}
+
+trait FactRow {
+    fn to_json(&self) -> Value;
+}
+
+impl FactRow for RegionVid {
+    fn to_json(&self) -> Value {
+        json!([Atom::index(*self)])
+    }
+}
+
+impl<A: Atom, B: Atom> FactRow for (A, B) {
+    fn to_json(&self) -> Value {
+        json!([self.0.index(), self.1.index()])
+    }
+}
+
+impl<A: Atom, B: Atom, C: Atom> FactRow for (A, B, C) {
+    fn to_json(&self) -> Value {
+        json!([self.0.index(), self.1.index(), self.2.index()])
+    }
+}
@@ -33,6 +33,7 @@
 use rustc_middle::mir::{InlineAsmOperand, Terminator, TerminatorKind};
 use rustc_middle::ty::query::Providers;
 use rustc_middle::ty::{self, CapturedPlace, ParamEnv, RegionVid, TyCtxt};
+use rustc_session::config::NllFactsFormat;
 use rustc_session::lint::builtin::UNUSED_MUT;
 use rustc_span::{Span, Symbol};
 
@@ -40,6 +41,7 @@
 use smallvec::SmallVec;
 use std::cell::RefCell;
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use rustc_mir_dataflow::impls::{
@@ -54,6 +56,7 @@
 use self::location::LocationTable;
 use self::prefixes::PrefixSet;
 use facts::AllFacts;
+use facts_json::{BorrowckDiagnostic, FactsJson};
 
 use self::path_utils::*;
 
@@ -65,6 +68,7 @@
 mod def_use;
 mod diagnostics;
 mod facts;
+mod facts_json;
 mod invalidation;
 mod location;
 mod member_constraints;
@@ -180,6 +184,11 @@ fn do_mir_borrowck<'a, 'tcx>(
     }
 
     let mut errors = error::BorrowckErrors::new();
+    let facts_json_enabled = tcx.sess.opts.unstable_opts.nll_facts
+        && tcx.sess.opts.unstable_opts.nll_facts_format == NllFactsFormat::Json;
+    if return_body_with_facts || facts_json_enabled {
+        errors.record_emitted();
+    }
 
     // Gather the upvars of a closure, if any.
     let tables = tcx.typeck_opt_const_arg(def);
@@ -438,6 +447,26 @@ fn do_mir_borrowck<'a, 'tcx>(
     }
 
     let tainted_by_errors = mbcx.emit_errors();
+    let emitted_errors = mbcx.take_emitted_errors();
+
+    if nll::explain_borrowck_enabled(tcx, def.did.to_def_id()) {
+        nll::dump_explain_borrowck(infcx, body, &regioncx);
+    }
+
+    if facts_json_enabled {
+        let facts_json = FactsJson {
+            body,
+            location_table,
+            borrow_set: &borrow_set,
+            regioncx: &regioncx,
+            input_facts: polonius_input.as_deref().expect("NLL facts were not generated"),
+            errors: &emitted_errors,
+        };
+        let def_path = tcx.def_path(def.did.to_def_id());
+        let dir_path = PathBuf::from(&tcx.sess.opts.unstable_opts.nll_facts_dir)
+            .join(def_path.to_filename_friendly_no_crate());
+        facts_json.write_to_dir(tcx, dir_path).unwrap();
+    }
 
     let result = BorrowCheckResult {
         concrete_opaque_types: opaque_type_values,
@@ -453,6 +482,9 @@ fn do_mir_borrowck<'a, 'tcx>(
             input_facts: *polonius_input.expect("Polonius input facts were not generated"),
             output_facts,
             location_table: location_table_owned,
+            borrow_set,
+            region_inference_context: regioncx,
+            errors: emitted_errors,
         }))
     } else {
         None
@@ -477,6 +509,12 @@ pub struct BodyWithBorrowckFacts<'tcx> {
     pub output_facts: Rc<self::nll::PoloniusOutput>,
     /// The table that maps Polonius points to locations in the table.
     pub location_table: LocationTable,
+    /// The borrows of the body, indexed by the Polonius loans.
+    pub borrow_set: Rc<BorrowSet<'tcx>>,
+    /// The region inference context, with the values inferred for each region.
+    pub region_inference_context: Rc<RegionInferenceContext<'tcx>>,
+    /// The errors reported by the borrow checker on the body.
+    pub errors: Vec<BorrowckDiagnostic>,
 }
 
 struct MirBorrowckCtxt<'cx, 'tcx> {
@@ -2277,6 +2315,8 @@ pub struct BorrowckErrors<'tcx> {
         buffered: Vec<Diagnostic>,
         /// Set to Some if we emit an error during borrowck
         tainted_by_errors: Option<ErrorGuaranteed>,
+        /// The diagnostics emitted so far, if they are recorded for the borrowck facts.
+        emitted: Option<Vec<BorrowckDiagnostic>>,
     }
 
     impl BorrowckErrors<'_> {
@@ -2285,9 +2325,15 @@ pub fn new() -> Self {
                 buffered_move_errors: BTreeMap::new(),
                 buffered: Default::default(),
                 tainted_by_errors: None,
+                emitted: None,
             }
         }
 
+        /// Records the diagnostics emitted from now on, see `take_emitted_errors`.
+        pub fn record_emitted(&mut self) {
+            self.emitted = Some(Vec::new());
+        }
+
         // FIXME(eddyb) this is a suboptimal API because `tainted_by_errors` is
         // set before any emission actually happens (weakening the guarantee).
         pub fn buffer_error(&mut self, t: DiagnosticBuilder<'_, ErrorGuaranteed>) {
@@ -2340,6 +2386,9 @@ pub fn emit_errors(&mut self) -> Option<ErrorGuaranteed> {
                 self.errors.buffered.sort_by_key(|diag| diag.sort_span);
 
                 for mut diag in self.errors.buffered.drain(..) {
+                    if let Some(emitted) = &mut self.errors.emitted {
+                        emitted.push(BorrowckDiagnostic::new(&diag));
+                    }
                     self.infcx.tcx.sess.diagnostic().emit_diagnostic(&mut diag);
                 }
             }
@@ -2347,6 +2396,11 @@ pub fn emit_errors(&mut self) -> Option<ErrorGuaranteed> {
             self.errors.tainted_by_errors
         }
 
+        /// Returns the diagnostics emitted since `record_emitted` was called.
+        pub fn take_emitted_errors(&mut self) -> Vec<BorrowckDiagnostic> {
+            self.errors.emitted.take().unwrap_or_default()
+        }
+
         pub fn has_buffered_errors(&self) -> bool {
             self.errors.buffered.is_empty()
         }
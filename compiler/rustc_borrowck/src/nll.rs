@@ -1,7 +1,7 @@
 //! The entry point of the NLL borrow checker.
 
 use rustc_data_structures::vec_map::VecMap;
-use rustc_hir::def_id::LocalDefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_index::vec::IndexVec;
 use rustc_infer::infer::InferCtxt;
 use rustc_middle::mir::{create_dump_file, dump_enabled, dump_mir, PassWhere};
@@ -9,7 +9,8 @@
     BasicBlock, Body, ClosureOutlivesSubject, ClosureRegionRequirements, LocalKind, Location,
     Promoted,
 };
-use rustc_middle::ty::{self, OpaqueHiddenType, Region, RegionVid};
+use rustc_middle::ty::{self, OpaqueHiddenType, Region, RegionVid, TyCtxt};
+use rustc_session::config::NllFactsFormat;
 use rustc_span::symbol::sym;
 use std::env;
 use std::fmt::Debug;
@@ -270,6 +271,9 @@ pub(crate) fn compute_regions<'cx, 'tcx>(
         liveness_constraints,
         elements,
     );
+    if explain_borrowck_enabled(infcx.tcx, body.source.def_id()) {
+        regioncx.record_blamed_paths();
+    }
 
     // Generate various additional constraints.
     invalidation::generate_invalidates(infcx.tcx, &mut all_facts, location_table, body, borrow_set);
@@ -278,7 +282,10 @@ pub(crate) fn compute_regions<'cx, 'tcx>(
 
     // Dump facts if requested.
     let polonius_output = all_facts.as_ref().and_then(|all_facts| {
-        if infcx.tcx.sess.opts.unstable_opts.nll_facts {
+        // The JSON document is written once the errors are known, by `do_mir_borrowck`.
+        if infcx.tcx.sess.opts.unstable_opts.nll_facts
+            && infcx.tcx.sess.opts.unstable_opts.nll_facts_format == NllFactsFormat::Tsv
+        {
             let def_path = infcx.tcx.def_path(def_id);
             let dir_path = PathBuf::from(&infcx.tcx.sess.opts.unstable_opts.nll_facts_dir)
                 .join(def_path.to_filename_friendly_no_crate());
@@ -368,6 +375,31 @@ pub(super) fn dump_mir_results<'a, 'tcx>(
     };
 }
 
+/// Whether `-Z explain-borrowck` asks for the constraint graph of the body of `def_id`. The
+/// bodies of closures and async blocks are explained along with the function they are in.
+pub(super) fn explain_borrowck_enabled(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    tcx.sess
+        .opts
+        .unstable_opts
+        .explain_borrowck
+        .as_ref()
+        .map_or(false, |path| tcx.def_path_str(tcx.typeck_root_def_id(def_id)) == *path)
+}
+
+/// Writes the constraint graph of `-Z explain-borrowck`, once the errors of the body have been
+/// reported.
+pub(super) fn dump_explain_borrowck<'tcx>(
+    infcx: &InferCtxt<'_, 'tcx>,
+    body: &Body<'tcx>,
+    regioncx: &RegionInferenceContext<'tcx>,
+) {
+    let _: io::Result<()> = try {
+        let mut file =
+            create_dump_file(infcx.tcx, "explain-borrowck.dot", None, "nll", &0, body.source)?;
+        regioncx.dump_graphviz_blamed_constraints(infcx.tcx, &mut file)?;
+    };
+}
+
 pub(super) fn dump_annotation<'a, 'tcx>(
     infcx: &InferCtxt<'a, 'tcx>,
     body: &Body<'tcx>,
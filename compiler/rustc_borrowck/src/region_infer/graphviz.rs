@@ -27,6 +27,49 @@ pub(crate) fn dump_graphviz_scc_constraints(&self, mut w: &mut dyn Write) -> io:
 
         dot::render(&SccConstraints { regioncx: self, nodes_per_scc }, &mut w)
     }
+
+    /// Write out the region constraint graph for `-Z explain-borrowck`, with
+    /// the constraint paths blamed for errors in bold, and each constraint
+    /// labeled with its category and span.
+    pub(crate) fn dump_graphviz_blamed_constraints(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mut w: &mut dyn Write,
+    ) -> io::Result<()> {
+        let mut edges: Vec<_> = self
+            .constraints
+            .outlives()
+            .iter()
+            .map(|constraint| ExplainedConstraint {
+                constraint: constraint.clone(),
+                on_blamed_path: false,
+                blamed: false,
+            })
+            .collect();
+
+        let blamed_paths = self.blamed_paths.as_ref().map(|paths| paths.borrow());
+        for BlamedPath { path, blamed } in blamed_paths.iter().flat_map(|paths| paths.iter()) {
+            for (i, constraint) in path.iter().enumerate() {
+                let edge = match edges.iter().position(|edge| edge.constraint == *constraint) {
+                    Some(edge) => edge,
+                    // The paths also go through implied constraints which are not part of the
+                    // constraint set, like `'static` outliving every region.
+                    None => {
+                        edges.push(ExplainedConstraint {
+                            constraint: constraint.clone(),
+                            on_blamed_path: false,
+                            blamed: false,
+                        });
+                        edges.len() - 1
+                    }
+                };
+                edges[edge].on_blamed_path = true;
+                edges[edge].blamed |= i == *blamed;
+            }
+        }
+
+        dot::render(&BlamedConstraints { tcx, regioncx: self, edges }, &mut w)
+    }
 }
 
 struct RawConstraints<'a, 'tcx> {
@@ -138,3 +181,71 @@ fn target(&'this self, edge: &(ConstraintSccIndex, ConstraintSccIndex)) -> Const
         edge.1
     }
 }
+
+struct ExplainedConstraint<'tcx> {
+    constraint: OutlivesConstraint<'tcx>,
+    on_blamed_path: bool,
+    /// Whether the error was reported at this constraint.
+    blamed: bool,
+}
+
+struct BlamedConstraints<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    regioncx: &'a RegionInferenceContext<'tcx>,
+    edges: Vec<ExplainedConstraint<'tcx>>,
+}
+
+impl<'a, 'this, 'tcx> dot::Labeller<'this> for BlamedConstraints<'a, 'tcx> {
+    type Node = RegionVid;
+    type Edge = usize;
+
+    fn graph_id(&'this self) -> dot::Id<'this> {
+        dot::Id::new("RegionInferenceContext").unwrap()
+    }
+    fn node_id(&'this self, n: &RegionVid) -> dot::Id<'this> {
+        dot::Id::new(format!("r{}", n.index())).unwrap()
+    }
+    fn node_shape(&'this self, _node: &RegionVid) -> Option<dot::LabelText<'this>> {
+        Some(dot::LabelText::LabelStr(Cow::Borrowed("box")))
+    }
+    fn node_label(&'this self, n: &RegionVid) -> dot::LabelText<'this> {
+        let label = match self.regioncx.definitions[*n].external_name {
+            Some(name) => format!("{:?} ({})", n, name),
+            None => format!("{:?}", n),
+        };
+        dot::LabelText::LabelStr(label.into())
+    }
+    fn edge_label(&'this self, e: &usize) -> dot::LabelText<'this> {
+        let ExplainedConstraint { constraint, blamed, .. } = &self.edges[*e];
+        let span = self.tcx.sess.source_map().span_to_embeddable_string(constraint.span);
+        let blamed = if *blamed { "blamed: " } else { "" };
+        dot::LabelText::LabelStr(format!("{}{:?}\n{}", blamed, constraint.category, span).into())
+    }
+    fn edge_style(&'this self, e: &usize) -> dot::Style {
+        if self.edges[*e].on_blamed_path { dot::Style::Bold } else { dot::Style::Dotted }
+    }
+}
+
+impl<'a, 'this, 'tcx> dot::GraphWalk<'this> for BlamedConstraints<'a, 'tcx> {
+    type Node = RegionVid;
+    type Edge = usize;
+
+    fn nodes(&'this self) -> dot::Nodes<'this, RegionVid> {
+        let vids: Vec<RegionVid> = self.regioncx.definitions.indices().collect();
+        vids.into()
+    }
+    fn edges(&'this self) -> dot::Edges<'this, usize> {
+        (0..self.edges.len()).collect()
+    }
+
+    // Render `a: b` as `a -> b`, indicating the flow
+    // of data during inference.
+
+    fn source(&'this self, edge: &usize) -> RegionVid {
+        self.edges[*edge].constraint.sup
+    }
+
+    fn target(&'this self, edge: &usize) -> RegionVid {
+        self.edges[*edge].constraint.sub
+    }
+}
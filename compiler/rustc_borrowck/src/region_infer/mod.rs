@@ -1,3 +1,4 @@
+use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::rc::Rc;
 
@@ -125,6 +126,21 @@ pub struct RegionInferenceContext<'tcx> {
     /// Information about how the universally quantified regions in
     /// scope on this function relate to one another.
     universal_region_relations: Frozen<UniversalRegionRelations<'tcx>>,
+
+    /// The constraint paths found by `best_blame_constraint_for_error` while
+    /// reporting errors, which `-Z explain-borrowck` highlights in the
+    /// constraint graph.
+    /// `None` unless `record_blamed_paths` was called.
+    blamed_paths: Option<RefCell<Vec<BlamedPath<'tcx>>>>,
+}
+
+/// A path of constraints forcing a region to outlive another one, and the
+/// constraint of the path that was blamed for it.
+#[derive(Clone, Debug)]
+pub(crate) struct BlamedPath<'tcx> {
+    pub(crate) path: Vec<OutlivesConstraint<'tcx>>,
+    /// The index in `path` of the blamed constraint.
+    pub(crate) blamed: usize,
 }
 
 /// Each time that `apply_member_constraint` is successful, it appends
@@ -314,6 +330,7 @@ pub(crate) fn new(
             type_tests,
             universal_regions,
             universal_region_relations,
+            blamed_paths: None,
         };
 
         result.init_free_and_bound_regions();
@@ -534,6 +551,36 @@ pub(crate) fn region_value_str(&self, r: RegionVid) -> String {
         self.scc_values.region_value_str(scc)
     }
 
+    /// Returns the locations contained in the value of `r`.
+    ///
+    /// Panics if called before `solve()` executes.
+    pub fn locations_outlived_by(&self, r: RegionVid) -> impl Iterator<Item = Location> + '_ {
+        let scc = self.constraint_sccs.scc(r);
+        self.scc_values.locations_outlived_by(scc)
+    }
+
+    /// Returns the universal regions contained in the value of `r`.
+    ///
+    /// Panics if called before `solve()` executes.
+    pub fn universal_regions_outlived_by(
+        &self,
+        r: RegionVid,
+    ) -> impl Iterator<Item = RegionVid> + '_ {
+        let scc = self.constraint_sccs.scc(r);
+        self.scc_values.universal_regions_outlived_by(scc)
+    }
+
+    /// Returns the placeholders contained in the value of `r`.
+    ///
+    /// Panics if called before `solve()` executes.
+    pub fn placeholders_contained_in(
+        &self,
+        r: RegionVid,
+    ) -> impl Iterator<Item = ty::PlaceholderRegion> + '_ {
+        let scc = self.constraint_sccs.scc(r);
+        self.scc_values.placeholders_contained_in(scc)
+    }
+
     /// Returns access to the value of `r` for debugging purposes.
     pub(crate) fn region_universe(&self, r: RegionVid) -> ty::UniverseIndex {
         let scc = self.constraint_sccs.scc(r.to_region_vid());
@@ -2055,6 +2102,34 @@ pub(crate) fn best_blame_constraint(
         from_region_origin: NllRegionVariableOrigin,
         target_test: impl Fn(RegionVid) -> bool,
     ) -> BlameConstraint<'tcx> {
+        self.best_blame_constraint_and_path(body, from_region, from_region_origin, target_test).0
+    }
+
+    /// Like `best_blame_constraint`, for the constraint reported in an error:
+    /// its path is recorded for `-Z explain-borrowck`, see `blamed_paths`.
+    pub(crate) fn best_blame_constraint_for_error(
+        &self,
+        body: &Body<'tcx>,
+        from_region: RegionVid,
+        from_region_origin: NllRegionVariableOrigin,
+        target_test: impl Fn(RegionVid) -> bool,
+    ) -> BlameConstraint<'tcx> {
+        let (blame, blamed_path) =
+            self.best_blame_constraint_and_path(body, from_region, from_region_origin, target_test);
+        if let Some(blamed_paths) = &self.blamed_paths {
+            blamed_paths.borrow_mut().push(blamed_path);
+        }
+        blame
+    }
+
+    /// Returns the best constraint to blame, and the path it was found in.
+    fn best_blame_constraint_and_path(
+        &self,
+        body: &Body<'tcx>,
+        from_region: RegionVid,
+        from_region_origin: NllRegionVariableOrigin,
+        target_test: impl Fn(RegionVid) -> bool,
+    ) -> (BlameConstraint<'tcx>, BlamedPath<'tcx>) {
         debug!(
             "best_blame_constraint(from_region={:?}, from_region_origin={:?})",
             from_region, from_region_origin
@@ -2226,7 +2301,7 @@ pub(crate) fn best_blame_constraint(
                 {
                     // The return expression is being influenced by the return type being
                     // impl Trait, point at the return type and not the return expr.
-                    return next.clone();
+                    return (next.clone(), BlamedPath { path, blamed: i + 1 });
                 }
             }
 
@@ -2246,17 +2321,24 @@ pub(crate) fn best_blame_constraint(
                 }
             }
 
-            return categorized_path[i].clone();
+            return (categorized_path[i].clone(), BlamedPath { path, blamed: i });
         }
 
         // If that search fails, that is.. unusual. Maybe everything
         // is in the same SCC or something. In that case, find what
         // appears to be the most interesting point to report to the
         // user via an even more ad-hoc guess.
+        let blamed = (0..path.len()).min_by_key(|&i| categorized_path[i].category).unwrap();
         categorized_path.sort_by(|p0, p1| p0.category.cmp(&p1.category));
         debug!("best_blame_constraint: sorted_path={:#?}", categorized_path);
 
-        categorized_path.remove(0)
+        (categorized_path.remove(0), BlamedPath { path, blamed })
+    }
+
+    /// Starts recording the constraint paths blamed by `best_blame_constraint_for_error`,
+    /// see `blamed_paths`.
+    pub(crate) fn record_blamed_paths(&mut self) {
+        self.blamed_paths = Some(RefCell::new(Vec::new()));
     }
 
     pub(crate) fn universe_info(&self, universe: ty::UniverseIndex) -> UniverseInfo<'tcx> {
@@ -0,0 +1,92 @@
+#![feature(asm_const, asm_sym)]
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+use std::arch::asm;
+
+static STATIC: u64 = 42;
+
+extern "C" fn add_one(x: u64) -> u64 {
+    x + 1
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn main() {
+    // `inout` with different input and output types
+    let ptr: *const u8;
+    let low: u32;
+    unsafe {
+        asm!("add {0}, 1", inout(reg) 0x1000u64 => ptr);
+        asm!("add {0}, 1", inout(reg) 0x1_0000_0005u64 => low);
+    }
+    assert_eq!(ptr as usize, 0x1001);
+    assert_eq!(low, 6);
+
+    // `const` operands
+    let seven: u64;
+    unsafe {
+        asm!("mov {0}, {1}", out(reg) seven, const 3 + 4);
+    }
+    assert_eq!(seven, 7);
+
+    // `sym` operands
+    let two: u64;
+    let static_value: u64;
+    unsafe {
+        asm!("call {f}", f = sym add_one, inout("rdi") 1u64 => _, lateout("rax") two, clobber_abi("C"));
+        asm!("mov {0}, [rip + {s}]", out(reg) static_value, s = sym STATIC);
+    }
+    assert_eq!(two, 2);
+    assert_eq!(static_value, 42);
+
+    // `pure` and `nomem` options
+    let doubled: u64;
+    unsafe {
+        asm!("lea {0}, [{1} + {1}]", out(reg) doubled, in(reg) 21u64, options(pure, nomem, nostack));
+    }
+    assert_eq!(doubled, 42);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+fn main() {
+    // `inout` with different input and output types
+    let ptr: *const u8;
+    let low: u32;
+    unsafe {
+        asm!("add {0}, {0}, #1", inout(reg) 0x1000u64 => ptr);
+        asm!("add {0}, {0}, #1", inout(reg) 0x1_0000_0005u64 => low);
+    }
+    assert_eq!(ptr as usize, 0x1001);
+    assert_eq!(low, 6);
+
+    // `const` operands
+    let seven: u64;
+    unsafe {
+        asm!("mov {0}, #{1}", out(reg) seven, const 3 + 4);
+    }
+    assert_eq!(seven, 7);
+
+    // `sym` operands
+    let two: u64;
+    let static_value: u64;
+    unsafe {
+        asm!("bl {f}", f = sym add_one, inout("x0") 1u64 => two, clobber_abi("C"));
+        asm!(
+            "adrp {0}, {s}",
+            "ldr {0}, [{0}, :lo12:{s}]",
+            out(reg) static_value,
+            s = sym STATIC,
+        );
+    }
+    assert_eq!(two, 2);
+    assert_eq!(static_value, 42);
+
+    // `pure` and `nomem` options
+    let doubled: u64;
+    unsafe {
+        asm!("add {0}, {1}, {1}", out(reg) doubled, in(reg) 21u64, options(pure, nomem, nostack));
+    }
+    assert_eq!(doubled, 42);
+}
+
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+fn main() {}
@@ -109,6 +109,10 @@ function base_sysroot_tests() {
     $MY_RUSTC example/float-minmax-pass.rs --crate-type bin -Cpanic=abort --target "$TARGET_TRIPLE"
     $RUN_WRAPPER ./target/out/float-minmax-pass
 
+    echo "[AOT] inline-asm"
+    $MY_RUSTC example/inline-asm.rs --crate-name inline_asm --crate-type bin --target "$TARGET_TRIPLE"
+    $RUN_WRAPPER ./target/out/inline_asm
+
     echo "[AOT] mod_bench"
     $MY_RUSTC example/mod_bench.rs --crate-type bin --target "$TARGET_TRIPLE"
     $RUN_WRAPPER ./target/out/mod_bench
@@ -1,7 +1,9 @@
 //! Drivers are responsible for calling [`codegen_fn`] or [`codegen_static`] for each mono item and
-//! performing any further actions like JIT executing or writing object files.
+//! performing any further actions like JIT executing or writing object files. Functions lowered by
+//! [`codegen_fn`] still have to be compiled using [`compile_fn`].
 //!
 //! [`codegen_fn`]: crate::base::codegen_fn
+//! [`compile_fn`]: crate::base::compile_fn
 //! [`codegen_static`]: crate::constant::codegen_static
 
 use rustc_middle::mir::mono::{Linkage as RLinkage, MonoItem, Visibility};
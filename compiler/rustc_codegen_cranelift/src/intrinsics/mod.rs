@@ -1265,6 +1265,13 @@ fn swap(bcx: &mut FunctionBuilder<'_>, v: Value) -> Value {
             // nop at runtime.
         }
 
+        sym::const_make_global => {
+            intrinsic_args!(fx, args => (ptr); intrinsic);
+
+            // returns the pointer unchanged at runtime.
+            ret.write_cvalue(fx, ptr);
+        }
+
         sym::black_box => {
             intrinsic_args!(fx, args => (a); intrinsic);
 
@@ -15,6 +15,15 @@ pub(crate) fn optimize_function<'tcx>(
 ) {
     // FIXME classify optimizations over opt levels once we have more
 
-    crate::pretty_clif::write_clif_file(tcx, "preopt", isa, instance, &ctx.func, &*clif_comments);
+    if crate::pretty_clif::should_write_ir(tcx) {
+        crate::pretty_clif::write_clif_file(
+            tcx.output_filenames(()),
+            tcx.symbol_name(instance).name,
+            "preopt",
+            isa,
+            &ctx.func,
+            &*clif_comments,
+        );
+    }
     crate::base::verify_func(tcx, &*clif_comments, &ctx.func);
 }
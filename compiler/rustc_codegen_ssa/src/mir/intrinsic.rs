@@ -393,6 +393,11 @@ pub fn codegen_intrinsic_call(
                 return;
             }
 
+            sym::const_make_global => {
+                // returns the pointer unchanged at runtime.
+                args[0].immediate()
+            }
+
             // This requires that atomic intrinsics follow a specific naming pattern:
             // "atomic_<operation>[_<ordering>]"
             name if let Some(atomic) = name_str.strip_prefix("atomic_") => {
@@ -1,3 +1,4 @@
+use super::trace::EvalTrace;
 use super::{CompileTimeEvalContext, CompileTimeInterpreter, ConstEvalErr};
 use crate::interpret::eval_nullary_intrinsic;
 use crate::interpret::{
@@ -302,9 +303,19 @@ pub fn eval_to_allocation_raw_provider<'tcx>(
         // they do not have to behave "as if" they were evaluated at runtime.
         CompileTimeInterpreter::new(tcx.const_eval_limit(), /*can_access_statics:*/ is_static),
     );
+    if tcx.sess.opts.unstable_opts.const_eval_trace {
+        ecx.machine.trace = Some(EvalTrace::new());
+    }
 
     let res = ecx.load_mir(cid.instance.def, cid.promoted);
-    match res.and_then(|body| eval_body_using_ecx(&mut ecx, cid, &body)) {
+    let span = res.as_ref().map_or(tcx.def_span(def.did), |body| body.span);
+    let res = res.and_then(|body| eval_body_using_ecx(&mut ecx, cid, &body));
+    if let Some(trace) = ecx.machine.trace.take() {
+        // The limit is only reached when it is not disabled with a value of `0`.
+        let hit_limit = tcx.const_eval_limit().0 != 0 && ecx.machine.steps_remaining == 0;
+        tcx.sess.code_stats.record_const_eval_trace(trace.finish(tcx, cid, span, hit_limit));
+    }
+    match res {
         Err(error) => {
             let err = ConstEvalErr::new(&ecx, error, None);
             // Some CTFE errors raise just a lint, not a hard error; see
@@ -18,10 +18,11 @@
 
 use crate::interpret::{
     self, compile_time_machine, AllocId, ConstAllocation, Frame, ImmTy, InterpCx, InterpResult,
-    OpTy, PlaceTy, Pointer, Scalar, StackPopUnwind,
+    OpTy, PlaceTy, Pointer, Scalar, StackPopJump, StackPopUnwind,
 };
 
 use super::error::*;
+use super::trace::EvalTrace;
 
 impl<'mir, 'tcx> InterpCx<'mir, 'tcx, CompileTimeInterpreter<'mir, 'tcx>> {
     /// "Intercept" a function call to a panic-related function
@@ -101,6 +102,9 @@ pub struct CompileTimeInterpreter<'mir, 'tcx> {
     /// * Pointers to allocations inside of statics can never leak outside, to a non-static global.
     /// This boolean here controls the second part.
     pub(super) can_access_statics: bool,
+
+    /// The statistics collected about the evaluation with `-Zconst-eval-trace`.
+    pub(super) trace: Option<EvalTrace<'tcx>>,
 }
 
 impl<'mir, 'tcx> CompileTimeInterpreter<'mir, 'tcx> {
@@ -109,6 +113,7 @@ pub(crate) fn new(const_eval_limit: Limit, can_access_statics: bool) -> Self {
             steps_remaining: const_eval_limit.0,
             stack: Vec::new(),
             can_access_statics,
+            trace: None,
         }
     }
 }
@@ -391,6 +396,30 @@ fn call_intrinsic(
                     )?;
                 }
             }
+            sym::const_make_global => {
+                let ptr = ecx.read_pointer(&args[0])?;
+                let (alloc_id, offset, _) = ecx.ptr_get_alloc_id(ptr)?;
+                if offset.bytes() != 0 {
+                    throw_ub_format!(
+                        "making {:?} global, which does not point to the beginning of an object",
+                        ptr
+                    );
+                }
+                if ecx.get_alloc_kind(alloc_id)
+                    != Some(interpret::MemoryKind::Machine(MemoryKind::Heap))
+                {
+                    throw_ub_format!(
+                        "making {alloc_id:?} global, which is not a heap allocation of this evaluation"
+                    );
+                }
+                // Interning recognizes the heap allocations made global by their mutability, see
+                // `intern_const_alloc_recursive`. Writing to or deallocating them is now an error.
+                if ecx.get_alloc_mutability(alloc_id)? == Mutability::Not {
+                    throw_ub_format!("making {alloc_id:?} global, which is already global");
+                }
+                ecx.alloc_mark_immutable(alloc_id)?;
+                ecx.write_pointer(ptr, dest)?;
+            }
             _ => {
                 return Err(ConstEvalErrKind::NeedsRfc(format!(
                     "calling intrinsic `{}`",
@@ -443,6 +472,10 @@ fn binary_ptr_op(
     }
 
     fn before_terminator(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx> {
+        if let Some(trace) = &mut ecx.machine.trace {
+            trace.step();
+        }
+
         // The step limit has already been hit in a previous call to `before_terminator`.
         if ecx.machine.steps_remaining == 0 {
             return Ok(());
@@ -477,6 +510,40 @@ fn init_frame_extra(
         }
     }
 
+    fn after_stack_push(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx> {
+        let machine = &mut ecx.machine;
+        if let Some(trace) = &mut machine.trace {
+            let (frame, callers) = machine.stack.split_last().unwrap();
+            let span = callers.last().map_or(frame.body.span, |caller| caller.current_span());
+            trace.enter(frame.instance, span);
+        }
+        Ok(())
+    }
+
+    fn after_stack_pop(
+        ecx: &mut InterpCx<'mir, 'tcx, Self>,
+        _frame: Frame<'mir, 'tcx>,
+        unwinding: bool,
+    ) -> InterpResult<'tcx, StackPopJump> {
+        // CTFE does not support unwinding.
+        assert!(!unwinding);
+        if let Some(trace) = &mut ecx.machine.trace {
+            trace.leave();
+        }
+        Ok(StackPopJump::Normal)
+    }
+
+    #[inline(always)]
+    fn after_memory_allocation(
+        machine: &mut Self,
+        size: Size,
+        _kind: interpret::MemoryKind<Self::MemoryKind>,
+    ) {
+        if let Some(trace) = &mut machine.trace {
+            trace.allocate(size.bytes());
+        }
+    }
+
     #[inline(always)]
     fn stack<'a>(
         ecx: &'a InterpCx<'mir, 'tcx, Self>,
@@ -15,6 +15,7 @@
 mod eval_queries;
 mod fn_queries;
 mod machine;
+mod trace;
 mod valtrees;
 
 pub use error::*;
@@ -0,0 +1,118 @@
+//! The statistics collected about the evaluation of a constant with `-Zconst-eval-trace`.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::mir::interpret::GlobalId;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_session::{ConstEvalFnStats, ConstEvalTrace};
+use rustc_span::Span;
+
+/// The maximum number of functions reported for each constant.
+const MAX_HOTTEST_FNS: usize = 5;
+
+/// A call of a function during the evaluation. The calls of the same function from the same call
+/// site in the same caller are merged, so that e.g. the iterations of a loop only add a single
+/// call to the tree.
+struct Call<'tcx> {
+    instance: ty::Instance<'tcx>,
+    /// The span of the call in the caller, or of the constant for the outermost call.
+    span: Span,
+    parent: Option<usize>,
+    /// The steps spent in this call, not counting the steps of the calls it makes.
+    steps: u64,
+}
+
+/// The call tree of the evaluation of a constant, with the steps spent in each call.
+pub(super) struct EvalTrace<'tcx> {
+    calls: Vec<Call<'tcx>>,
+    /// The index in `calls` of the call made by a caller to an instance at a call site.
+    callees: FxHashMap<(Option<usize>, ty::Instance<'tcx>, Span), usize>,
+    /// The call currently being evaluated, `None` outside of the outermost call.
+    current: Option<usize>,
+    bytes_allocated: u64,
+}
+
+impl<'tcx> EvalTrace<'tcx> {
+    pub(super) fn new() -> Self {
+        EvalTrace {
+            calls: Vec::new(),
+            callees: FxHashMap::default(),
+            current: None,
+            bytes_allocated: 0,
+        }
+    }
+
+    /// Called when a frame of `instance` is pushed, `span` being the span of the call.
+    pub(super) fn enter(&mut self, instance: ty::Instance<'tcx>, span: Span) {
+        let parent = self.current;
+        let calls = &mut self.calls;
+        let call = *self.callees.entry((parent, instance, span)).or_insert_with(|| {
+            calls.push(Call { instance, span, parent, steps: 0 });
+            calls.len() - 1
+        });
+        self.current = Some(call);
+    }
+
+    /// Called when the frame of the current call is popped.
+    pub(super) fn leave(&mut self) {
+        let current = self.current.expect("popped a frame outside of the evaluation");
+        self.current = self.calls[current].parent;
+    }
+
+    pub(super) fn step(&mut self) {
+        if let Some(current) = self.current {
+            self.calls[current].steps += 1;
+        }
+    }
+
+    pub(super) fn allocate(&mut self, bytes: u64) {
+        self.bytes_allocated += bytes;
+    }
+
+    pub(super) fn finish(
+        self,
+        tcx: TyCtxt<'tcx>,
+        cid: GlobalId<'tcx>,
+        span: Span,
+        hit_limit: bool,
+    ) -> ConstEvalTrace {
+        let describe = |instance: ty::Instance<'tcx>| with_no_trimmed_paths!(instance.to_string());
+
+        let mut fn_steps = FxHashMap::default();
+        for call in &self.calls {
+            *fn_steps.entry(call.instance).or_insert(0) += call.steps;
+        }
+        let mut hottest_fns: Vec<_> = fn_steps
+            .into_iter()
+            .map(|(instance, steps)| ConstEvalFnStats {
+                description: describe(instance),
+                span: tcx.def_span(instance.def_id()),
+                steps,
+            })
+            .collect();
+        hottest_fns.sort_by(|fn1, fn2| {
+            fn2.steps.cmp(&fn1.steps).then_with(|| fn1.description.cmp(&fn2.description))
+        });
+        hottest_fns.truncate(MAX_HOTTEST_FNS);
+
+        let mut hottest_stack = Vec::new();
+        let hottest_call = (0..self.calls.len()).max_by_key(|&call| self.calls[call].steps);
+        let mut call = hottest_call;
+        while let Some(index) = call {
+            let Call { instance, span, parent, steps } = self.calls[index];
+            hottest_stack.push(ConstEvalFnStats { description: describe(instance), span, steps });
+            call = parent;
+        }
+        hottest_stack.reverse();
+
+        ConstEvalTrace {
+            description: cid.display(tcx),
+            span,
+            steps: self.calls.iter().map(|call| call.steps).sum(),
+            bytes_allocated: self.bytes_allocated,
+            hit_limit,
+            hottest_fns,
+            hottest_stack,
+        }
+    }
+}
@@ -405,7 +405,7 @@ pub fn intern_const_alloc_recursive<
     debug!(?todo);
     debug!("dead_alloc_map: {:#?}", ecx.memory.dead_alloc_map);
     while let Some(alloc_id) = todo.pop() {
-        if let Some((_, mut alloc)) = ecx.memory.alloc_map.remove(&alloc_id) {
+        if let Some((kind, mut alloc)) = ecx.memory.alloc_map.remove(&alloc_id) {
             // We can't call the `intern_shallow` method here, as its logic is tailored to safe
             // references and a `leftover_allocations` set (where we only have a todo-list here).
             // So we hand-roll the interning logic here again.
@@ -427,6 +427,14 @@ pub fn intern_const_alloc_recursive<
                     // immutability is so important.
                     alloc.mutability = Mutability::Not;
                 }
+                // Memory allocated with `const_allocate` can only be reached through raw
+                // pointers, which is how `Vec`-like and `String`-like values built at compile
+                // time refer to their buffer. It is allowed once made global by
+                // `const_make_global`, which marks it as immutable: nothing can mutate it during
+                // the evaluation, and the code using the constant promised not to mutate it.
+                InternKind::Constant
+                    if kind == MemoryKind::Machine(const_eval::MemoryKind::Heap)
+                        && alloc.mutability == Mutability::Not => {}
                 InternKind::Constant => {
                     // If it's a constant, we should not have any "leftovers" as everything
                     // is tracked by const-checking.
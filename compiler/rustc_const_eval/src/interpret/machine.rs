@@ -365,6 +365,16 @@ fn before_memory_write(
         Ok(())
     }
 
+    /// Hook for performing extra operations on a new memory allocation, after it has been adjusted
+    /// by `adjust_allocation`.
+    #[inline(always)]
+    fn after_memory_allocation(
+        _machine: &mut Self,
+        _size: Size,
+        _kind: MemoryKind<Self::MemoryKind>,
+    ) {
+    }
+
     /// Hook for performing extra operations on a memory deallocation.
     #[inline(always)]
     fn before_memory_deallocation(
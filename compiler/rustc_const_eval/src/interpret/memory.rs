@@ -228,6 +228,7 @@ pub fn allocate_raw_ptr(
             "dynamically allocating global memory"
         );
         let alloc = M::adjust_allocation(self, id, Cow::Owned(alloc), Some(kind))?;
+        M::after_memory_allocation(&mut self.machine, alloc.size(), kind);
         self.memory.alloc_map.insert(id, (kind, alloc.into_owned()));
         Ok(M::adjust_alloc_base_pointer(self, Pointer::from(id)))
     }
@@ -596,6 +597,12 @@ pub fn get_alloc_mutability<'a>(&'a self, id: AllocId) -> InterpResult<'tcx, Mut
         Ok(self.get_alloc_raw(id)?.mutability)
     }
 
+    /// Return the kind of the given allocation, or `None` if it is not a live allocation of this
+    /// interpreter, e.g. because it is global or was deallocated.
+    pub fn get_alloc_kind(&self, id: AllocId) -> Option<MemoryKind<M::MemoryKind>> {
+        self.memory.alloc_map.get(id).map(|&(kind, _)| kind)
+    }
+
     /// Gives raw mutable access to the `Allocation`, without bounds or alignment checks.
     /// The caller is responsible for calling the access hooks!
     ///
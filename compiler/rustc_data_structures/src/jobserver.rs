@@ -1,4 +1,4 @@
-pub use jobserver_crate::Client;
+pub use jobserver_crate::{Acquired, Client, HelperThread};
 use std::sync::LazyLock;
 
 // We can only call `from_env` once per process
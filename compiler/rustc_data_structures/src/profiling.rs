@@ -85,18 +85,21 @@
 use crate::fx::FxHashMap;
 
 use std::borrow::Borrow;
+use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::convert::Into;
 use std::error::Error;
+use std::fmt::Write as _;
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 pub use measureme::EventId;
 use measureme::{EventIdBuilder, Profiler, SerializableString, StringId};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use smallvec::SmallVec;
 
 bitflags::bitflags! {
@@ -145,6 +148,15 @@ struct EventFilter: u32 {
 /// Something that uniquely identifies a query invocation.
 pub struct QueryInvocationId(pub u32);
 
+/// The different settings that the `-Z self-profile-summary` flag can have.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SelfProfileSummaryFormat {
+    /// Print a table of the queries with the most self time to stderr.
+    Text,
+    /// Write the statistics of all queries as JSON next to the profiling data.
+    Json,
+}
+
 /// A reference to the SelfProfiler. It can be cloned and sent across thread
 /// boundaries at will.
 #[derive(Clone)]
@@ -405,6 +417,7 @@ pub fn generic_activity_with_args(
     pub fn query_provider(&self) -> TimingGuard<'_> {
         self.exec(EventFilter::QUERY_PROVIDERS, |profiler| {
             TimingGuard::start(profiler, profiler.query_event_kind, EventId::INVALID)
+                .with_summary(profiler, SummaryEventKind::QueryProvider)
         })
     }
 
@@ -413,6 +426,7 @@ pub fn query_provider(&self) -> TimingGuard<'_> {
     pub fn query_cache_hit(&self, query_invocation_id: QueryInvocationId) {
         self.instant_query_event(
             |profiler| profiler.query_cache_hit_event_kind,
+            QuerySummary::record_cache_hit,
             query_invocation_id,
             EventFilter::QUERY_CACHE_HITS,
         );
@@ -439,6 +453,7 @@ pub fn incr_cache_loading(&self) -> TimingGuard<'_> {
                 profiler.incremental_load_result_event_kind,
                 EventId::INVALID,
             )
+            .with_summary(profiler, SummaryEventKind::IncrCacheLoad)
         })
     }
 
@@ -459,6 +474,7 @@ pub fn incr_result_hashing(&self) -> TimingGuard<'_> {
     fn instant_query_event(
         &self,
         event_kind: fn(&SelfProfiler) -> StringId,
+        summarize: fn(&QuerySummary, QueryInvocationId),
         query_invocation_id: QueryInvocationId,
         event_filter: EventFilter,
     ) {
@@ -472,6 +488,10 @@ fn instant_query_event(
                 thread_id,
             );
 
+            if let Some(summary) = &profiler.query_summary {
+                summarize(summary, query_invocation_id);
+            }
+
             TimingGuard::none()
         }));
     }
@@ -545,6 +565,9 @@ pub struct SelfProfiler {
     query_blocked_event_kind: StringId,
     query_cache_hit_event_kind: StringId,
     artifact_size_event_kind: StringId,
+
+    /// Per-query statistics collected for `-Z self-profile-summary`.
+    query_summary: Option<QuerySummary>,
 }
 
 impl SelfProfiler {
@@ -553,6 +576,7 @@ pub fn new(
         crate_name: Option<&str>,
         event_filters: Option<&[String]>,
         counter_name: &str,
+        summary_format: Option<SelfProfileSummaryFormat>,
     ) -> Result<SelfProfiler, Box<dyn Error + Send + Sync>> {
         fs::create_dir_all(output_directory)?;
 
@@ -608,6 +632,12 @@ pub fn new(
             event_filter_mask = EventFilter::DEFAULT;
         }
 
+        // The summary reports cache hits, which aren't recorded by default.
+        let query_summary = summary_format.map(|format| {
+            event_filter_mask |= EventFilter::QUERY_CACHE_HITS;
+            QuerySummary::new(format, path.with_extension("summary.json"))
+        });
+
         Ok(SelfProfiler {
             profiler,
             event_filter_mask,
@@ -619,6 +649,7 @@ pub fn new(
             query_blocked_event_kind,
             query_cache_hit_event_kind,
             artifact_size_event_kind,
+            query_summary,
         })
     }
 
@@ -670,6 +701,33 @@ pub fn bulk_map_query_invocation_id_to_single_string<I>(&self, from: I, to: Stri
         self.profiler.bulk_map_virtual_to_single_concrete_string(from, to);
     }
 
+    /// Associates query invocations with the name of their query, so that
+    /// `-Z self-profile-summary` can aggregate them. Does nothing if no summary
+    /// was requested.
+    pub fn map_query_invocation_ids_to_query_name<I>(&self, from: I, query_name: &'static str)
+    where
+        I: Iterator<Item = QueryInvocationId>,
+    {
+        if let Some(summary) = &self.query_summary {
+            summary.map_query_invocation_ids_to_query_name(from, query_name);
+        }
+    }
+
+    #[inline]
+    pub fn query_summary_enabled(&self) -> bool {
+        self.query_summary.is_some()
+    }
+
+    /// Prints or writes the summary requested with `-Z self-profile-summary`.
+    /// Must be called after the query invocations have been mapped to query
+    /// names, anything executed afterwards isn't part of the summary.
+    pub fn write_query_summary(&self) -> io::Result<()> {
+        match &self.query_summary {
+            Some(summary) => summary.write(),
+            None => Ok(()),
+        }
+    }
+
     pub fn query_key_recording_enabled(&self) -> bool {
         self.event_filter_mask.contains(EventFilter::QUERY_KEYS)
     }
@@ -680,7 +738,7 @@ pub fn event_id_builder(&self) -> EventIdBuilder<'_> {
 }
 
 #[must_use]
-pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a>>);
+pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a>>, Option<SummaryTimer<'a>>);
 
 impl<'a> TimingGuard<'a> {
     #[inline]
@@ -693,23 +751,37 @@ pub fn start(
         let raw_profiler = &profiler.profiler;
         let timing_guard =
             raw_profiler.start_recording_interval_event(event_kind, event_id, thread_id);
-        TimingGuard(Some(timing_guard))
+        TimingGuard(Some(timing_guard), None)
+    }
+
+    /// Also measures the event for `-Z self-profile-summary`, if requested.
+    #[inline]
+    fn with_summary(mut self, profiler: &'a SelfProfiler, kind: SummaryEventKind) -> Self {
+        if let Some(summary) = &profiler.query_summary {
+            self.1 = Some(SummaryTimer::start(summary, kind));
+        }
+        self
     }
 
     #[inline]
     pub fn finish_with_query_invocation_id(self, query_invocation_id: QueryInvocationId) {
-        if let Some(guard) = self.0 {
+        let TimingGuard(guard, summary_timer) = self;
+        if let Some(guard) = guard {
             cold_path(|| {
                 let event_id = StringId::new_virtual(query_invocation_id.0);
                 let event_id = EventId::from_virtual(event_id);
                 guard.finish_with_override_event_id(event_id);
+
+                if let Some(mut summary_timer) = summary_timer {
+                    summary_timer.query_invocation_id = Some(query_invocation_id);
+                }
             });
         }
     }
 
     #[inline]
     pub fn none() -> TimingGuard<'a> {
-        TimingGuard(None)
+        TimingGuard(None, None)
     }
 
     #[inline(always)]
@@ -753,6 +825,238 @@ fn drop(&mut self) {
     }
 }
 
+/// The query events measured for `-Z self-profile-summary`.
+#[derive(Clone, Copy)]
+enum SummaryEventKind {
+    QueryProvider,
+    IncrCacheLoad,
+}
+
+thread_local! {
+    /// For each summarized event currently running on this thread, the time
+    /// spent in the summarized events nested in it.
+    static SUMMARY_CHILD_TIME: RefCell<Vec<Duration>> = RefCell::new(Vec::new());
+}
+
+/// Measures the wall time of a query event for `-Z self-profile-summary`.
+/// The event is recorded on drop, once `query_invocation_id` is known.
+struct SummaryTimer<'a> {
+    summary: &'a QuerySummary,
+    kind: SummaryEventKind,
+    start: Instant,
+    query_invocation_id: Option<QueryInvocationId>,
+}
+
+impl<'a> SummaryTimer<'a> {
+    fn start(summary: &'a QuerySummary, kind: SummaryEventKind) -> SummaryTimer<'a> {
+        SUMMARY_CHILD_TIME.with(|stack| stack.borrow_mut().push(Duration::ZERO));
+        SummaryTimer { summary, kind, start: Instant::now(), query_invocation_id: None }
+    }
+}
+
+impl Drop for SummaryTimer<'_> {
+    fn drop(&mut self) {
+        let time = self.start.elapsed();
+        // Time spent in nested events is attributed to them, not to this event.
+        let child_time = SUMMARY_CHILD_TIME.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let child_time = stack.pop().unwrap_or_default();
+            if let Some(parent_child_time) = stack.last_mut() {
+                *parent_child_time += time;
+            }
+            child_time
+        });
+
+        if let Some(query_invocation_id) = self.query_invocation_id.take() {
+            let self_time = time.saturating_sub(child_time);
+            self.summary.record_event(query_invocation_id, self.kind, self_time, time);
+        }
+    }
+}
+
+#[derive(Default)]
+struct InvocationStats {
+    query_name: Option<&'static str>,
+    executions: u64,
+    self_time: Duration,
+    time: Duration,
+    cache_hits: u64,
+    incr_load_time: Duration,
+}
+
+#[derive(Default)]
+struct QueryStats {
+    executions: u64,
+    self_time: Duration,
+    time: Duration,
+    cache_hits: u64,
+    incr_load_time: Duration,
+}
+
+/// Aggregates query events in memory for `-Z self-profile-summary`.
+///
+/// Events are recorded per `QueryInvocationId`, just like in the profiling
+/// data, and only grouped by query once the invocations have been mapped to
+/// their query name at the end of the session.
+struct QuerySummary {
+    format: SelfProfileSummaryFormat,
+    json_path: PathBuf,
+    start: Instant,
+    invocations: Mutex<FxHashMap<u32, InvocationStats>>,
+}
+
+/// The number of queries in the table printed by `-Z self-profile-summary=text`.
+const SUMMARY_TABLE_ROWS: usize = 30;
+
+impl QuerySummary {
+    fn new(format: SelfProfileSummaryFormat, json_path: PathBuf) -> QuerySummary {
+        QuerySummary {
+            format,
+            json_path,
+            start: Instant::now(),
+            invocations: Mutex::new(FxHashMap::default()),
+        }
+    }
+
+    fn record_event(
+        &self,
+        query_invocation_id: QueryInvocationId,
+        kind: SummaryEventKind,
+        self_time: Duration,
+        time: Duration,
+    ) {
+        let mut invocations = self.invocations.lock();
+        let stats = invocations.entry(query_invocation_id.0).or_default();
+        match kind {
+            SummaryEventKind::QueryProvider => {
+                stats.executions += 1;
+                stats.self_time += self_time;
+                stats.time += time;
+            }
+            SummaryEventKind::IncrCacheLoad => stats.incr_load_time += self_time,
+        }
+    }
+
+    fn record_cache_hit(&self, query_invocation_id: QueryInvocationId) {
+        self.invocations.lock().entry(query_invocation_id.0).or_default().cache_hits += 1;
+    }
+
+    fn map_query_invocation_ids_to_query_name<I>(&self, from: I, query_name: &'static str)
+    where
+        I: Iterator<Item = QueryInvocationId>,
+    {
+        let mut invocations = self.invocations.lock();
+        for query_invocation_id in from {
+            if let Some(stats) = invocations.get_mut(&query_invocation_id.0) {
+                stats.query_name = Some(query_name);
+            }
+        }
+    }
+
+    /// Groups the recorded invocations by query, sorted by descending self time.
+    fn query_stats(&self) -> Vec<(&'static str, QueryStats)> {
+        let mut queries: FxHashMap<&'static str, QueryStats> = FxHashMap::default();
+        for stats in self.invocations.lock().values() {
+            let query = queries.entry(stats.query_name.unwrap_or("<unknown>")).or_default();
+            query.executions += stats.executions;
+            query.self_time += stats.self_time;
+            query.time += stats.time;
+            query.cache_hits += stats.cache_hits;
+            query.incr_load_time += stats.incr_load_time;
+        }
+
+        let mut queries: Vec<_> = queries.into_iter().collect();
+        queries.sort_by(|(a_name, a), (b_name, b)| {
+            b.self_time.cmp(&a.self_time).then_with(|| a_name.cmp(b_name))
+        });
+        queries
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let total_time = self.start.elapsed();
+        let queries = self.query_stats();
+        match self.format {
+            SelfProfileSummaryFormat::Text => {
+                eprint!("{}", Self::format_table(&queries, total_time));
+                Ok(())
+            }
+            SelfProfileSummaryFormat::Json => {
+                fs::write(&self.json_path, Self::format_json(&queries, total_time))
+            }
+        }
+    }
+
+    fn format_table(queries: &[(&'static str, QueryStats)], total_time: Duration) -> String {
+        let shown = &queries[..queries.len().min(SUMMARY_TABLE_ROWS)];
+        let name_width = shown.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max(5);
+
+        let mut table = String::new();
+        writeln!(
+            table,
+            "{:<name_width$}  {:>12}  {:>7}  {:>12}  {:>11}  {:>10}  {:>15}",
+            "query", "self time", "% total", "time", "invocations", "cache hits", "incr. load time",
+        )
+        .unwrap();
+        for (name, stats) in shown {
+            writeln!(
+                table,
+                "{:<name_width$}  {:>12}  {:>6.2}%  {:>12}  {:>11}  {:>10}  {:>15}",
+                name,
+                duration_to_millis_str(stats.self_time),
+                percentage(stats.self_time, total_time),
+                duration_to_millis_str(stats.time),
+                stats.executions,
+                stats.cache_hits,
+                duration_to_millis_str(stats.incr_load_time),
+            )
+            .unwrap();
+        }
+        if queries.len() > shown.len() {
+            writeln!(table, "... and {} more queries", queries.len() - shown.len()).unwrap();
+        }
+        writeln!(table, "total time: {}", duration_to_millis_str(total_time)).unwrap();
+        table
+    }
+
+    fn format_json(queries: &[(&'static str, QueryStats)], total_time: Duration) -> String {
+        let mut json = String::new();
+        write!(json, "{{\"total_time_ms\":{:.3},\"queries\":[", millis(total_time)).unwrap();
+        for (i, (name, stats)) in queries.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            // Query names are plain identifiers, except for `<unknown>`, and
+            // never need escaping.
+            write!(
+                json,
+                "{{\"query\":\"{}\",\"self_time_ms\":{:.3},\"time_ms\":{:.3},\"invocations\":{},\
+                \"cache_hits\":{},\"incr_load_time_ms\":{:.3}}}",
+                name,
+                millis(stats.self_time),
+                millis(stats.time),
+                stats.executions,
+                stats.cache_hits,
+                millis(stats.incr_load_time),
+            )
+            .unwrap();
+        }
+        json.push_str("]}\n");
+        json
+    }
+}
+
+fn millis(dur: Duration) -> f64 {
+    dur.as_secs_f64() * 1000.0
+}
+
+fn duration_to_millis_str(dur: Duration) -> String {
+    format!("{:.3}ms", millis(dur))
+}
+
+fn percentage(part: Duration, total: Duration) -> f64 {
+    if total.is_zero() { 0.0 } else { part.as_secs_f64() / total.as_secs_f64() * 100.0 }
+}
+
 fn should_print_passes(dur: Duration, start_rss: Option<usize>, end_rss: Option<usize>) -> bool {
     if dur.as_millis() > 5 {
         return true;
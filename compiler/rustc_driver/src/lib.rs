@@ -27,8 +27,9 @@
 use rustc_lint::LintStore;
 use rustc_log::stdout_isatty;
 use rustc_metadata::locator;
+use rustc_middle::mir::MirPassRegistry;
 use rustc_save_analysis as save;
-use rustc_save_analysis::DumpHandler;
+use rustc_save_analysis::{DumpHandler, ScipHandler};
 use rustc_session::config::{nightly_options, CG_OPTIONS, Z_OPTIONS};
 use rustc_session::config::{ErrorOutputType, Input, OutputType, PrintRequest, TrimmedDefPaths};
 use rustc_session::cstore::MetadataLoader;
@@ -85,6 +86,9 @@ pub fn abort_on_err<T>(result: Result<T, ErrorGuaranteed>, sess: &Session) -> T
 pub trait Callbacks {
     /// Called before creating the compiler instance
     fn config(&mut self, _config: &mut interface::Config) {}
+    /// Called right after `config` to register additional MIR passes, which are run at the
+    /// given points of the MIR pipeline of every body of the local crate
+    fn register_mir_passes(&mut self, _registry: &mut MirPassRegistry) {}
     /// Called after parsing. Return value instructs the compiler whether to
     /// continue the compilation afterwards (defaults to `Compilation::Continue`)
     fn after_parsing<'tcx>(
@@ -228,6 +232,7 @@ fn run_compiler(
         parse_sess_created: None,
         register_lints: None,
         override_queries: None,
+        mir_pass_registry: Default::default(),
         make_codegen_backend,
         registry: diagnostics_registry(),
     };
@@ -239,6 +244,7 @@ fn run_compiler(
             config.input_path = input_file_path;
 
             callbacks.config(&mut config);
+            callbacks.register_mir_passes(&mut config.mir_pass_registry);
         }
         Ok(None) => match matches.free.len() {
             0 => {
@@ -300,7 +306,8 @@ fn run_compiler(
         .and_then(|| {
             list_metadata(sess, &*compiler.codegen_backend().metadata_loader(), compiler.input())
         })
-        .and_then(|| try_process_rlink(sess, compiler));
+        .and_then(|| try_process_rlink(sess, compiler))
+        .and_then(|| explain_saved_dep_graphs(sess));
 
         if should_stop == Compilation::Stop {
             return sess.compile_status();
@@ -391,6 +398,19 @@ fn run_compiler(
                         )
                     });
                 }
+                if result.is_ok() && sess.opts.output_types.contains_key(&OutputType::Scip) {
+                    let crate_name = queries.crate_name()?.peek().clone();
+                    let path = tcx.output_filenames(()).path(OutputType::Scip);
+                    sess.time("scip_index", || {
+                        save::process_crate(
+                            tcx,
+                            &crate_name,
+                            compiler.input(),
+                            Some(ScipHandler::config()),
+                            ScipHandler::new(path),
+                        )
+                    });
+                }
                 result
             })?;
 
@@ -406,7 +426,14 @@ fn run_compiler(
 
             let linker = queries.linker()?;
             Ok(Some(linker))
-        })?;
+        });
+
+        // Also report the evaluations of constants that failed to compile, as hitting the const
+        // eval limit is one of the reasons to look at these traces.
+        if sess.opts.unstable_opts.const_eval_trace {
+            sess.code_stats.report_const_eval_traces(sess.diagnostic());
+        }
+        let linker = linker?;
 
         if let Some(linker) = linker {
             let _timer = sess.timer("link");
@@ -623,6 +650,21 @@ pub fn list_metadata(
     Compilation::Continue
 }
 
+pub fn explain_saved_dep_graphs(sess: &Session) -> Compilation {
+    if sess.opts.unstable_opts.explain_saved_dep_graphs.is_some() {
+        // Whether a query is eval-always is only known from its callbacks,
+        // which don't need a `TyCtxt` to be built.
+        let arena = rustc_middle::arena::Arena::default();
+        let query_kinds = rustc_query_impl::query_callbacks(&arena);
+        rustc_incremental::explain_saved_dep_graphs(sess, &|kind| {
+            query_kinds[kind as usize].is_eval_always
+        });
+        return Compilation::Stop;
+    }
+
+    Compilation::Continue
+}
+
 fn print_crate_info(
     codegen_backend: &dyn CodegenBackend,
     sess: &Session,
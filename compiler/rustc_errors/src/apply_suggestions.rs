@@ -0,0 +1,269 @@
+//! Resolves the machine-applicable suggestions of the emitted diagnostics to
+//! edits of the source files, for `--apply-suggestions`.
+//!
+//! Suggestions are applied in the order they were emitted. A suggestion whose
+//! edits overlap with those of a suggestion that was already accepted is
+//! skipped as a whole, so that the result doesn't depend on how the edits of
+//! two unrelated suggestions would interleave. Running the compiler again
+//! applies the skipped suggestions if they still apply.
+
+use crate::Substitution;
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_span::source_map::SourceMap;
+use rustc_span::SourceFile;
+
+use std::fmt::Write;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Number of unchanged lines shown around the changes of a unified diff.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// The source files changed by the applied suggestions.
+pub struct SuggestionFixes {
+    pub files: Vec<FixedFile>,
+    /// The number of suggestions that weren't applied because they overlap
+    /// with another suggestion.
+    pub overlapping: usize,
+    /// The number of suggestions that couldn't be applied, because they edit
+    /// a file that isn't a local source file or changed since it was read.
+    pub unapplicable: usize,
+}
+
+impl SuggestionFixes {
+    /// The number of suggestions applied to all files.
+    pub fn applied(&self) -> usize {
+        self.files.iter().map(|file| file.applied).sum()
+    }
+}
+
+/// A source file with the edits of the suggestions applied to it.
+pub struct FixedFile {
+    pub path: PathBuf,
+    /// The number of suggestions applied to this file.
+    pub applied: usize,
+    original: String,
+    /// The accepted edits, sorted by position and not overlapping.
+    edits: Vec<Edit>,
+}
+
+#[derive(PartialEq)]
+struct Edit {
+    /// The replaced byte range of the file, as found on disk.
+    range: Range<usize>,
+    snippet: String,
+}
+
+impl Edit {
+    fn overlaps(&self, other: &Edit) -> bool {
+        // Two insertions at the same position overlap too, as the order in
+        // which they should be applied is unknown.
+        let same_insertion_point = self.range.is_empty()
+            && other.range.is_empty()
+            && self.range.start == other.range.start;
+        same_insertion_point
+            || (self.range.start < other.range.end && other.range.start < self.range.end)
+    }
+}
+
+/// Resolves the given suggestions to the edits of the source files that they
+/// make, skipping overlapping suggestions.
+pub fn resolve_suggestions(sm: &SourceMap, suggestions: &[Substitution]) -> SuggestionFixes {
+    // Files are `None` if they can't be edited.
+    let mut files: FxIndexMap<PathBuf, Option<FixedFile>> = FxIndexMap::default();
+    let mut overlapping = 0;
+    let mut unapplicable = 0;
+
+    'suggestions: for suggestion in suggestions {
+        let mut edits = Vec::with_capacity(suggestion.parts.len());
+        for part in &suggestion.parts {
+            let Some((path, range)) = resolve_span(sm, part.span, &mut files) else {
+                unapplicable += 1;
+                continue 'suggestions;
+            };
+            edits.push((path, Edit { range, snippet: part.snippet.clone() }));
+        }
+
+        // Identical suggestions may be emitted by different diagnostics.
+        let mut new_edits = Vec::with_capacity(edits.len());
+        for (path, edit) in edits {
+            let file = files[&path].as_ref().unwrap();
+            if file.edits.contains(&edit) {
+                continue;
+            }
+            if file
+                .edits
+                .iter()
+                .chain(new_edits.iter().map(|(_, edit)| edit))
+                .any(|e| e.overlaps(&edit))
+            {
+                overlapping += 1;
+                continue 'suggestions;
+            }
+            new_edits.push((path, edit));
+        }
+        if new_edits.is_empty() {
+            continue;
+        }
+
+        let mut applied_to = Vec::new();
+        for (path, edit) in new_edits {
+            let file = files[&path].as_mut().unwrap();
+            let key = |e: &Edit| (e.range.start, e.range.end);
+            let index = file.edits.partition_point(|e| key(e) <= key(&edit));
+            file.edits.insert(index, edit);
+            if !applied_to.contains(&path) {
+                file.applied += 1;
+                applied_to.push(path);
+            }
+        }
+    }
+
+    let files = files.into_values().flatten().filter(|file| !file.edits.is_empty()).collect();
+    SuggestionFixes { files, overlapping, unapplicable }
+}
+
+/// Returns the local path of the file the span points into and the byte range
+/// of the span in the file on disk, reading the file if needed.
+fn resolve_span(
+    sm: &SourceMap,
+    span: rustc_span::Span,
+    files: &mut FxIndexMap<PathBuf, Option<FixedFile>>,
+) -> Option<(PathBuf, Range<usize>)> {
+    if span.is_dummy() {
+        return None;
+    }
+    let source_file = sm.lookup_source_file(span.lo());
+    if span.hi() > source_file.end_pos {
+        return None;
+    }
+    let rustc_span::FileName::Real(name) = &source_file.name else {
+        return None;
+    };
+    let path = name.local_path()?.to_path_buf();
+
+    let file = files
+        .entry(path.clone())
+        .or_insert_with(|| read_source_file(&source_file, path.clone()))
+        .as_ref()?;
+    let start = source_file.original_relative_byte_pos(span.lo()).to_usize();
+    let end = source_file.original_relative_byte_pos(span.hi()).to_usize();
+    if !file.original.is_char_boundary(start) || !file.original.is_char_boundary(end) {
+        return None;
+    }
+    Some((path, start..end))
+}
+
+/// Reads the source file from disk, as byte positions in the source map are
+/// relative to its normalized contents. Returns `None` if the file changed
+/// since the compiler read it.
+fn read_source_file(source_file: &Lrc<SourceFile>, path: PathBuf) -> Option<FixedFile> {
+    let original = fs::read_to_string(&path).ok()?;
+    if !source_file.src_hash.matches(&original) {
+        return None;
+    }
+    Some(FixedFile { path, applied: 0, original, edits: Vec::new() })
+}
+
+impl FixedFile {
+    /// Returns the contents of the file with the suggestions applied.
+    pub fn fixed_source(&self) -> String {
+        apply_edits(&self.original, 0, &self.edits)
+    }
+
+    /// Returns the changes made to the file as a unified diff.
+    pub fn unified_diff(&self) -> String {
+        let text = &self.original;
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .filter(|&start| start < text.len())
+            .collect();
+        let line_of = |pos: usize| line_starts.partition_point(|&start| start <= pos).max(1) - 1;
+        let line_end = |line: usize| line_starts.get(line + 1).copied().unwrap_or(text.len());
+        let last_line = line_starts.len().saturating_sub(1);
+
+        // Group the edits whose changed lines, including their context,
+        // overlap or are adjacent into hunks of (first line, last line, edits).
+        let mut hunks: Vec<(usize, usize, Range<usize>)> = Vec::new();
+        for (i, edit) in self.edits.iter().enumerate() {
+            let first = line_of(edit.range.start).saturating_sub(DIFF_CONTEXT_LINES);
+            let last_changed = line_of(edit.range.end.saturating_sub(1).max(edit.range.start));
+            let last = (last_changed + DIFF_CONTEXT_LINES).min(last_line);
+            match hunks.last_mut() {
+                Some((_, hunk_last, edits)) if first <= *hunk_last + 1 => {
+                    *hunk_last = last.max(*hunk_last);
+                    edits.end = i + 1;
+                }
+                _ => hunks.push((first, last, i..i + 1)),
+            }
+        }
+
+        let mut diff = format!("--- {}\n+++ {}\n", self.path.display(), self.path.display());
+        let mut line_delta = 0isize;
+        for (first, last, edits) in hunks {
+            let start = line_starts.get(first).copied().unwrap_or(0);
+            let old = &text[start..line_end(last)];
+            let new = apply_edits(old, start, &self.edits[edits]);
+            let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+            let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+
+            // Only the lines between the common prefix and suffix changed.
+            let prefix =
+                old_lines.iter().zip(&new_lines).take_while(|(old, new)| old == new).count();
+            let suffix = old_lines[prefix..]
+                .iter()
+                .rev()
+                .zip(new_lines[prefix..].iter().rev())
+                .take_while(|(old, new)| old == new)
+                .count();
+
+            let new_first = (first as isize + line_delta) as usize;
+            line_delta += new_lines.len() as isize - old_lines.len() as isize;
+            writeln!(
+                diff,
+                "@@ -{},{} +{},{} @@",
+                first + 1,
+                old_lines.len(),
+                new_first + 1,
+                new_lines.len()
+            )
+            .unwrap();
+
+            let context_before = old_lines[..prefix].iter().map(|line| (' ', line));
+            let removed =
+                old_lines[prefix..old_lines.len() - suffix].iter().map(|line| ('-', line));
+            let added = new_lines[prefix..new_lines.len() - suffix].iter().map(|line| ('+', line));
+            let context_after =
+                old_lines[old_lines.len() - suffix..].iter().map(|line| (' ', line));
+            for (marker, line) in context_before.chain(removed).chain(added).chain(context_after) {
+                diff.push(marker);
+                diff.push_str(line);
+                if !line.ends_with('\n') {
+                    diff.push_str("\n\\ No newline at end of file\n");
+                }
+            }
+        }
+        diff
+    }
+}
+
+/// Applies the sorted edits to `text`, which starts at byte `offset` of the
+/// file the edits refer to.
+fn apply_edits(text: &str, offset: usize, edits: &[Edit]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut pos = 0;
+    for edit in edits {
+        let range = edit.range.start - offset..edit.range.end - offset;
+        result.push_str(&text[pos..range.start]);
+        result.push_str(&edit.snippet);
+        pos = range.end;
+    }
+    result.push_str(&text[pos..]);
+    result
+}
+
+#[cfg(test)]
+mod tests;
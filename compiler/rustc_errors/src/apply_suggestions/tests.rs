@@ -0,0 +1,92 @@
+use super::*;
+
+fn fixed_file(original: &str, edits: &[(Range<usize>, &str)]) -> FixedFile {
+    FixedFile {
+        path: PathBuf::from("test.rs"),
+        applied: edits.len(),
+        original: original.to_string(),
+        edits: edits
+            .iter()
+            .map(|(range, snippet)| Edit { range: range.clone(), snippet: snippet.to_string() })
+            .collect(),
+    }
+}
+
+#[test]
+fn overlapping_edits() {
+    let edit = |range: Range<usize>| Edit { range, snippet: String::new() };
+    assert!(edit(0..4).overlaps(&edit(2..6)));
+    assert!(edit(2..6).overlaps(&edit(0..4)));
+    assert!(edit(0..6).overlaps(&edit(2..2)));
+    assert!(edit(2..2).overlaps(&edit(2..2)));
+    assert!(!edit(0..2).overlaps(&edit(2..4)));
+    assert!(!edit(2..2).overlaps(&edit(2..4)));
+    assert!(!edit(0..2).overlaps(&edit(2..2)));
+}
+
+#[test]
+fn fixed_source() {
+    let file = fixed_file("let mut x = 1;\n", &[(4..8, ""), (15..15, "x;\n")]);
+    assert_eq!(file.fixed_source(), "let x = 1;\nx;\n");
+}
+
+#[test]
+fn unified_diff() {
+    let original = "fn main() {\n    let mut a = 1;\n    a;\n}\n";
+    let file = fixed_file(original, &[(20..24, "")]);
+    assert_eq!(
+        file.unified_diff(),
+        "--- test.rs\n\
+         +++ test.rs\n\
+         @@ -1,4 +1,4 @@\n \
+         fn main() {\n\
+         -    let mut a = 1;\n\
+         +    let a = 1;\n     \
+         a;\n \
+         }\n"
+    );
+}
+
+#[test]
+fn unified_diff_hunks() {
+    let original: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+    let second_line = original.find("line 2").unwrap();
+    let last_line = original.find("line 20").unwrap();
+    let file = fixed_file(
+        &original,
+        &[(second_line..second_line, "inserted\n"), (last_line..original.len(), "")],
+    );
+    assert_eq!(
+        file.unified_diff(),
+        "--- test.rs\n\
+         +++ test.rs\n\
+         @@ -1,5 +1,6 @@\n \
+         line 1\n\
+         +inserted\n \
+         line 2\n \
+         line 3\n \
+         line 4\n \
+         line 5\n\
+         @@ -17,4 +18,3 @@\n \
+         line 17\n \
+         line 18\n \
+         line 19\n\
+         -line 20\n"
+    );
+}
+
+#[test]
+fn unified_diff_no_newline_at_end_of_file() {
+    let file = fixed_file("a\nb", &[(2..3, "c")]);
+    assert_eq!(
+        file.unified_diff(),
+        "--- test.rs\n\
+         +++ test.rs\n\
+         @@ -1,2 +1,2 @@\n \
+         a\n\
+         -b\n\
+         \\ No newline at end of file\n\
+         +c\n\
+         \\ No newline at end of file\n"
+    );
+}
@@ -12,6 +12,7 @@
 use rustc_span::source_map::SourceMap;
 use rustc_span::{FileLines, SourceFile, Span};
 
+use crate::json::DependencyUsage;
 use crate::snippet::{Annotation, AnnotationType, Line, MultilineAnnotation, Style, StyledString};
 use crate::styled_buffer::StyledBuffer;
 use crate::{
@@ -219,6 +220,9 @@ fn emit_unused_externs(
     ) {
     }
 
+    /// Emit the report of how each `--extern` dependency is used
+    fn emit_dependency_usage(&mut self, _dependencies: &[DependencyUsage]) {}
+
     /// Checks if should show explanations about "rustc --explain"
     fn should_show_explain(&self) -> bool {
         true
@@ -185,6 +185,19 @@ fn emit_unused_externs(&mut self, lint_level: rustc_lint_defs::Level, unused_ext
         }
     }
 
+    fn emit_dependency_usage(&mut self, dependencies: &[DependencyUsage]) {
+        let data = DependencyUsageReport { dependency_usage: dependencies };
+        let result = if self.pretty {
+            writeln!(&mut self.dst, "{}", serde_json::to_string_pretty(&data).unwrap())
+        } else {
+            writeln!(&mut self.dst, "{}", serde_json::to_string(&data).unwrap())
+        }
+        .and_then(|_| self.dst.flush());
+        if let Err(e) = result {
+            panic!("failed to print dependency usage: {:?}", e);
+        }
+    }
+
     fn source_map(&self) -> Option<&Lrc<SourceMap>> {
         Some(&self.sm)
     }
@@ -307,6 +320,26 @@ struct UnusedExterns<'a, 'b, 'c> {
     unused_extern_names: &'b [&'c str],
 }
 
+#[derive(Serialize)]
+struct DependencyUsageReport<'a> {
+    dependency_usage: &'a [DependencyUsage],
+}
+
+/// How a dependency passed with `--extern` is used by the crate.
+#[derive(Serialize)]
+pub struct DependencyUsage {
+    /// The name of the dependency in the extern prelude.
+    pub name: String,
+    /// One of `used`, `glob-only`, `macros-only`, `reexports-only`,
+    /// `extern-crate-only`, `test-only` or `unused`.
+    pub usage: &'static str,
+    /// The paths through which items of the dependency are reached from the
+    /// re-exports of other crates, like `foo::Bar`.
+    pub reexported_through: Vec<String>,
+    /// The macros whose expansions use the dependency, including its own.
+    pub macros: Vec<String>,
+}
+
 impl Diagnostic {
     fn from_errors_diagnostic(diag: &crate::Diagnostic, je: &JsonEmitter) -> Diagnostic {
         let args = je.to_fluent_args(diag.args());
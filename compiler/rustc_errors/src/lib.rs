@@ -50,12 +50,14 @@
 use termcolor::{Color, ColorSpec};
 
 pub mod annotate_snippet_emitter_writer;
+pub mod apply_suggestions;
 mod diagnostic;
 mod diagnostic_builder;
 pub mod emitter;
 pub mod json;
 mod lock;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 
@@ -429,6 +431,10 @@ struct HandlerInner {
 
     future_breakage_diagnostics: Vec<Diagnostic>,
 
+    /// The machine-applicable suggestions of the emitted diagnostics, if
+    /// [`HandlerFlags::collect_suggestions`] is set.
+    machine_applicable_suggestions: Vec<Substitution>,
+
     /// The [`Self::unstable_expect_diagnostics`] should be empty when this struct is
     /// dropped. However, it can have values if the compilation is stopped early
     /// or is only partially executed. To avoid ICEs, like in rust#94953 we only
@@ -484,6 +490,10 @@ pub struct HandlerFlags {
     pub macro_backtrace: bool,
     /// If true, identical diagnostics are reported only once.
     pub deduplicate_diagnostics: bool,
+    /// If true, the machine-applicable suggestions of emitted diagnostics are
+    /// collected so that they can be applied to the source files.
+    /// (rustc: see `--apply-suggestions`)
+    pub collect_suggestions: bool,
 }
 
 impl Drop for HandlerInner {
@@ -588,6 +598,7 @@ pub fn with_emitter_and_flags(
                 emitted_diagnostics: Default::default(),
                 stashed_diagnostics: Default::default(),
                 future_breakage_diagnostics: Vec::new(),
+                machine_applicable_suggestions: Vec::new(),
                 check_unstable_expect_diagnostics: false,
                 unstable_expect_diagnostics: Vec::new(),
                 fulfilled_expectations: Default::default(),
@@ -999,6 +1010,10 @@ pub fn take_future_breakage_diagnostics(&self) -> Vec<Diagnostic> {
         std::mem::take(&mut self.inner.borrow_mut().future_breakage_diagnostics)
     }
 
+    pub fn take_machine_applicable_suggestions(&self) -> Vec<Substitution> {
+        std::mem::take(&mut self.inner.borrow_mut().machine_applicable_suggestions)
+    }
+
     pub fn abort_if_errors(&self) {
         self.inner.borrow_mut().abort_if_errors()
     }
@@ -1052,6 +1067,10 @@ pub fn emit_unused_externs(
         inner.emit_unused_externs(lint_level, unused_externs)
     }
 
+    pub fn emit_dependency_usage(&self, dependencies: &[json::DependencyUsage]) {
+        self.inner.borrow_mut().emitter.emit_dependency_usage(dependencies)
+    }
+
     pub fn update_unstable_expectation_id(
         &self,
         unstable_to_stable: &FxHashMap<LintExpectationId, LintExpectationId>,
@@ -1195,6 +1214,9 @@ fn emit_diagnostic(&mut self, diagnostic: &mut Diagnostic) -> Option<ErrorGuaran
             diagnostic.children.drain_filter(already_emitted_sub).for_each(|_| {});
 
             self.emitter.emit_diagnostic(&diagnostic);
+            if self.flags.collect_suggestions {
+                self.collect_machine_applicable_suggestions(diagnostic);
+            }
             if diagnostic.is_error() {
                 self.deduplicated_err_count += 1;
             } else if let Warning(_) = diagnostic.level {
@@ -1216,6 +1238,18 @@ fn emit_diagnostic(&mut self, diagnostic: &mut Diagnostic) -> Option<ErrorGuaran
         }
     }
 
+    fn collect_machine_applicable_suggestions(&mut self, diagnostic: &Diagnostic) {
+        let Ok(suggestions) = &diagnostic.suggestions else { return };
+        for suggestion in suggestions {
+            // Suggestions with several alternatives need a human to choose one.
+            if let (Applicability::MachineApplicable, [substitution]) =
+                (suggestion.applicability, &suggestion.substitutions[..])
+            {
+                self.machine_applicable_suggestions.push(substitution.clone());
+            }
+        }
+    }
+
     fn emit_artifact_notification(&mut self, path: &Path, artifact_type: &str) {
         self.emitter.emit_artifact_notification(path, artifact_type);
     }
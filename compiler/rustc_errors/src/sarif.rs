@@ -0,0 +1,493 @@
+//! A SARIF emitter for errors.
+//!
+//! [SARIF] (the Static Analysis Results Interchange Format) is a standard JSON
+//! format consumed by code-review and security dashboards. Unlike the JSON
+//! emitter, which streams one object per diagnostic, a SARIF log is a single
+//! document: diagnostics are collected as they are emitted and the whole log is
+//! written out once the emitter is dropped.
+//!
+//! Diagnostics are mapped onto SARIF as follows:
+//!
+//! - Every error code or lint name becomes a `reportingDescriptor` ("rule") of
+//!   the `rustc` tool driver, with the long `--explain` text as its description.
+//! - Every top-level diagnostic becomes a `result`. Its primary spans become
+//!   `locations`, while secondary labelled spans and spanned child diagnostics
+//!   become `relatedLocations`. Children without a span are appended to the
+//!   result message.
+//! - Every substitution of a `CodeSuggestion` becomes a `fix`, with the
+//!   suggestion's `Applicability` recorded in the fix's property bag.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use rustc_span::source_map::SourceMap;
+
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::DiagnosticId;
+use crate::{CodeSuggestion, FluentBundle, LazyFallbackBundle, Level, MultiSpan, SubDiagnostic};
+use rustc_lint_defs::Applicability;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_error_messages::FluentArgs;
+use rustc_span::Span;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+pub struct SarifEmitter {
+    dst: Box<dyn Write + Send>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    fallback_bundle: LazyFallbackBundle,
+    pretty: bool,
+    /// Rules referenced by `results`, in order of first use.
+    rules: Vec<Rule>,
+    /// Maps a rule id to its index in `rules`.
+    rule_indices: FxHashMap<String, usize>,
+    results: Vec<SarifResult>,
+}
+
+impl SarifEmitter {
+    pub fn stderr(
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+        pretty: bool,
+    ) -> SarifEmitter {
+        SarifEmitter::new(
+            Box::new(io::BufWriter::new(io::stderr())),
+            registry,
+            source_map,
+            fluent_bundle,
+            fallback_bundle,
+            pretty,
+        )
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+        pretty: bool,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst,
+            registry,
+            sm: source_map,
+            fluent_bundle,
+            fallback_bundle,
+            pretty,
+            rules: Vec::new(),
+            rule_indices: FxHashMap::default(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Returns the index of the rule for `code`, registering it on first use.
+    fn rule_index(&mut self, code: &DiagnosticId) -> usize {
+        let (id, is_lint) = match code {
+            DiagnosticId::Error(s) => (s, false),
+            DiagnosticId::Lint { name, .. } => (name, true),
+        };
+        if let Some(&index) = self.rule_indices.get(id) {
+            return index;
+        }
+
+        let explanation = if is_lint {
+            None
+        } else {
+            self.registry
+                .as_ref()
+                .and_then(|registry| registry.try_find_description(id).ok())
+                .flatten()
+        };
+        let rule = Rule {
+            id: id.clone(),
+            short_description: explanation.and_then(Rule::summary).map(Message::new),
+            full_description: explanation.map(|e| Message::new(e.to_owned())),
+            help_uri: (!is_lint && explanation.is_some())
+                .then(|| format!("https://doc.rust-lang.org/error-index.html#{id}")),
+        };
+
+        let index = self.rules.len();
+        self.rules.push(rule);
+        self.rule_indices.insert(id.clone(), index);
+        index
+    }
+
+    fn write_log(&mut self) -> io::Result<()> {
+        let rules = std::mem::take(&mut self.rules);
+        let results = std::mem::take(&mut self.results);
+        let log = Log {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        rules,
+                    },
+                },
+                column_kind: "unicodeCodePoints",
+                results,
+            }],
+        };
+        if self.pretty {
+            writeln!(&mut self.dst, "{}", serde_json::to_string_pretty(&log).unwrap())
+        } else {
+            writeln!(&mut self.dst, "{}", serde_json::to_string(&log).unwrap())
+        }
+        .and_then(|_| self.dst.flush())
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        // The emitter can be dropped while unwinding from a fatal error, so avoid
+        // panicking here; there is nowhere left to report the failure anyway.
+        if let Err(e) = self.write_log() {
+            eprintln!("failed to print SARIF log: {:?}", e);
+        }
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        // "aborting due to previous error" and `--explain` hints are not results.
+        if diag.level == Level::FailureNote {
+            return;
+        }
+
+        let args = self.to_fluent_args(diag.args());
+        let mut message = self.translate_messages(&diag.message, &args).into_owned();
+
+        let mut locations = Vec::new();
+        let mut related_locations = Vec::new();
+        for span_label in diag.span.span_labels() {
+            let label = span_label.label.as_ref().map(|l| self.translate_message(l, &args));
+            let Some(physical_location) = PhysicalLocation::from_span(span_label.span, self) else {
+                continue;
+            };
+            if span_label.is_primary {
+                locations.push(Location {
+                    id: None,
+                    physical_location,
+                    message: label.map(|l| Message::new(l.into_owned())),
+                });
+            } else {
+                related_locations.push(Location {
+                    id: Some(related_locations.len()),
+                    physical_location,
+                    message: label.map(|l| Message::new(l.into_owned())),
+                });
+            }
+        }
+
+        for child in &diag.children {
+            let child_message = self.translate_messages(&child.message, &args);
+            let child_message = format!("{}: {}", child.level.to_str(), child_message);
+            let mut spanned = false;
+            for span in child_spans(child) {
+                let Some(physical_location) = PhysicalLocation::from_span(span, self) else {
+                    continue;
+                };
+                spanned = true;
+                related_locations.push(Location {
+                    id: Some(related_locations.len()),
+                    physical_location,
+                    message: Some(Message::new(child_message.clone())),
+                });
+            }
+            if !spanned {
+                message.push('\n');
+                message.push_str(&child_message);
+            }
+        }
+
+        let fixes = diag
+            .suggestions
+            .iter()
+            .flatten()
+            .flat_map(|sugg| Fix::from_suggestion(sugg, &args, self))
+            .collect();
+
+        let (rule_id, rule_index) = match &diag.code {
+            Some(code) => {
+                let index = self.rule_index(code);
+                (Some(self.rules[index].id.clone()), Some(index))
+            }
+            None => (None, None),
+        };
+
+        self.results.push(SarifResult {
+            rule_id,
+            rule_index,
+            level: sarif_level(diag.level),
+            message: Message::new(message),
+            locations,
+            related_locations,
+            fixes,
+        });
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.fluent_bundle.as_ref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &**self.fallback_bundle
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+}
+
+/// Returns the spans a child diagnostic should be reported at, preferring the
+/// render span like the JSON emitter does.
+fn child_spans(child: &SubDiagnostic) -> Vec<Span> {
+    let span: &MultiSpan = child.render_span.as_ref().unwrap_or(&child.span);
+    span.primary_spans().to_vec()
+}
+
+/// Maps a diagnostic level onto a SARIF `result.level`.
+fn sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error { .. } => "error",
+        Level::Warning(_) => "warning",
+        Level::Note | Level::OnceNote | Level::Help | Level::FailureNote => "note",
+        Level::Allow | Level::Expect(_) => "none",
+    }
+}
+
+// The following data types are provided just for serialisation.
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    /// Columns are character offsets, not the default UTF-16 code units.
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: ToolComponent,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+/// A SARIF `reportingDescriptor`: an error code or a lint.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_description: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+}
+
+impl Rule {
+    /// The first line of an error code explanation is a one sentence summary.
+    fn summary(explanation: &str) -> Option<String> {
+        explanation.lines().map(str::trim).find(|l| !l.is_empty()).map(str::to_owned)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// "error", "warning", "note" or "none".
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+impl Message {
+    fn new(text: String) -> Message {
+        Message { text }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    /// Only set for related locations, which must be uniquely numbered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_line: usize,
+    /// 1-based, exclusive, character offset.
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+struct FixProperties {
+    applicability: Applicability,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: Content,
+}
+
+#[derive(Serialize)]
+struct Content {
+    text: String,
+}
+
+impl PhysicalLocation {
+    /// Returns `None` for spans that do not point into a source file.
+    fn from_span(span: Span, se: &SarifEmitter) -> Option<PhysicalLocation> {
+        if span.is_dummy() {
+            return None;
+        }
+        let start = se.sm.lookup_char_pos(span.lo());
+        let end = se.sm.lookup_char_pos(span.hi());
+        let byte_start = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        Some(PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: se.sm.filename_for_diagnostics(&start.file.name).to_string(),
+            },
+            region: Region {
+                start_line: start.line,
+                start_column: start.col.0 + 1,
+                end_line: end.line,
+                end_column: end.col.0 + 1,
+                byte_offset: byte_start,
+                byte_length: byte_end - byte_start,
+            },
+        })
+    }
+}
+
+impl Fix {
+    /// Each alternative substitution of a suggestion is a separate fix. The parts
+    /// of a substitution are grouped into one `artifactChange` per file.
+    fn from_suggestion(
+        suggestion: &CodeSuggestion,
+        args: &FluentArgs<'_>,
+        se: &SarifEmitter,
+    ) -> Vec<Fix> {
+        let description = se.translate_message(&suggestion.msg, args).into_owned();
+        suggestion
+            .substitutions
+            .iter()
+            .filter_map(|substitution| {
+                let mut artifact_changes: Vec<ArtifactChange> = Vec::new();
+                for part in &substitution.parts {
+                    let Some(location) = PhysicalLocation::from_span(part.span, se) else {
+                        continue;
+                    };
+                    let replacement = Replacement {
+                        deleted_region: location.region,
+                        inserted_content: Content { text: part.snippet.clone() },
+                    };
+                    let uri = location.artifact_location.uri;
+                    match artifact_changes.iter_mut().find(|c| c.artifact_location.uri == uri) {
+                        Some(change) => change.replacements.push(replacement),
+                        None => artifact_changes.push(ArtifactChange {
+                            artifact_location: ArtifactLocation { uri },
+                            replacements: vec![replacement],
+                        }),
+                    }
+                }
+                (!artifact_changes.is_empty()).then(|| Fix {
+                    description: Message::new(description.clone()),
+                    artifact_changes,
+                    properties: FixProperties { applicability: suggestion.applicability },
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+use crate::sarif::SarifEmitter;
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+
+use crate::Handler;
+use rustc_span::{BytePos, Span};
+
+use std::path::Path;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Emits diagnostics through `f` and returns the SARIF log written once the
+/// handler is dropped.
+fn emit_sarif(code: &str, f: impl FnOnce(&Handler)) -> serde_json::Value {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(rustc_error_messages::DEFAULT_LOCALE_RESOURCES, false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let se = SarifEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            Some(Registry::new(&[("E0001", Some("Summary line.\n\nLonger text.\n"))])),
+            sm,
+            None,
+            fallback_bundle,
+            false,
+        );
+
+        let handler = Handler::with_emitter(true, None, Box::new(se));
+        f(&handler);
+        drop(handler);
+
+        let bytes = output.lock().unwrap();
+        serde_json::from_str(str::from_utf8(&bytes).unwrap()).unwrap()
+    })
+}
+
+#[test]
+fn empty_log() {
+    let log = emit_sarif("", |_| {});
+    assert_eq!(log["version"], "2.1.0");
+    assert_eq!(log["runs"][0]["tool"]["driver"]["name"], "rustc");
+    assert_eq!(log["runs"][0]["results"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn result_with_rule_and_locations() {
+    let log = emit_sarif("fn foo() {}\nfn bar() {}\n", |handler| {
+        let mut span = MultiSpan::from_span(Span::with_root_ctxt(BytePos(3), BytePos(6)));
+        span.push_span_label(Span::with_root_ctxt(BytePos(15), BytePos(18)), "other");
+        let mut diag = handler.struct_span_err(span, "foo");
+        diag.code(DiagnosticId::Error("E0001".to_owned()));
+        diag.note("a note without a span");
+        diag.span_note(Span::with_root_ctxt(BytePos(0), BytePos(2)), "a spanned note");
+        diag.emit();
+    });
+
+    let run = &log["runs"][0];
+    let rule = &run["tool"]["driver"]["rules"][0];
+    assert_eq!(rule["id"], "E0001");
+    assert_eq!(rule["shortDescription"]["text"], "Summary line.");
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "E0001");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["message"]["text"], "foo\nnote: a note without a span");
+
+    let region = &result["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 1);
+    assert_eq!(region["startColumn"], 4);
+    assert_eq!(region["endColumn"], 7);
+    assert_eq!(region["byteOffset"], 3);
+    assert_eq!(region["byteLength"], 3);
+
+    let related = result["relatedLocations"].as_array().unwrap();
+    assert_eq!(related.len(), 2);
+    assert_eq!(related[0]["id"], 0);
+    assert_eq!(related[0]["message"]["text"], "other");
+    assert_eq!(related[0]["physicalLocation"]["region"]["startLine"], 2);
+    assert_eq!(related[1]["id"], 1);
+    assert_eq!(related[1]["message"]["text"], "note: a spanned note");
+}
+
+#[test]
+fn suggestions_become_fixes() {
+    let log = emit_sarif("let x = 1;\n", |handler| {
+        let span = Span::with_root_ctxt(BytePos(4), BytePos(5));
+        handler
+            .struct_span_warn(span, "bad name")
+            .span_suggestion(span, "rename it", "y", Applicability::MachineApplicable)
+            .emit();
+    });
+
+    let result = &log["runs"][0]["results"][0];
+    assert_eq!(result["level"], "warning");
+    let fix = &result["fixes"][0];
+    assert_eq!(fix["description"]["text"], "rename it");
+    assert_eq!(fix["properties"]["applicability"], "MachineApplicable");
+    let change = &fix["artifactChanges"][0];
+    assert_eq!(change["artifactLocation"]["uri"], "test.rs");
+    assert_eq!(change["replacements"][0]["insertedContent"]["text"], "y");
+    assert_eq!(change["replacements"][0]["deletedRegion"]["byteOffset"], 4);
+}
@@ -34,7 +34,7 @@
 //! ```
 
 use rustc_ast as ast;
-use rustc_data_structures::fx::FxHashSet;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::graph::implementation::{Direction, NodeIndex, INCOMING, OUTGOING};
 use rustc_graphviz as dot;
 use rustc_hir as hir;
@@ -45,8 +45,10 @@
 };
 use rustc_middle::hir::nested_filter;
 use rustc_middle::ty::TyCtxt;
+use rustc_session::config::DepGraphDumpFormat;
 use rustc_span::symbol::{sym, Symbol};
 use rustc_span::Span;
+use serde_json::json;
 
 use std::env;
 use std::fs::{self, File};
@@ -56,7 +58,7 @@
 pub fn assert_dep_graph(tcx: TyCtxt<'_>) {
     tcx.dep_graph.with_ignore(|| {
         if tcx.sess.opts.unstable_opts.dump_dep_graph {
-            tcx.dep_graph.with_query(dump_graph);
+            tcx.dep_graph.with_query(|query| dump_graph(tcx, query));
         }
 
         if !tcx.sess.opts.unstable_opts.query_dep_graph {
@@ -229,7 +231,7 @@ fn check_paths<'tcx>(tcx: TyCtxt<'tcx>, if_this_changed: &Sources, then_this_wou
     });
 }
 
-fn dump_graph(query: &DepGraphQuery) {
+fn dump_graph(tcx: TyCtxt<'_>, query: &DepGraphQuery) {
     let path: String = env::var("RUST_DEP_GRAPH").unwrap_or_else(|_| "dep_graph".to_string());
 
     let nodes = match env::var("RUST_DEP_GRAPH_FILTER") {
@@ -243,6 +245,12 @@ fn dump_graph(query: &DepGraphQuery) {
         }
         Err(_) => query.nodes().into_iter().map(|n| n.kind).collect(),
     };
+
+    if tcx.sess.opts.unstable_opts.dump_dep_graph_format == DepGraphDumpFormat::Json {
+        dump_graph_json(tcx, query, &nodes, &path);
+        return;
+    }
+
     let edges = filter_edges(&query, &nodes);
 
     {
@@ -263,6 +271,53 @@ fn dump_graph(query: &DepGraphQuery) {
     }
 }
 
+/// Writes the dep nodes of the given kinds to a `.json` file, along with the
+/// edges between them. Unlike the graphviz output, nodes aren't merged by kind
+/// and carry the color they have been marked with in this session.
+fn dump_graph_json(tcx: TyCtxt<'_>, query: &DepGraphQuery, kinds: &FxHashSet<DepKind>, path: &str) {
+    let mut indices = FxHashMap::default();
+    let mut nodes = Vec::new();
+    for (index, node) in query.graph.all_nodes().iter().enumerate() {
+        let dep_node = &node.data;
+        if !kinds.contains(&dep_node.kind) {
+            continue;
+        }
+        indices.insert(index, nodes.len());
+
+        let color = if tcx.dep_graph.prev_fingerprint_of(dep_node).is_none() {
+            "new"
+        } else if tcx.dep_graph.is_green(dep_node) {
+            "green"
+        } else if tcx.dep_graph.is_red(dep_node) {
+            "red"
+        } else {
+            "unknown"
+        };
+        nodes.push(json!({
+            "kind": format!("{:?}", dep_node.kind),
+            "label": format!("{:?}", dep_node),
+            "hash": dep_node.hash.to_string(),
+            "color": color,
+        }));
+    }
+
+    // An edge `[a, b]` means that node `a` read node `b`.
+    let edges: Vec<_> = query
+        .graph
+        .all_edges()
+        .iter()
+        .filter_map(|edge| {
+            let source = indices.get(&edge.source().0)?;
+            let target = indices.get(&edge.target().0)?;
+            Some(json!([source, target]))
+        })
+        .collect();
+
+    let json_path = format!("{}.json", path);
+    let file = BufWriter::new(File::create(&json_path).unwrap());
+    serde_json::to_writer(file, &json!({ "nodes": nodes, "edges": edges })).unwrap();
+}
+
 #[allow(missing_docs)]
 pub struct GraphvizDepGraph(FxHashSet<DepKind>, Vec<(DepKind, DepKind)>);
 
@@ -0,0 +1,221 @@
+//! This pass is only used for debugging unexpected re-execution of
+//! queries in incremental compilation. With `-Z explain-dep-graph=filter`,
+//! it prints for every node of the previous session's dependency graph
+//! matching the filter whether its result was reused, and if not, why it
+//! had to be re-executed.
+//!
+//! At the end of a session, every node of the previous dependency graph
+//! that was needed has been marked green (its result is unchanged) or red
+//! (its result changed). A node is re-executed when it can't be marked
+//! green because one of the nodes it read in the previous session is red,
+//! so following the first dependency that isn't green leads to the nodes
+//! that changed by themselves, such as the HIR of an edited item.
+//!
+//! The filter uses the same syntax as `RUST_DEP_GRAPH_FILTER`, for example
+//! `-Z explain-dep-graph='typeck & my_fn'` prints:
+//!
+//! ```text
+//! typeck(krate[1b2c]::my_fn): re-executed, result changed
+//!     because it reads hir_owner_nodes(krate[1b2c]::my_fn): re-executed, result changed
+//!     because it reads hir_crate(): re-executed, result changed
+//!     hir_crate() is re-executed in every session
+//! ```
+//!
+//! The same explanation can be computed after the fact, without compiling,
+//! from copies of the session directories of two consecutive sessions with
+//! `-Z explain-saved-dep-graphs=previous,current`. A node of the previous
+//! graph was then marked green if it is in the current graph with the same
+//! fingerprint, red if its fingerprint changed, and not marked at all if it
+//! isn't in the current graph. As there is no `TyCtxt` to resolve def paths
+//! from hashes, nodes are only labelled with their kind and hash.
+
+use crate::persist::{load_dep_graph_from_session_dir, LoadResult};
+use rustc_middle::dep_graph::{
+    DepContext, DepKind, DepNodeColor, DepNodeFilter, SerializedDepGraph, SerializedDepNodeIndex,
+};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+
+use std::fmt::Write;
+use std::path::Path;
+
+#[allow(missing_docs)]
+pub fn explain_dep_graph(tcx: TyCtxt<'_>) {
+    let Some(filter_text) = &tcx.sess.opts.unstable_opts.explain_dep_graph else {
+        return;
+    };
+    let Some(previous) = tcx.dep_graph.previous_graph() else {
+        return;
+    };
+
+    let colors = (0..previous.node_count())
+        .map(|index| match tcx.dep_graph.previous_node_color(SerializedDepNodeIndex::new(index)) {
+            Some(DepNodeColor::Green(_)) => Some(Color::Green),
+            Some(DepNodeColor::Red) => Some(Color::Red),
+            None => None,
+        })
+        .collect();
+    let explainer =
+        Explainer { previous, colors, is_eval_always: &|kind| tcx.is_eval_always(kind) };
+    explainer.print(filter_text);
+}
+
+/// Explains why the queries matching the `-Z explain-dep-graph` filter were
+/// re-executed, from the dependency graphs saved in the two incremental
+/// session directories given to `-Z explain-saved-dep-graphs`, without
+/// compiling anything.
+pub fn explain_saved_dep_graphs(sess: &Session, is_eval_always: &dyn Fn(DepKind) -> bool) {
+    let Some((previous_dir, current_dir)) = &sess.opts.unstable_opts.explain_saved_dep_graphs
+    else {
+        return;
+    };
+    let filter_text = sess.opts.unstable_opts.explain_dep_graph.as_deref().unwrap_or("");
+
+    let load = |session_dir: &Path| match load_dep_graph_from_session_dir(
+        session_dir,
+        sess.is_nightly_build(),
+    ) {
+        LoadResult::Ok { data } => data,
+        LoadResult::DataOutOfDate => sess.fatal(&format!(
+            "no dependency graph produced by this compiler version in `{}`",
+            session_dir.display()
+        )),
+        LoadResult::Error { message } => sess.fatal(&message),
+    };
+    let previous = load(previous_dir);
+    let current = load(current_dir);
+
+    // Marking a node green keeps its fingerprint, re-executing it computes a
+    // new one, and nodes that weren't needed aren't in the current graph.
+    let colors = (0..previous.node_count())
+        .map(SerializedDepNodeIndex::new)
+        .map(|prev_index| {
+            let fingerprint = current.fingerprint_of(&previous.index_to_node(prev_index))?;
+            if fingerprint == previous.fingerprint_by_index(prev_index) {
+                Some(Color::Green)
+            } else {
+                Some(Color::Red)
+            }
+        })
+        .collect();
+    let explainer = Explainer { previous: &previous, colors, is_eval_always };
+    explainer.print(filter_text);
+}
+
+/// The color a node of the previous graph was marked with in the explained
+/// session. Unlike `DepNodeColor`, it doesn't need the index of the node in
+/// the current graph, which isn't known when explaining saved graphs.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Color {
+    Green,
+    Red,
+}
+
+struct Explainer<'a> {
+    previous: &'a SerializedDepGraph,
+    /// The color of each node of the previous graph, `None` for the nodes
+    /// that weren't marked.
+    colors: Vec<Option<Color>>,
+    is_eval_always: &'a dyn Fn(DepKind) -> bool,
+}
+
+impl<'a> Explainer<'a> {
+    fn print(&self, filter_text: &str) {
+        let filter = DepNodeFilter::new(filter_text);
+        let mut explanations = Vec::new();
+        for prev_index in (0..self.previous.node_count()).map(SerializedDepNodeIndex::new) {
+            // Nodes that haven't been marked weren't needed in this session, and
+            // may refer to items that don't exist anymore.
+            let Some(color) = self.color(prev_index) else {
+                continue;
+            };
+            if filter.test(&self.previous.index_to_node(prev_index)) {
+                explanations.push(self.explain(prev_index, color));
+            }
+        }
+
+        if explanations.is_empty() {
+            println!(
+                "no node of the previous dependency graph needed in this session matches `{}`",
+                filter_text
+            );
+        }
+        explanations.sort();
+        for explanation in explanations {
+            print!("{}", explanation);
+        }
+    }
+
+    fn color(&self, prev_index: SerializedDepNodeIndex) -> Option<Color> {
+        self.colors[prev_index.index()]
+    }
+
+    fn explain(&self, prev_index: SerializedDepNodeIndex, color: Color) -> String {
+        let dep_node = self.previous.index_to_node(prev_index);
+        let mut explanation = format!("{:?}: {}\n", dep_node, self.status(prev_index, color));
+
+        // Follow the dependencies that prevented marking the nodes green, down
+        // to a node that was re-executed although its dependencies are green.
+        let mut current = dep_node;
+        let mut current_index = prev_index;
+        while let Some(dependency) = self.first_non_green_dependency(current_index) {
+            current = self.previous.index_to_node(dependency);
+            current_index = dependency;
+            let status = match self.color(dependency) {
+                Some(color) => self.status(dependency, color),
+                None => "never marked",
+            };
+            writeln!(explanation, "    because it reads {}: {}", self.label(dependency), status)
+                .unwrap();
+        }
+        if current_index != prev_index && (self.is_eval_always)(current.kind) {
+            writeln!(
+                explanation,
+                "    {} is re-executed in every session",
+                self.label(current_index)
+            )
+            .unwrap();
+        }
+
+        explanation
+    }
+
+    fn status(&self, prev_index: SerializedDepNodeIndex, color: Color) -> &'static str {
+        let dep_node = self.previous.index_to_node(prev_index);
+        match color {
+            Color::Red => "re-executed, result changed",
+            Color::Green
+                if (self.is_eval_always)(dep_node.kind)
+                    || self.first_non_green_dependency(prev_index).is_some() =>
+            {
+                "re-executed, result unchanged"
+            }
+            Color::Green => "reused",
+        }
+    }
+
+    /// Returns the first node read by the given node in the previous session
+    /// that isn't green. Marking nodes green stops at that node, which is why
+    /// the given node had to be re-executed.
+    fn first_non_green_dependency(
+        &self,
+        prev_index: SerializedDepNodeIndex,
+    ) -> Option<SerializedDepNodeIndex> {
+        self.previous
+            .edge_targets_from(prev_index)
+            .iter()
+            .copied()
+            .find(|&dependency| self.color(dependency) != Some(Color::Green))
+    }
+
+    fn label(&self, prev_index: SerializedDepNodeIndex) -> String {
+        let dep_node = self.previous.index_to_node(prev_index);
+        if self.color(prev_index).is_some() {
+            format!("{:?}", dep_node)
+        } else {
+            // Don't try to resolve the def path of nodes that weren't needed,
+            // their item may have been removed.
+            format!("{:?}({})", dep_node.kind, dep_node.hash)
+        }
+    }
+}
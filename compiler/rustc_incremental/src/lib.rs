@@ -13,9 +13,12 @@
 
 mod assert_dep_graph;
 pub mod assert_module_sources;
+mod explain_dep_graph;
 mod persist;
 
 use assert_dep_graph::assert_dep_graph;
+use explain_dep_graph::explain_dep_graph;
+pub use explain_dep_graph::explain_saved_dep_graphs;
 pub use persist::copy_cgu_workproduct_to_incr_comp_cache_dir;
 pub use persist::delete_workproduct_files;
 pub use persist::finalize_session_directory;
@@ -27,4 +30,6 @@
 pub use persist::save_dep_graph;
 pub use persist::save_work_product_index;
 pub use persist::LoadResult;
-pub use persist::{build_dep_graph, load_dep_graph, DepGraphFuture};
+pub use persist::{
+    build_dep_graph, load_dep_graph, load_dep_graph_from_session_dir, DepGraphFuture,
+};
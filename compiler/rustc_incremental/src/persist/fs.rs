@@ -137,6 +137,11 @@
 pub fn dep_graph_path(sess: &Session) -> PathBuf {
     in_incr_comp_dir_sess(sess, DEP_GRAPH_FILENAME)
 }
+/// Returns the path to the dependency graph saved in the given session directory,
+/// which doesn't need to be the directory of the current session.
+pub fn dep_graph_path_in_session_dir(session_dir: &Path) -> PathBuf {
+    in_incr_comp_dir(session_dir, DEP_GRAPH_FILENAME)
+}
 /// Returns the path to a session's staging dependency graph.
 ///
 /// On the difference between dep-graph and staging dep-graph,
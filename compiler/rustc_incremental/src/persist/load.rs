@@ -212,6 +212,26 @@ pub fn load_dep_graph(sess: &Session) -> DepGraphFuture {
     }))
 }
 
+/// Loads the dependency graph saved in an arbitrary session directory, for
+/// example a copy of the directory of a previous session, to inspect it
+/// outside of that session. Unlike [`load_dep_graph`], the graph is loaded
+/// whatever the command-line arguments it was produced with.
+pub fn load_dep_graph_from_session_dir(
+    session_dir: &Path,
+    nightly_build: bool,
+) -> LoadResult<SerializedDepGraph> {
+    let path = dep_graph_path_in_session_dir(session_dir);
+    match load_data(false, &path, nightly_build) {
+        LoadResult::DataOutOfDate => LoadResult::DataOutOfDate,
+        LoadResult::Error { message } => LoadResult::Error { message },
+        LoadResult::Ok { data: (bytes, start_pos) } => {
+            let mut decoder = MemDecoder::new(&bytes, start_pos);
+            let _prev_commandline_args_hash = u64::decode(&mut decoder);
+            LoadResult::Ok { data: SerializedDepGraph::decode(&mut decoder) }
+        }
+    }
+}
+
 /// Attempts to load the query result cache from disk
 ///
 /// If we are not in incremental compilation mode, returns `None`.
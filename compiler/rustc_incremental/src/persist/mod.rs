@@ -17,7 +17,7 @@
 pub use fs::prepare_session_directory;
 pub use load::load_query_result_cache;
 pub use load::LoadResult;
-pub use load::{load_dep_graph, DepGraphFuture};
+pub use load::{load_dep_graph, load_dep_graph_from_session_dir, DepGraphFuture};
 pub use save::build_dep_graph;
 pub use save::save_dep_graph;
 pub use save::save_work_product_index;
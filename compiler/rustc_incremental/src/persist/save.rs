@@ -37,6 +37,7 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
         let staging_dep_graph_path = staging_dep_graph_path(sess);
 
         sess.time("assert_dep_graph", || crate::assert_dep_graph(tcx));
+        sess.time("explain_dep_graph", || crate::explain_dep_graph(tcx));
         sess.time("check_dirty_clean", || dirty_clean::check_dirty_clean_annotations(tcx));
 
         if sess.opts.unstable_opts.incremental_info {
@@ -10,6 +10,7 @@
 use rustc_errors::registry::Registry;
 use rustc_errors::{ErrorGuaranteed, Handler};
 use rustc_lint::LintStore;
+use rustc_middle::mir::MirPassRegistry;
 use rustc_middle::ty;
 use rustc_parse::maybe_new_parser_from_source_str;
 use rustc_query_impl::QueryCtxt;
@@ -40,6 +41,7 @@ pub struct Compiler {
     pub(crate) register_lints: Option<Box<dyn Fn(&Session, &mut LintStore) + Send + Sync>>,
     pub(crate) override_queries:
         Option<fn(&Session, &mut ty::query::Providers, &mut ty::query::ExternProviders)>,
+    pub(crate) mir_pass_registry: Lrc<MirPassRegistry>,
 }
 
 impl Compiler {
@@ -268,6 +270,10 @@ pub struct Config {
     pub override_queries:
         Option<fn(&Session, &mut ty::query::Providers, &mut ty::query::ExternProviders)>,
 
+    /// MIR passes to run in addition to the built-in ones, at the points of the MIR pipeline they
+    /// have been registered for.
+    pub mir_pass_registry: MirPassRegistry,
+
     /// This is a callback from the driver that is called to create a codegen backend.
     pub make_codegen_backend:
         Option<Box<dyn FnOnce(&config::Options) -> Box<dyn CodegenBackend> + Send>>,
@@ -312,6 +318,7 @@ pub fn create_compiler_and_run<R>(config: Config, f: impl FnOnce(&Compiler) -> R
         temps_dir,
         register_lints: config.register_lints,
         override_queries: config.override_queries,
+        mir_pass_registry: Lrc::new(config.mir_pass_registry),
     };
 
     rustc_span::with_source_map(compiler.sess.parse_sess.clone_source_map(), move || {
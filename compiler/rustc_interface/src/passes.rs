@@ -832,6 +832,7 @@ pub fn create_global_ctxt<'tcx>(
             TyCtxt::create_global_ctxt(
                 sess,
                 lint_store,
+                compiler.mir_pass_registry.clone(),
                 arena,
                 hir_arena,
                 definitions,
@@ -981,6 +982,8 @@ fn analysis(tcx: TyCtxt<'_>, (): ()) -> Result<()> {
             }
         );
 
+        sess.time("dependency_usage", || CStore::from_tcx(tcx).report_dependency_usage(tcx));
+
         // This check has to be run after all lints are done processing. We don't
         // define a lint filter, as all lint checks should have finished at this point.
         sess.time("check_lint_expectations", || tcx.check_expectations(None));
@@ -391,6 +391,12 @@ pub fn enter<F, T>(&self, f: F) -> T
                 gcx.enter(rustc_query_impl::alloc_self_profile_query_strings);
             }
 
+            self.session().prof.with_profiler(|profiler| {
+                if let Err(err) = profiler.write_query_summary() {
+                    self.session().warn(&format!("failed to write self-profile summary: {err}"));
+                }
+            });
+
             self.session()
                 .time("serialize_dep_graph", || gcx.enter(rustc_incremental::save_dep_graph));
         }
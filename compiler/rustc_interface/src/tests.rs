@@ -2,6 +2,7 @@
 use crate::interface::parse_cfgspecs;
 
 use rustc_data_structures::fx::FxHashSet;
+use rustc_data_structures::profiling::SelfProfileSummaryFormat;
 use rustc_errors::{emitter::HumanReadableErrorType, registry, ColorConfig};
 use rustc_session::config::InstrumentCoverage;
 use rustc_session::config::Strip;
@@ -10,8 +11,9 @@
     rustc_optgroups, ErrorOutputType, ExternLocation, LocationDetail, Options, Passes,
 };
 use rustc_session::config::{
-    BranchProtection, Externs, OomStrategy, OutputType, OutputTypes, PAuthKey, PacRet,
-    ProcMacroExecutionStrategy, SymbolManglingVersion, WasiExecModel,
+    BranchProtection, DepGraphDumpFormat, Externs, MirDumpFormat, NllFactsFormat, OomStrategy,
+    OutputType, OutputTypes, PAuthKey, PacRet, ProcMacroExecutionStrategy, SymbolManglingVersion,
+    WasiExecModel,
 };
 use rustc_session::config::{CFGuard, ExternEntry, LinkerPluginLto, LtoCli, SwitchWithOptPath};
 use rustc_session::lint::Level;
@@ -647,16 +649,25 @@ macro_rules! untracked {
     untracked!(assert_incr_state, Some(String::from("loaded")));
     untracked!(deduplicate_diagnostics, false);
     untracked!(dep_tasks, true);
+    untracked!(describe_lints, true);
     untracked!(dlltool, Some(PathBuf::from("custom_dlltool.exe")));
     untracked!(dont_buffer_diagnostics, true);
     untracked!(dump_dep_graph, true);
+    untracked!(dump_dep_graph_format, DepGraphDumpFormat::Json);
     untracked!(dump_drop_tracking_cfg, Some("cfg.dot".to_string()));
     untracked!(dump_mir, Some(String::from("abc")));
     untracked!(dump_mir_dataflow, true);
     untracked!(dump_mir_dir, String::from("abc"));
     untracked!(dump_mir_exclude_pass_number, true);
+    untracked!(dump_mir_format, MirDumpFormat::Json);
     untracked!(dump_mir_graphviz, true);
     untracked!(emit_stack_sizes, true);
+    untracked!(explain_borrowck, Some(String::from("my_fn")));
+    untracked!(explain_dep_graph, Some(String::from("typeck")));
+    untracked!(
+        explain_saved_dep_graphs,
+        Some((PathBuf::from("incr/previous"), PathBuf::from("incr/current")))
+    );
     untracked!(future_incompat_test, true);
     untracked!(hir_stats, true);
     untracked!(identify_regions, true);
@@ -672,6 +683,7 @@ macro_rules! untracked {
     untracked!(meta_stats, true);
     untracked!(mir_pretty_relative_line_numbers, true);
     untracked!(nll_facts, true);
+    untracked!(nll_facts_format, NllFactsFormat::Json);
     untracked!(no_analysis, true);
     untracked!(no_interleave_lints, true);
     untracked!(no_leak_check, true);
@@ -682,6 +694,7 @@ macro_rules! untracked {
     untracked!(pre_link_args, vec![String::from("abc"), String::from("def")]);
     untracked!(profile_closures, true);
     untracked!(print_llvm_passes, true);
+    untracked!(print_mono_item_stats, true);
     untracked!(print_mono_items, Some(String::from("abc")));
     untracked!(print_type_sizes, true);
     untracked!(proc_macro_backtrace, true);
@@ -690,6 +703,7 @@ macro_rules! untracked {
     untracked!(save_analysis, true);
     untracked!(self_profile, SwitchWithOptPath::Enabled(None));
     untracked!(self_profile_events, Some(vec![String::new()]));
+    untracked!(self_profile_summary, Some(SelfProfileSummaryFormat::Json));
     untracked!(span_debug, true);
     untracked!(span_free_formats, true);
     untracked!(temps_dir, Some(String::from("abc")));
@@ -731,6 +745,7 @@ macro_rules! tracked {
     );
     tracked!(chalk, true);
     tracked!(codegen_backend, Some("abc".to_string()));
+    tracked!(const_eval_trace, true);
     tracked!(crate_attr, vec!["abc".to_string()]);
     tracked!(debug_info_for_profiling, true);
     tracked!(debug_macros, true);
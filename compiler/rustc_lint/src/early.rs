@@ -54,13 +54,19 @@ fn check_id(&mut self, id: ast::NodeId) {
 
     /// Merge the lints specified by any lint attributes into the
     /// current lint context, call the provided function, then reset the
-    /// lints in effect to their previous state.
-    fn with_lint_attrs<F>(&mut self, id: ast::NodeId, attrs: &'a [ast::Attribute], f: F)
-    where
+    /// lints in effect to their previous state. `item_span` is the span of
+    /// the node if it is an item.
+    fn with_lint_attrs<F>(
+        &mut self,
+        id: ast::NodeId,
+        attrs: &'a [ast::Attribute],
+        item_span: Option<Span>,
+        f: F,
+    ) where
         F: FnOnce(&mut Self),
     {
         let is_crate_node = id == ast::CRATE_NODE_ID;
-        let push = self.context.builder.push(attrs, is_crate_node, None);
+        let push = self.context.builder.push(attrs, is_crate_node, None, item_span);
 
         self.check_id(id);
         debug!("early context: enter_attrs({:?})", attrs);
@@ -74,14 +80,14 @@ fn with_lint_attrs<F>(&mut self, id: ast::NodeId, attrs: &'a [ast::Attribute], f
 
 impl<'a, T: EarlyLintPass> ast_visit::Visitor<'a> for EarlyContextAndPass<'a, T> {
     fn visit_param(&mut self, param: &'a ast::Param) {
-        self.with_lint_attrs(param.id, &param.attrs, |cx| {
+        self.with_lint_attrs(param.id, &param.attrs, None, |cx| {
             run_early_pass!(cx, check_param, param);
             ast_visit::walk_param(cx, param);
         });
     }
 
     fn visit_item(&mut self, it: &'a ast::Item) {
-        self.with_lint_attrs(it.id, &it.attrs, |cx| {
+        self.with_lint_attrs(it.id, &it.attrs, Some(it.span), |cx| {
             run_early_pass!(cx, check_item, it);
             ast_visit::walk_item(cx, it);
             run_early_pass!(cx, check_item_post, it);
@@ -89,7 +95,7 @@ fn visit_item(&mut self, it: &'a ast::Item) {
     }
 
     fn visit_foreign_item(&mut self, it: &'a ast::ForeignItem) {
-        self.with_lint_attrs(it.id, &it.attrs, |cx| {
+        self.with_lint_attrs(it.id, &it.attrs, Some(it.span), |cx| {
             ast_visit::walk_foreign_item(cx, it);
         })
     }
@@ -102,7 +108,7 @@ fn visit_pat(&mut self, p: &'a ast::Pat) {
     }
 
     fn visit_pat_field(&mut self, field: &'a ast::PatField) {
-        self.with_lint_attrs(field.id, &field.attrs, |cx| {
+        self.with_lint_attrs(field.id, &field.attrs, None, |cx| {
             ast_visit::walk_pat_field(cx, field);
         });
     }
@@ -113,14 +119,14 @@ fn visit_anon_const(&mut self, c: &'a ast::AnonConst) {
     }
 
     fn visit_expr(&mut self, e: &'a ast::Expr) {
-        self.with_lint_attrs(e.id, &e.attrs, |cx| {
+        self.with_lint_attrs(e.id, &e.attrs, None, |cx| {
             run_early_pass!(cx, check_expr, e);
             ast_visit::walk_expr(cx, e);
         })
     }
 
     fn visit_expr_field(&mut self, f: &'a ast::ExprField) {
-        self.with_lint_attrs(f.id, &f.attrs, |cx| {
+        self.with_lint_attrs(f.id, &f.attrs, None, |cx| {
             ast_visit::walk_expr_field(cx, f);
         })
     }
@@ -134,7 +140,7 @@ fn visit_stmt(&mut self, s: &'a ast::Stmt) {
         //
         // Note that statements get their attributes from
         // the AST struct that they wrap (e.g. an item)
-        self.with_lint_attrs(s.id, s.attrs(), |cx| {
+        self.with_lint_attrs(s.id, s.attrs(), None, |cx| {
             run_early_pass!(cx, check_stmt, s);
             cx.check_id(s.id);
         });
@@ -167,13 +173,13 @@ fn visit_variant_data(&mut self, s: &'a ast::VariantData) {
     }
 
     fn visit_field_def(&mut self, s: &'a ast::FieldDef) {
-        self.with_lint_attrs(s.id, &s.attrs, |cx| {
+        self.with_lint_attrs(s.id, &s.attrs, None, |cx| {
             ast_visit::walk_field_def(cx, s);
         })
     }
 
     fn visit_variant(&mut self, v: &'a ast::Variant) {
-        self.with_lint_attrs(v.id, &v.attrs, |cx| {
+        self.with_lint_attrs(v.id, &v.attrs, None, |cx| {
             run_early_pass!(cx, check_variant, v);
             ast_visit::walk_variant(cx, v);
         })
@@ -190,7 +196,7 @@ fn visit_ident(&mut self, ident: Ident) {
     }
 
     fn visit_local(&mut self, l: &'a ast::Local) {
-        self.with_lint_attrs(l.id, &l.attrs, |cx| {
+        self.with_lint_attrs(l.id, &l.attrs, None, |cx| {
             run_early_pass!(cx, check_local, l);
             ast_visit::walk_local(cx, l);
         })
@@ -203,7 +209,7 @@ fn visit_block(&mut self, b: &'a ast::Block) {
     }
 
     fn visit_arm(&mut self, a: &'a ast::Arm) {
-        self.with_lint_attrs(a.id, &a.attrs, |cx| {
+        self.with_lint_attrs(a.id, &a.attrs, None, |cx| {
             run_early_pass!(cx, check_arm, a);
             ast_visit::walk_arm(cx, a);
         })
@@ -225,7 +231,7 @@ fn visit_generic_arg(&mut self, arg: &'a ast::GenericArg) {
     }
 
     fn visit_generic_param(&mut self, param: &'a ast::GenericParam) {
-        self.with_lint_attrs(param.id, &param.attrs, |cx| {
+        self.with_lint_attrs(param.id, &param.attrs, None, |cx| {
             run_early_pass!(cx, check_generic_param, param);
             ast_visit::walk_generic_param(cx, param);
         });
@@ -246,7 +252,7 @@ fn visit_poly_trait_ref(&mut self, t: &'a ast::PolyTraitRef) {
     }
 
     fn visit_assoc_item(&mut self, item: &'a ast::AssocItem, ctxt: ast_visit::AssocCtxt) {
-        self.with_lint_attrs(item.id, &item.attrs, |cx| match ctxt {
+        self.with_lint_attrs(item.id, &item.attrs, Some(item.span), |cx| match ctxt {
             ast_visit::AssocCtxt::Trait => {
                 run_early_pass!(cx, check_trait_item, item);
                 ast_visit::walk_assoc_item(cx, item, ctxt);
@@ -389,7 +395,7 @@ fn early_lint_node<'a>(
         pass,
     };
 
-    cx.with_lint_attrs(check_node.id(), check_node.attrs(), |cx| check_node.check(cx));
+    cx.with_lint_attrs(check_node.id(), check_node.attrs(), None, |cx| check_node.check(cx));
     cx.context.buffered
 }
 
@@ -20,7 +20,7 @@
 use rustc_session::parse::{add_feature_diagnostics, feature_err};
 use rustc_session::Session;
 use rustc_span::symbol::{sym, Symbol};
-use rustc_span::{Span, DUMMY_SP};
+use rustc_span::{BytePos, FileName, Span, DUMMY_SP};
 use tracing::debug;
 
 fn lint_levels(tcx: TyCtxt<'_>, (): ()) -> LintLevelMap {
@@ -32,14 +32,22 @@ fn lint_levels(tcx: TyCtxt<'_>, (): ()) -> LintLevelMap {
 
     builder.levels.id_to_set.reserve(krate.owners.len() + 1);
 
-    let push =
-        builder.levels.push(tcx.hir().attrs(hir::CRATE_HIR_ID), true, Some(hir::CRATE_HIR_ID));
+    let push = builder.levels.push(
+        tcx.hir().attrs(hir::CRATE_HIR_ID),
+        true,
+        Some(hir::CRATE_HIR_ID),
+        None,
+    );
+    let crate_set = builder.levels.cur;
 
     builder.levels.register_id(hir::CRATE_HIR_ID);
     tcx.hir().walk_toplevel_module(&mut builder);
     builder.levels.pop(push);
 
     builder.levels.update_unstable_expectation_ids();
+    if tcx.sess.opts.unstable_opts.describe_lints {
+        builder.levels.describe_lint_levels(crate_set);
+    }
     builder.levels.build_map()
 }
 
@@ -55,10 +63,16 @@ pub struct LintLevelsBuilder<'s> {
     warn_about_weird_lints: bool,
     store: &'s LintStore,
     registered_tools: &'s RegisteredTools,
+    /// The index of the `--lint-config` override whose levels are in effect.
+    cur_path_override: Option<usize>,
+    /// The index of the override matching each source file, by the start
+    /// position of the file.
+    path_overrides: FxHashMap<BytePos, Option<usize>>,
 }
 
 pub struct BuilderPush {
     prev: LintStackIndex,
+    prev_path_override: Option<usize>,
     pub changed: bool,
 }
 
@@ -79,6 +93,8 @@ pub fn new(
             warn_about_weird_lints,
             store,
             registered_tools,
+            cur_path_override: None,
+            path_overrides: Default::default(),
         };
         builder.process_command_line(sess, store);
         assert_eq!(builder.sets.list.len(), 1);
@@ -106,6 +122,10 @@ fn process_command_line(&mut self, sess: &Session, store: &LintStore) {
 
         self.cur =
             self.sets.list.push(LintSet { specs: FxHashMap::default(), parent: COMMAND_LINE });
+        // The levels of the `--lint-config` file are overridden by the flags.
+        if let Some(lint_config) = &sess.opts.lint_config {
+            self.insert_config_levels(&lint_config.lints, None);
+        }
         for &(ref lint_name, level) in &sess.opts.lint_opts {
             store.check_lint_name_cmdline(sess, &lint_name, level, self.registered_tools);
             let orig_level = level;
@@ -131,6 +151,60 @@ fn process_command_line(&mut self, sess: &Session, store: &LintStore) {
         }
     }
 
+    /// Inserts the levels of the `--lint-config` file, or of its override for
+    /// `path`. Lint groups are inserted first, so that the level of a lint
+    /// takes precedence over the level of its groups, like on the command line
+    /// where the last flag wins.
+    fn insert_config_levels(&mut self, lints: &[(String, Level)], path: Option<Symbol>) {
+        let (groups, lints): (Vec<_>, Vec<_>) =
+            lints.iter().partition(|(name, _)| self.store.is_lint_group(Symbol::intern(name)));
+        for &(ref name, level) in groups.into_iter().chain(lints) {
+            let ids = match self.store.find_lints(name) {
+                Ok(ids) => ids,
+                // Like in attributes, the lints of a tool are unknown when the
+                // tool isn't running.
+                Err(_) if name.contains("::") => continue,
+                Err(_) => {
+                    self.sess.warn(&format!(
+                        "unknown lint `{}` in the `--lint-config` file",
+                        name.replace('_', "-")
+                    ));
+                    continue;
+                }
+            };
+            let src = LintLevelSource::ConfigFile(Symbol::intern(name), path);
+            for id in ids {
+                if self.check_gated_lint(id, DUMMY_SP) {
+                    self.insert_spec(id, (level, src));
+                }
+            }
+        }
+    }
+
+    /// Inserts the levels of the last `--lint-config` override matching the
+    /// source file `span` is in, unless they are already in effect.
+    fn insert_path_override(&mut self, span: Span) {
+        let sess = self.sess;
+        let Some(lint_config) = &sess.opts.lint_config else { return };
+        if lint_config.overrides.is_empty() || span.is_dummy() {
+            return;
+        }
+        let source_file = sess.source_map().lookup_source_file(span.lo());
+        let index = *self.path_overrides.entry(source_file.start_pos).or_insert_with(|| {
+            let FileName::Real(name) = &source_file.name else { return None };
+            let working_dir = sess.opts.working_dir.local_path_if_available();
+            lint_config.override_for(name.local_path()?, working_dir)
+        });
+        let Some(index) = index else { return };
+        if self.cur_path_override == Some(index) {
+            return;
+        }
+        self.cur_path_override = Some(index);
+        let config_override = &lint_config.overrides[index];
+        let path = Symbol::intern(&config_override.paths.join(", "));
+        self.insert_config_levels(&config_override.lints, Some(path));
+    }
+
     /// Attempts to insert the `id` to `level_src` map entry. If unsuccessful
     /// (e.g. if a forbid was already inserted on the same scope), then emits a
     /// diagnostic with no change to `specs`.
@@ -156,6 +230,7 @@ fn insert_spec(&mut self, id: LintId, (level, src): LevelAndSource) {
                     LintLevelSource::Default => false,
                     LintLevelSource::Node(symbol, _, _) => self.store.is_lint_group(symbol),
                     LintLevelSource::CommandLine(symbol, _) => self.store.is_lint_group(symbol),
+                    LintLevelSource::ConfigFile(symbol, _) => self.store.is_lint_group(symbol),
                 };
                 debug!(
                     "fcw_warning={:?}, specs.get(&id) = {:?}, old_src={:?}, id_name={:?}",
@@ -183,6 +258,9 @@ fn insert_spec(&mut self, id: LintId, (level, src): LevelAndSource) {
                         LintLevelSource::CommandLine(_, _) => {
                             diag.note("`forbid` lint level was set on command line");
                         }
+                        LintLevelSource::ConfigFile(_, _) => {
+                            diag.note("`forbid` lint level was set in the `--lint-config` file");
+                        }
                     }
                 };
                 if !fcw_warning {
@@ -254,6 +332,8 @@ fn insert_spec(&mut self, id: LintId, (level, src): LevelAndSource) {
     /// * Lint levels will be updated based on the attributes provided
     /// * Lint attributes are validated, e.g., a `#[forbid]` can't be switched to
     ///   `#[allow]`
+    /// * If `item_span` is the span of an item, the levels of the `--lint-config`
+    ///   override for its source file are applied before the attributes
     ///
     /// Don't forget to call `pop`!
     pub(crate) fn push(
@@ -261,9 +341,14 @@ pub(crate) fn push(
         attrs: &[ast::Attribute],
         is_crate_node: bool,
         source_hir_id: Option<HirId>,
+        item_span: Option<Span>,
     ) -> BuilderPush {
         let prev = self.cur;
+        let prev_path_override = self.cur_path_override;
         self.cur = self.sets.list.push(LintSet { specs: FxHashMap::default(), parent: prev });
+        if let Some(span) = item_span {
+            self.insert_path_override(span);
+        }
 
         let sess = self.sess;
         let bad_attr = |span| struct_span_err!(sess, span, E0452, "malformed lint attribute input");
@@ -627,7 +712,7 @@ pub(crate) fn push(
             self.cur = prev;
         }
 
-        BuilderPush { prev, changed: prev != self.cur }
+        BuilderPush { prev, prev_path_override, changed: prev != self.cur }
     }
 
     fn create_stable_id(
@@ -668,6 +753,7 @@ fn check_gated_lint(&self, lint_id: LintId, span: Span) -> bool {
     /// Called after `push` when the scope of a set of attributes are exited.
     pub fn pop(&mut self, push: BuilderPush) {
         self.cur = push.prev;
+        self.cur_path_override = push.prev_path_override;
     }
 
     /// Find the lint level for a lint.
@@ -693,6 +779,84 @@ pub fn register_id(&mut self, id: HirId) {
         self.id_to_set.insert(id, self.cur);
     }
 
+    /// Prints the level of every lint at the crate root, and in the files
+    /// matched by the overrides of the `--lint-config` file, with where the
+    /// level was set. This is `-Z describe-lints`.
+    fn describe_lint_levels(&mut self, crate_set: LintStackIndex) {
+        let mut lints = self.store.get_lints().to_vec();
+        lints.sort_by_key(|lint| lint.name_lower());
+        let levels_at = |builder: &Self, set| -> Vec<_> {
+            lints
+                .iter()
+                .map(|&lint| (lint, builder.sets.get_lint_level(lint, set, None, builder.sess)))
+                .collect()
+        };
+        let crate_levels = levels_at(self, crate_set);
+        println!("Lint levels at the crate root:\n");
+        self.print_lint_levels(&crate_levels);
+
+        let sess = self.sess;
+        let Some(lint_config) = &sess.opts.lint_config else { return };
+        let prev = self.cur;
+        for config_override in &lint_config.overrides {
+            // The levels of an override are applied on top of those of the
+            // crate root, like for an item at the root of a matched file.
+            let path = Symbol::intern(&config_override.paths.join(", "));
+            self.cur =
+                self.sets.list.push(LintSet { specs: FxHashMap::default(), parent: crate_set });
+            self.insert_config_levels(&config_override.lints, Some(path));
+            let levels: Vec<_> = levels_at(self, self.cur)
+                .into_iter()
+                .zip(&crate_levels)
+                .filter(|((_, (level, _)), (_, (crate_level, _)))| level != crate_level)
+                .map(|(levels, _)| levels)
+                .collect();
+            println!("\nLint levels that differ in the files under `{}`:\n", path);
+            self.print_lint_levels(&levels);
+        }
+        self.cur = prev;
+    }
+
+    fn print_lint_levels(&self, levels: &[(&'static Lint, LevelAndSource)]) {
+        let source = |src: LintLevelSource| match src {
+            LintLevelSource::Default => "default".to_string(),
+            LintLevelSource::Node(name, span, _) => format!(
+                "attribute `{}` at {}",
+                name,
+                self.sess.source_map().span_to_embeddable_string(span)
+            ),
+            LintLevelSource::CommandLine(name, level) => {
+                let flag = match level {
+                    Level::Deny => "-D",
+                    Level::Forbid => "-F",
+                    Level::Allow => "-A",
+                    Level::ForceWarn(_) => "--force-warn",
+                    Level::Warn | Level::Expect(_) => "-W",
+                };
+                format!("`{} {}` on the command line", flag, name.as_str().replace('_', "-"))
+            }
+            LintLevelSource::ConfigFile(name, None) => {
+                format!("`{}` in the `--lint-config` file", name.as_str().replace('_', "-"))
+            }
+            LintLevelSource::ConfigFile(name, Some(path)) => format!(
+                "`{}` in the `--lint-config` override for `{}`",
+                name.as_str().replace('_', "-"),
+                path
+            ),
+        };
+        let rows: Vec<_> = levels
+            .iter()
+            .map(|&(lint, (level, src))| {
+                (lint.name_lower().replace('_', "-"), level.as_str(), source(src))
+            })
+            .collect();
+        let name_width = rows.iter().map(|(name, ..)| name.chars().count()).max().unwrap_or(0);
+        let level_width = rows.iter().map(|(_, level, _)| level.len()).max().unwrap_or(0);
+        for (name, level, source) in rows {
+            println!("    {:name_width$}  {:level_width$}  {}", name, level, source);
+        }
+    }
+
     fn update_unstable_expectation_ids(&self) {
         self.sess.diagnostic().update_unstable_expectation_id(&self.expectation_id_map);
     }
@@ -718,7 +882,8 @@ fn with_lint_attrs<F>(&mut self, id: hir::HirId, f: F)
     {
         let is_crate_hir = id == hir::CRATE_HIR_ID;
         let attrs = self.tcx.hir().attrs(id);
-        let push = self.levels.push(attrs, is_crate_hir, Some(id));
+        let item_span = (id.is_owner() && !is_crate_hir).then(|| self.tcx.hir().span(id));
+        let push = self.levels.push(attrs, is_crate_hir, Some(id), item_span);
 
         if push.changed {
             self.levels.register_id(id);
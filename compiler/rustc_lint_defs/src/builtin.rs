@@ -440,6 +440,55 @@
     crate_level_only
 }
 
+declare_lint! {
+    /// The `glob_only_crate_dependencies` lint detects crate dependencies
+    /// that are only named by glob imports.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,ignore (needs extern crate)
+    /// #![warn(glob_only_crate_dependencies)]
+    ///
+    /// use regex::*;
+    ///
+    /// fn main() {
+    ///     Regex::new("a+").unwrap();
+    /// }
+    /// ```
+    ///
+    /// This will produce:
+    ///
+    /// ```text
+    /// warning: external crate `regex` is only used through a glob import
+    ///  --> src/main.rs:3:1
+    ///   |
+    /// 3 | use regex::*;
+    ///   | ^^^^^^^^^^^^^
+    ///   |
+    /// note: the lint level is defined here
+    ///  --> src/main.rs:1:9
+    ///   |
+    /// 1 | #![warn(glob_only_crate_dependencies)]
+    ///   |         ^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+    ///   = help: import the items used from `regex` by name
+    /// ```
+    ///
+    /// ### Explanation
+    ///
+    /// When a dependency is only named by glob imports, the items used from
+    /// it can't be found by searching the source code for the name of the
+    /// crate. This makes it hard to tell whether the dependency is still
+    /// needed, and easy to remove a dependency that is used, or to keep one
+    /// that isn't. Importing the used items by name avoids this.
+    ///
+    /// This lint is "allow" by default because glob imports are often used on
+    /// purpose, for example to import the prelude of a crate. It is only
+    /// checked when it is enabled at the crate level or on the command line.
+    pub GLOB_ONLY_CRATE_DEPENDENCIES,
+    Allow,
+    "crate dependencies that are only used through glob imports"
+}
+
 declare_lint! {
     /// The `unused_qualifications` lint detects unnecessarily qualified
     /// names.
@@ -3223,6 +3272,7 @@
         UNUSED_IMPORTS,
         UNUSED_EXTERN_CRATES,
         UNUSED_CRATE_DEPENDENCIES,
+        GLOB_ONLY_CRATE_DEPENDENCIES,
         UNUSED_QUALIFICATIONS,
         UNKNOWN_LINTS,
         UNFULFILLED_LINT_EXPECTATIONS,
@@ -1,5 +1,6 @@
 //! Validates all used crates and extern libraries and loads their metadata
 
+use crate::dependency_usage::collect_crate_usage;
 use crate::locator::{CrateError, CrateLocator, CratePaths};
 use crate::rmeta::{CrateDep, CrateMetadata, CrateNumMap, CrateRoot, MetadataBlob};
 
@@ -8,12 +9,13 @@
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::svh::Svh;
 use rustc_data_structures::sync::Lrc;
+use rustc_errors::json::DependencyUsage;
 use rustc_expand::base::SyntaxExtension;
 use rustc_hir::def_id::{CrateNum, LocalDefId, StableCrateId, LOCAL_CRATE};
 use rustc_hir::definitions::Definitions;
 use rustc_index::vec::IndexVec;
 use rustc_middle::ty::TyCtxt;
-use rustc_session::config::{self, CrateType, ExternLocation};
+use rustc_session::config::{self, CrateType, ExternEntry, ExternLocation};
 use rustc_session::cstore::{CrateDepKind, CrateSource, ExternCrate};
 use rustc_session::cstore::{ExternCrateSource, MetadataLoaderDyn};
 use rustc_session::lint;
@@ -47,6 +49,10 @@ pub struct CStore {
 
     /// Unused externs of the crate
     unused_externs: Vec<Symbol>,
+
+    /// The crates loaded for the names of the extern prelude that the crate
+    /// refers to.
+    extern_crate_nums: FxHashMap<Symbol, CrateNum>,
 }
 
 impl std::fmt::Debug for CStore {
@@ -221,6 +227,76 @@ pub fn report_unused_deps(&self, tcx: TyCtxt<'_>) {
             );
         }
     }
+
+    /// Reports how each dependency passed with `--extern` is used, with
+    /// `--json=dependency-usage`, and lints the dependencies that are only
+    /// named by glob imports.
+    pub fn report_dependency_usage(&self, tcx: TyCtxt<'_>) {
+        let lint = lint::builtin::GLOB_ONLY_CRATE_DEPENDENCIES;
+        let lint_level = tcx.lint_level_at_node(lint, rustc_hir::CRATE_HIR_ID).0;
+        if !tcx.sess.opts.json_dependency_usage && lint_level == lint::Level::Allow {
+            return;
+        }
+
+        let crate_usage = collect_crate_usage(tcx);
+        let mut report = Vec::new();
+        for (name, entry) in tcx.sess.opts.externs.iter() {
+            if let ExternLocation::FoundInLibrarySearchDirectories = entry.location {
+                // Don't worry about pathless `--extern foo` sysroot references
+                continue;
+            }
+            if entry.nounused_dep {
+                continue;
+            }
+            let usage = self.extern_crate_num(name, entry).and_then(|cnum| crate_usage.get(&cnum));
+            let Some(usage) = usage else {
+                report.push(DependencyUsage {
+                    name: name.clone(),
+                    usage: "unused",
+                    reexported_through: Vec::new(),
+                    macros: Vec::new(),
+                });
+                continue;
+            };
+
+            let kind = usage.kind();
+            if kind == "glob-only" {
+                for &(hir_id, span) in &usage.glob_imports {
+                    tcx.struct_span_lint_hir(lint, hir_id, span, |lint| {
+                        lint.build(&format!(
+                            "external crate `{}` is only used through a glob import",
+                            name
+                        ))
+                        .help(&format!("import the items used from `{}` by name", name))
+                        .emit();
+                    });
+                }
+            }
+            report.push(DependencyUsage {
+                name: name.clone(),
+                usage: kind,
+                reexported_through: usage.reexported_through.iter().cloned().collect(),
+                macros: usage.macros.iter().cloned().collect(),
+            });
+        }
+
+        if tcx.sess.opts.json_dependency_usage {
+            tcx.sess.parse_sess.span_diagnostic.emit_dependency_usage(&report);
+        }
+    }
+
+    /// Returns the crate passed with `--extern name`. It was loaded for
+    /// `name` if the crate refers to it, and otherwise may have been loaded
+    /// as a dependency of another crate, from the same file.
+    fn extern_crate_num(&self, name: &str, entry: &ExternEntry) -> Option<CrateNum> {
+        if let Some(&cnum) = self.extern_crate_nums.get(&Symbol::intern(name)) {
+            return Some(cnum);
+        }
+        let files: Vec<_> = entry.files()?.map(|file| file.canonicalized()).collect();
+        self.iter_crate_data()
+            .find(|(_, data)| data.source().paths().any(|path| files.contains(&path)))
+            .map(|(cnum, _)| cnum)
+    }
 }
 
 impl<'a> CrateLoader<'a> {
@@ -247,6 +323,7 @@ pub fn new(
                 has_global_allocator: false,
                 stable_crate_ids,
                 unused_externs: Vec::new(),
+                extern_crate_nums: Default::default(),
             },
             used_extern_options: Default::default(),
         }
@@ -506,7 +583,10 @@ fn resolve_crate<'b>(
     ) -> Option<CrateNum> {
         self.used_extern_options.insert(name);
         match self.maybe_resolve_crate(name, dep_kind, None) {
-            Ok(cnum) => Some(cnum),
+            Ok(cnum) => {
+                self.cstore.extern_crate_nums.insert(name, cnum);
+                Some(cnum)
+            }
             Err(err) => {
                 let missing_core =
                     self.maybe_resolve_crate(sym::core, CrateDepKind::Explicit, None).is_err();
@@ -0,0 +1,229 @@
+//! Finds out how the crate uses its dependencies, for the
+//! `--json=dependency-usage` report and the `glob_only_crate_dependencies`
+//! lint.
+//!
+//! A dependency is needed by the crate if one of its paths names it. The
+//! dependencies whose items are only reached through the re-exports of other
+//! crates, or that are only used by macro expansions or in `#[cfg(test)]`
+//! code, are told apart so that a build system can tell which dependencies
+//! can be removed, or moved to the development dependencies.
+
+use rustc_ast as ast;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir as hir;
+use rustc_hir::def::Res;
+use rustc_hir::def_id::{CrateNum, DefId, LOCAL_CRATE};
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_hir::HirId;
+use rustc_middle::hir::nested_filter;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::hygiene::SyntaxContext;
+use rustc_span::symbol::{kw, sym};
+use rustc_span::Span;
+
+use std::collections::BTreeSet;
+
+/// How a dependency is used by the crate.
+#[derive(Default)]
+pub(crate) struct CrateUsage {
+    /// The crate is named by a path outside of glob imports.
+    pub named: bool,
+    /// The glob imports naming the crate.
+    pub glob_imports: Vec<(HirId, Span)>,
+    /// The macros whose expansions use the crate.
+    pub macros: BTreeSet<String>,
+    /// The paths reaching items of the crate through the re-exports of other
+    /// crates.
+    pub reexported_through: BTreeSet<String>,
+    /// The crate is named by an `extern crate` item.
+    pub extern_crate: bool,
+    /// The crate is used in `#[cfg(test)]` code.
+    pub tests: bool,
+}
+
+impl CrateUsage {
+    /// Classifies the usage of the crate, from the one that requires the
+    /// dependency the most to the one that requires it the least.
+    pub fn kind(&self) -> &'static str {
+        if self.named {
+            "used"
+        } else if !self.glob_imports.is_empty() {
+            "glob-only"
+        } else if !self.macros.is_empty() {
+            "macros-only"
+        } else if !self.reexported_through.is_empty() {
+            "reexports-only"
+        } else if self.extern_crate {
+            "extern-crate-only"
+        } else if self.tests {
+            "test-only"
+        } else {
+            "unused"
+        }
+    }
+}
+
+/// Walks the HIR of the crate to find out how it uses each of the crates it
+/// depends on.
+pub(crate) fn collect_crate_usage(tcx: TyCtxt<'_>) -> FxHashMap<CrateNum, CrateUsage> {
+    let mut collector = UsageCollector {
+        tcx,
+        usage: Default::default(),
+        in_tests: false,
+        seen_expansions: Default::default(),
+    };
+    tcx.hir().walk_toplevel_module(&mut collector);
+    collector.usage
+}
+
+struct UsageCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    usage: FxHashMap<CrateNum, CrateUsage>,
+    /// Whether the visitor is in a `#[cfg(test)]` item.
+    in_tests: bool,
+    /// The syntax contexts whose macro backtrace was already recorded, with
+    /// whether they were in `#[cfg(test)]` code.
+    seen_expansions: FxHashSet<(SyntaxContext, bool)>,
+}
+
+impl<'tcx> UsageCollector<'tcx> {
+    fn usage(&mut self, cnum: CrateNum) -> &mut CrateUsage {
+        self.usage.entry(cnum).or_default()
+    }
+
+    /// Records the macros of other crates that `span` was expanded from.
+    fn record_expansion(&mut self, span: Span) {
+        if !span.from_expansion() || !self.seen_expansions.insert((span.ctxt(), self.in_tests)) {
+            return;
+        }
+        for expn_data in span.macro_backtrace() {
+            let Some(def_id) = expn_data.macro_def_id else { continue };
+            if def_id.is_local() {
+                continue;
+            }
+            let name = self.tcx.def_path_str(def_id);
+            let in_tests = self.in_tests;
+            let usage = self.usage(def_id.krate);
+            if in_tests {
+                usage.tests = true;
+            } else {
+                usage.macros.insert(name);
+            }
+        }
+    }
+
+    /// Returns the macro of another crate whose expansion `span` comes from.
+    fn extern_macro(&self, span: Span) -> Option<DefId> {
+        span.ctxt().outer_expn_data().macro_def_id.filter(|def_id| !def_id.is_local())
+    }
+
+    /// Records a use of the crate `cnum` at `span`: as a use in tests, as a
+    /// use by the macro `span` was expanded from, or with `record` for a use
+    /// by the code of this crate.
+    fn record_use(&mut self, cnum: CrateNum, span: Span, record: impl FnOnce(&mut CrateUsage)) {
+        if self.in_tests {
+            self.usage(cnum).tests = true;
+        } else if let Some(macro_def_id) = self.extern_macro(span) {
+            let name = self.tcx.def_path_str(macro_def_id);
+            self.usage(cnum).macros.insert(name);
+        } else {
+            record(self.usage(cnum));
+        }
+    }
+
+    fn record_path(&mut self, path: &hir::Path<'_>, glob_import: Option<(HirId, Span)>) {
+        self.record_expansion(path.span);
+
+        // The crate named by the first segment, as in `foo::Bar` or
+        // `::foo::Bar`.
+        let segments = || path.segments.iter().filter(|segment| segment.ident.name != kw::PathRoot);
+        let Some(root) = segments()
+            .next()
+            .and_then(|segment| segment.res?.opt_def_id()?.as_crate_root())
+            .filter(|&cnum| cnum != LOCAL_CRATE)
+        else {
+            return;
+        };
+        let def_crate = match path.res {
+            Res::Def(_, def_id) if def_id.krate != root && def_id.krate != LOCAL_CRATE => {
+                Some(def_id.krate)
+            }
+            _ => None,
+        };
+
+        self.record_use(root, path.span, |usage| match glob_import {
+            Some(glob_import) => usage.glob_imports.push(glob_import),
+            None => usage.named = true,
+        });
+
+        let Some(def_crate) = def_crate else { return };
+        if self.in_tests {
+            self.usage(def_crate).tests = true;
+        } else if self.extern_macro(path.span).is_none() {
+            let path =
+                segments().map(|segment| segment.ident.to_string()).collect::<Vec<_>>().join("::");
+            self.usage(def_crate).reexported_through.insert(path);
+        }
+    }
+
+    fn with_test_attrs(&mut self, hir_id: HirId, f: impl FnOnce(&mut Self)) {
+        let prev = self.in_tests;
+        self.in_tests |= is_test_only(self.tcx.hir().attrs(hir_id));
+        f(self);
+        self.in_tests = prev;
+    }
+}
+
+/// Returns `true` for the items that only exist when compiling tests.
+fn is_test_only(attrs: &[ast::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.has_name(sym::rustc_test_marker)
+            || attr.has_name(sym::cfg)
+                && attr
+                    .meta_item_list()
+                    .map_or(false, |list| list.iter().any(|item| item.has_name(sym::test)))
+    })
+}
+
+impl<'tcx> Visitor<'tcx> for UsageCollector<'tcx> {
+    type NestedFilter = nested_filter::All;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_item(&mut self, item: &'tcx hir::Item<'tcx>) {
+        self.with_test_attrs(item.hir_id(), |this| {
+            this.record_expansion(item.span);
+            match item.kind {
+                hir::ItemKind::Use(path, hir::UseKind::Glob) => {
+                    this.record_path(path, Some((item.hir_id(), item.span)));
+                }
+                hir::ItemKind::ExternCrate(_) => {
+                    if let Some(cnum) = this.tcx.extern_mod_stmt_cnum(item.def_id) {
+                        this.record_use(cnum, item.span, |usage| usage.extern_crate = true);
+                    }
+                }
+                _ => intravisit::walk_item(this, item),
+            }
+        })
+    }
+
+    fn visit_trait_item(&mut self, item: &'tcx hir::TraitItem<'tcx>) {
+        self.with_test_attrs(item.hir_id(), |this| intravisit::walk_trait_item(this, item))
+    }
+
+    fn visit_impl_item(&mut self, item: &'tcx hir::ImplItem<'tcx>) {
+        self.with_test_attrs(item.hir_id(), |this| intravisit::walk_impl_item(this, item))
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
+        self.record_expansion(expr.span);
+        intravisit::walk_expr(self, expr)
+    }
+
+    fn visit_path(&mut self, path: &'tcx hir::Path<'tcx>, _id: HirId) {
+        self.record_path(path, None);
+        intravisit::walk_path(self, path)
+    }
+}
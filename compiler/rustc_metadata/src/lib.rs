@@ -28,6 +28,7 @@
 pub use rmeta::{provide, provide_extern};
 
 mod dependency_format;
+mod dependency_usage;
 mod foreign_modules;
 mod native_libs;
 mod rmeta;
@@ -29,6 +29,10 @@ pub enum LintLevelSource {
     /// The provided `Level` is the level specified on the command line.
     /// (The actual level may be lower due to `--cap-lints`.)
     CommandLine(Symbol, Level),
+
+    /// Lint level was set by the `--lint-config` file, for the whole crate
+    /// or, if the path is given, by an override for the files under that path.
+    ConfigFile(Symbol, Option<Symbol> /* override path */),
 }
 
 impl LintLevelSource {
@@ -37,6 +41,7 @@ pub fn name(&self) -> Symbol {
             LintLevelSource::Default => symbol::kw::Default,
             LintLevelSource::Node(name, _, _) => name,
             LintLevelSource::CommandLine(name, _) => name,
+            LintLevelSource::ConfigFile(name, _) => name,
         }
     }
 
@@ -45,6 +50,7 @@ pub fn span(&self) -> Span {
             LintLevelSource::Default => DUMMY_SP,
             LintLevelSource::Node(_, span, _) => span,
             LintLevelSource::CommandLine(_, _) => DUMMY_SP,
+            LintLevelSource::ConfigFile(_, _) => DUMMY_SP,
         }
     }
 }
@@ -112,11 +118,12 @@ pub fn get_lint_level(
         }
 
         // Ensure that we never exceed the `--cap-lints` argument
-        // unless the source is a --force-warn
-        level = if let LintLevelSource::CommandLine(_, Level::ForceWarn(_)) = src {
-            level
-        } else {
-            cmp::min(level, self.lint_cap)
+        // unless the source is a --force-warn, or a `force-warn` level
+        // of the `--lint-config` file
+        level = match (level, src) {
+            (_, LintLevelSource::CommandLine(_, Level::ForceWarn(_)))
+            | (Level::ForceWarn(_), LintLevelSource::ConfigFile(..)) => level,
+            _ => cmp::min(level, self.lint_cap),
         };
 
         if let Some(driver_level) = sess.driver_lint_caps.get(&LintId::of(lint)) {
@@ -261,6 +268,31 @@ pub fn explain_lint_level_source(
                 ));
             }
         }
+        LintLevelSource::ConfigFile(lint_config_name, path) => {
+            let set_by = match path {
+                Some(path) => format!("an override for `{}` in the `--lint-config` file", path),
+                None => "the `--lint-config` file".to_string(),
+            };
+            let hyphen_case_lint_name = name.replace('_', "-");
+            if lint_config_name.as_str() == name {
+                err.note_once(&format!(
+                    "`{} = \"{}\"` set by {}",
+                    hyphen_case_lint_name,
+                    level.as_str(),
+                    set_by
+                ));
+            } else {
+                let hyphen_case_config_name = lint_config_name.as_str().replace('_', "-");
+                err.note_once(&format!(
+                    "`{} = \"{}\"` implied by `{} = \"{}\"`, set by {}",
+                    hyphen_case_lint_name,
+                    level.as_str(),
+                    hyphen_case_config_name,
+                    level.as_str(),
+                    set_by
+                ));
+            }
+        }
         LintLevelSource::Node(lint_attr_name, src, reason) => {
             if let Some(rationale) = reason {
                 err.note(rationale.as_str());
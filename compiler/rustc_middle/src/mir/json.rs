@@ -0,0 +1,166 @@
+//! Serializes MIR bodies as JSON for `-Z dump-mir-format=json`.
+//!
+//! Unlike the pretty-printed MIR, the JSON output keeps statements, terminators,
+//! locals and spans apart, so tools that compare MIR between passes don't have to
+//! parse the textual format. Types, places and operands are still rendered with
+//! their `Debug` representation.
+
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::Span;
+use serde_json::{json, Value};
+
+/// Writes `body` as a JSON object, along with the pass it was dumped for.
+pub fn write_mir_fn_json<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    pass_num: Option<&dyn Display>,
+    pass_name: &str,
+    disambiguator: &dyn Display,
+    w: &mut dyn Write,
+) -> io::Result<()> {
+    // see notes on #41697 in `pretty.rs`
+    let def_path =
+        ty::print::with_forced_impl_filename_line!(tcx.def_path_str(body.source.def_id()));
+
+    let locals: Vec<_> = body
+        .local_decls
+        .iter_enumerated()
+        .map(|(local, decl)| {
+            json!({
+                "local": format!("{:?}", local),
+                "ty": decl.ty.to_string(),
+                "mutable": decl.mutability == Mutability::Mut,
+                "internal": decl.internal,
+                "span": span_to_json(tcx, decl.source_info.span),
+                "scope": decl.source_info.scope.index(),
+            })
+        })
+        .collect();
+
+    let var_debug_info: Vec<_> = body
+        .var_debug_info
+        .iter()
+        .map(|info| {
+            json!({
+                "name": info.name.as_str(),
+                "value": format!("{:?}", info.value),
+                "span": span_to_json(tcx, info.source_info.span),
+                "scope": info.source_info.scope.index(),
+            })
+        })
+        .collect();
+
+    let basic_blocks: Vec<_> = body
+        .basic_blocks()
+        .iter_enumerated()
+        .map(|(block, data)| {
+            let statements: Vec<_> = data
+                .statements
+                .iter()
+                .map(|statement| {
+                    json!({
+                        "kind": statement_kind_name(&statement.kind),
+                        "text": format!("{:?}", statement),
+                        "span": span_to_json(tcx, statement.source_info.span),
+                        "scope": statement.source_info.scope.index(),
+                    })
+                })
+                .collect();
+
+            let terminator = data.terminator();
+            let successors: Vec<_> = terminator
+                .successors()
+                .zip(terminator.kind.fmt_successor_labels())
+                .map(|(target, label)| json!({ "target": format!("{:?}", target), "label": label }))
+                .collect();
+
+            json!({
+                "block": format!("{:?}", block),
+                "is_cleanup": data.is_cleanup,
+                "statements": statements,
+                "terminator": {
+                    "kind": terminator_kind_name(&terminator.kind),
+                    "text": format!("{:?}", terminator.kind),
+                    "successors": successors,
+                    "span": span_to_json(tcx, terminator.source_info.span),
+                    "scope": terminator.source_info.scope.index(),
+                },
+            })
+        })
+        .collect();
+
+    let mir = json!({
+        "def_path": def_path,
+        "promoted": body.source.promoted.map(|promoted| format!("{:?}", promoted)),
+        "pass_name": pass_name,
+        "pass_num": pass_num.map(|pass_num| pass_num.to_string()),
+        "disambiguator": disambiguator.to_string(),
+        "phase": format!("{:?}", body.phase),
+        "span": span_to_json(tcx, body.span),
+        "arg_count": body.arg_count,
+        "return_ty": body.return_ty().to_string(),
+        "locals": locals,
+        "var_debug_info": var_debug_info,
+        "basic_blocks": basic_blocks,
+    });
+
+    serde_json::to_writer_pretty(&mut *w, &mir)?;
+    writeln!(w)
+}
+
+/// Spans are written with 1-based lines and columns, the end being exclusive.
+pub fn span_to_json(tcx: TyCtxt<'_>, span: Span) -> Value {
+    if span.is_dummy() {
+        return Value::Null;
+    }
+    let source_map = tcx.sess.source_map();
+    let lo = source_map.lookup_char_pos(span.lo());
+    let hi = source_map.lookup_char_pos(span.hi());
+    json!({
+        "file": lo.file.name.prefer_remapped().to_string(),
+        "start_line": lo.line,
+        "start_col": lo.col.0 + 1,
+        "end_line": hi.line,
+        "end_col": hi.col.0 + 1,
+    })
+}
+
+fn statement_kind_name(kind: &StatementKind<'_>) -> &'static str {
+    match kind {
+        StatementKind::Assign(..) => "Assign",
+        StatementKind::FakeRead(..) => "FakeRead",
+        StatementKind::SetDiscriminant { .. } => "SetDiscriminant",
+        StatementKind::Deinit(..) => "Deinit",
+        StatementKind::StorageLive(..) => "StorageLive",
+        StatementKind::StorageDead(..) => "StorageDead",
+        StatementKind::Retag(..) => "Retag",
+        StatementKind::AscribeUserType(..) => "AscribeUserType",
+        StatementKind::Coverage(..) => "Coverage",
+        StatementKind::CopyNonOverlapping(..) => "CopyNonOverlapping",
+        StatementKind::Nop => "Nop",
+    }
+}
+
+fn terminator_kind_name(kind: &TerminatorKind<'_>) -> &'static str {
+    match kind {
+        TerminatorKind::Goto { .. } => "Goto",
+        TerminatorKind::SwitchInt { .. } => "SwitchInt",
+        TerminatorKind::Resume => "Resume",
+        TerminatorKind::Abort => "Abort",
+        TerminatorKind::Return => "Return",
+        TerminatorKind::Unreachable => "Unreachable",
+        TerminatorKind::Drop { .. } => "Drop",
+        TerminatorKind::DropAndReplace { .. } => "DropAndReplace",
+        TerminatorKind::Call { .. } => "Call",
+        TerminatorKind::Assert { .. } => "Assert",
+        TerminatorKind::Yield { .. } => "Yield",
+        TerminatorKind::GeneratorDrop => "GeneratorDrop",
+        TerminatorKind::FalseEdge { .. } => "FalseEdge",
+        TerminatorKind::FalseUnwind { .. } => "FalseUnwind",
+        TerminatorKind::InlineAsm { .. } => "InlineAsm",
+    }
+}
@@ -51,6 +51,7 @@
 mod graph_cyclic_cache;
 pub mod graphviz;
 pub mod interpret;
+pub mod json;
 pub mod mono;
 pub mod patch;
 mod predecessors;
@@ -126,6 +127,54 @@ fn is_mir_dump_enabled(&self) -> bool {
     }
 }
 
+/// The points of the MIR pipeline at which the passes of a [`MirPassRegistry`] run.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MirPassPoint {
+    /// In `mir_drops_elaborated_and_const_checked`, right after borrowck and before any other pass.
+    /// The MIR still contains the false edges and the fake reads only needed by borrowck.
+    AfterBorrowck,
+    /// At the end of `mir_drops_elaborated_and_const_checked`, once drops have been elaborated.
+    /// The MIR of const items and `const fn`s is evaluated by CTFE after this point.
+    DropsElaborated,
+    /// In `optimized_mir`, before any optimization.
+    BeforeOptimizations,
+    /// In `optimized_mir`, after generators have been lowered to state machines.
+    GeneratorsLowered,
+    /// In `optimized_mir`, after all optimizations and before the MIR is prepared for codegen.
+    AfterOptimizations,
+}
+
+/// Additional MIR passes registered by a driver, which run at the given [`MirPassPoint`]s of the
+/// pipeline together with the built-in passes. Like the built-in passes, they can be toggled with
+/// `-Zmir-enable-passes` using their [`MirPass::name`].
+///
+/// Registered passes must not change the [`MirPhase`]. They aren't tracked by incremental
+/// compilation, so changing the registered passes requires a clean build.
+#[derive(Default)]
+pub struct MirPassRegistry {
+    passes: Vec<(MirPassPoint, Box<dyn for<'tcx> MirPass<'tcx> + Send + Sync>)>,
+}
+
+impl MirPassRegistry {
+    /// Registers `pass` to run at `point`. Passes registered at the same point run in the order in
+    /// which they were registered.
+    pub fn register(
+        &mut self,
+        point: MirPassPoint,
+        pass: impl for<'tcx> MirPass<'tcx> + Send + Sync + 'static,
+    ) {
+        self.passes.push((point, Box::new(pass)));
+    }
+
+    /// The passes registered at `point`, in the order in which they have to run.
+    pub fn passes(
+        &self,
+        point: MirPassPoint,
+    ) -> impl Iterator<Item = &(dyn for<'tcx> MirPass<'tcx> + Send + Sync)> {
+        self.passes.iter().filter(move |(p, _)| *p == point).map(|(_, pass)| &**pass)
+    }
+}
+
 impl MirPhase {
     /// Gets the index of the current MirPhase within the set of all `MirPhase`s.
     pub fn phase_index(&self) -> usize {
@@ -996,6 +1045,19 @@ pub enum VarDebugInfoContents<'tcx> {
     /// based on a `Local`, not a `Static`, and contains no indexing.
     Place(Place<'tcx>),
     Const(Constant<'tcx>),
+    /// The user variable's data is split across several fragments,
+    /// each described by a `VarDebugInfoFragment`, e.g. because its
+    /// fields were replaced by separate locals by an optimization.
+    /// See DWARF 5's "2.6.1.2 Composite Location Descriptions"
+    /// and LLVM's `DW_OP_LLVM_fragment` for more details on
+    /// the underlying debuginfo feature this relies on.
+    Composite {
+        /// Type of the original user variable.
+        ty: Ty<'tcx>,
+        /// All the parts of the original user variable, which ended
+        /// up in disjoint places, due to optimizations.
+        fragments: Vec<VarDebugInfoFragment<'tcx>>,
+    },
 }
 
 impl<'tcx> Debug for VarDebugInfoContents<'tcx> {
@@ -1003,7 +1065,44 @@ fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         match self {
             VarDebugInfoContents::Const(c) => write!(fmt, "{}", c),
             VarDebugInfoContents::Place(p) => write!(fmt, "{:?}", p),
+            VarDebugInfoContents::Composite { ty, fragments } => {
+                write!(fmt, "{:?}{{ ", ty)?;
+                for f in fragments.iter() {
+                    write!(fmt, "{:?}, ", f)?;
+                }
+                write!(fmt, "}}")
+            }
+        }
+    }
+}
+
+#[derive(Clone, TyEncodable, TyDecodable, HashStable, TypeFoldable, TypeVisitable)]
+pub struct VarDebugInfoFragment<'tcx> {
+    /// Where in the composite user variable this fragment is,
+    /// represented as a "projection" into the composite variable.
+    /// At lower levels, this corresponds to a byte/bit range.
+    /// NOTE: There's an unenforced invariant that this contains
+    /// only `Field`s, and not projections into `enum` variants or `union`s.
+    pub projection: Vec<PlaceElem<'tcx>>,
+
+    /// Where the data for this fragment can be found.
+    /// NOTE: There's an unenforced invariant that this `Place`
+    /// contains no indexing (with a non-constant index).
+    pub contents: Place<'tcx>,
+}
+
+impl Debug for VarDebugInfoFragment<'_> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        for elem in self.projection.iter() {
+            match elem {
+                ProjectionElem::Field(field, _) => {
+                    write!(fmt, ".{:?}", field.index())?;
+                }
+                _ => bug!("unsupported fragment projection `{:?}`", elem),
+            }
         }
+
+        write!(fmt, " => {:?}", self.contents)
     }
 }
 
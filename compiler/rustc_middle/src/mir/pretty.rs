@@ -6,6 +6,7 @@
 use std::path::{Path, PathBuf};
 
 use super::graphviz::write_mir_fn_graphviz;
+use super::json::write_mir_fn_json;
 use super::spanview::write_mir_fn_spanview;
 use either::Either;
 use rustc_data_structures::fx::FxHashMap;
@@ -19,6 +20,7 @@
 use rustc_middle::mir::MirSource;
 use rustc_middle::mir::*;
 use rustc_middle::ty::{self, TyCtxt};
+use rustc_session::config::MirDumpFormat;
 use rustc_target::abi::Size;
 
 const INDENT: &str = "    ";
@@ -119,27 +121,35 @@ fn dump_matched_mir_node<'tcx, F>(
 ) where
     F: FnMut(PassWhere, &mut dyn Write) -> io::Result<()>,
 {
-    let _: io::Result<()> = try {
-        let mut file =
-            create_dump_file(tcx, "mir", pass_num, pass_name, disambiguator, body.source)?;
-        // see notes on #41697 above
-        let def_path =
-            ty::print::with_forced_impl_filename_line!(tcx.def_path_str(body.source.def_id()));
-        write!(file, "// MIR for `{}", def_path)?;
-        match body.source.promoted {
-            None => write!(file, "`")?,
-            Some(promoted) => write!(file, "::{:?}`", promoted)?,
-        }
-        writeln!(file, " {} {}", disambiguator, pass_name)?;
-        if let Some(ref layout) = body.generator_layout() {
-            writeln!(file, "/* generator_layout = {:#?} */", layout)?;
-        }
-        writeln!(file)?;
-        extra_data(PassWhere::BeforeCFG, &mut file)?;
-        write_user_type_annotations(tcx, body, &mut file)?;
-        write_mir_fn(tcx, body, &mut extra_data, &mut file)?;
-        extra_data(PassWhere::AfterCFG, &mut file)?;
-    };
+    if tcx.sess.opts.unstable_opts.dump_mir_format == MirDumpFormat::Json {
+        let _: io::Result<()> = try {
+            let mut file =
+                create_dump_file(tcx, "json", pass_num, pass_name, disambiguator, body.source)?;
+            write_mir_fn_json(tcx, body, pass_num, pass_name, disambiguator, &mut file)?;
+        };
+    } else {
+        let _: io::Result<()> = try {
+            let mut file =
+                create_dump_file(tcx, "mir", pass_num, pass_name, disambiguator, body.source)?;
+            // see notes on #41697 above
+            let def_path =
+                ty::print::with_forced_impl_filename_line!(tcx.def_path_str(body.source.def_id()));
+            write!(file, "// MIR for `{}", def_path)?;
+            match body.source.promoted {
+                None => write!(file, "`")?,
+                Some(promoted) => write!(file, "::{:?}`", promoted)?,
+            }
+            writeln!(file, " {} {}", disambiguator, pass_name)?;
+            if let Some(ref layout) = body.generator_layout() {
+                writeln!(file, "/* generator_layout = {:#?} */", layout)?;
+            }
+            writeln!(file)?;
+            extra_data(PassWhere::BeforeCFG, &mut file)?;
+            write_user_type_annotations(tcx, body, &mut file)?;
+            write_mir_fn(tcx, body, &mut extra_data, &mut file)?;
+            extra_data(PassWhere::AfterCFG, &mut file)?;
+        };
+    }
 
     if tcx.sess.opts.unstable_opts.dump_mir_graphviz {
         let _: io::Result<()> = try {
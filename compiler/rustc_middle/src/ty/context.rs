@@ -10,7 +10,8 @@
 use crate::middle::stability;
 use crate::mir::interpret::{self, Allocation, ConstAllocation};
 use crate::mir::{
-    Body, BorrowCheckResult, Field, Local, Place, PlaceElem, ProjectionKind, Promoted,
+    Body, BorrowCheckResult, Field, Local, MirPassRegistry, Place, PlaceElem, ProjectionKind,
+    Promoted,
 };
 use crate::thir::Thir;
 use crate::traits;
@@ -1058,6 +1059,9 @@ pub struct GlobalCtxt<'tcx> {
     /// we can upcast to `Any` for some additional type safety.
     pub lint_store: Lrc<dyn Any + sync::Sync + sync::Send>,
 
+    /// The MIR passes registered by the driver in addition to the built-in ones.
+    pub mir_pass_registry: Lrc<MirPassRegistry>,
+
     pub dep_graph: DepGraph,
 
     pub prof: SelfProfilerRef,
@@ -1236,6 +1240,7 @@ pub fn lift<T: Lift<'tcx>>(self, value: T) -> Option<T::Lifted> {
     pub fn create_global_ctxt(
         s: &'tcx Session,
         lint_store: Lrc<dyn Any + sync::Send + sync::Sync>,
+        mir_pass_registry: Lrc<MirPassRegistry>,
         arena: &'tcx WorkerLocal<Arena<'tcx>>,
         hir_arena: &'tcx WorkerLocal<hir::Arena<'tcx>>,
         definitions: Definitions,
@@ -1268,6 +1273,7 @@ pub fn create_global_ctxt(
         GlobalCtxt {
             sess: s,
             lint_store,
+            mir_pass_registry,
             arena,
             hir_arena,
             interners,
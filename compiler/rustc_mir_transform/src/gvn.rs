@@ -0,0 +1,278 @@
+//! Global value numbering (GVN).
+//!
+//! This pass finds computations which are known to produce the same value as a computation done
+//! earlier in the body, and replaces them with a copy of the local holding that earlier result.
+//! It mostly cleans up after inlining, which duplicates the `Len`, `Discriminant`, casts and
+//! arithmetic done by the callees on values the caller already computed.
+//!
+//! Values are only tracked for SSA locals, i.e. locals which are assigned exactly once (or not at
+//! all for arguments) and never borrowed. Two computations are given the same value number when
+//! they apply the same operation to operands with the same value numbers. Since an SSA local only
+//! has one definition, and this definition dominates all its uses, the value of a computation on
+//! SSA operands is the same at every point dominated by all the definitions of its operands.
+//!
+//! Memory is handled conservatively: no value is tracked for a place which is not an SSA local,
+//! with the single exception of the length of a slice behind an SSA pointer, which is part of the
+//! metadata of the pointer itself. `Discriminant` is thus only numbered for SSA locals.
+//!
+//! The pass runs after `ConstProp`, so the computations folded to constants by const propagation
+//! have already been replaced by their value, and the lints emitted by const propagation don't see
+//! the copies introduced here.
+
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_data_structures::graph::dominators::Dominators;
+use rustc_index::bit_set::BitSet;
+use rustc_index::vec::IndexVec;
+use rustc_middle::mir::visit::{MutVisitor, MutatingUseContext, PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{Ty, TyCtxt};
+use rustc_mir_dataflow::impls::borrowed_locals;
+
+use crate::MirPass;
+
+pub struct GVN;
+
+impl<'tcx> MirPass<'tcx> for GVN {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        sess.mir_opt_level() >= 2
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        let ssa = ssa_locals(body);
+        let dominators = body.basic_blocks.dominators();
+        let reverse_postorder: Vec<_> =
+            traversal::reverse_postorder(body).map(|(block, _)| block).collect();
+
+        let mut state = VnState::new(body, &ssa);
+        let local_decls = &body.local_decls;
+        let basic_blocks = body.basic_blocks.as_mut_preserves_cfg();
+        // Visiting the blocks in reverse postorder visits the definition of each SSA local before
+        // its uses, as the definition dominates the uses.
+        for block in reverse_postorder {
+            for (statement_index, statement) in
+                basic_blocks[block].statements.iter_mut().enumerate()
+            {
+                if let StatementKind::Assign(box (place, rvalue)) = &mut statement.kind
+                    && let Some(local) = place.as_local()
+                    && ssa.contains(local)
+                {
+                    let location = Location { block, statement_index };
+                    state.visit_assignment(local_decls, &dominators, local, rvalue, location);
+                }
+            }
+        }
+
+        if state.reused.is_empty() {
+            return;
+        }
+
+        // The reused locals are now used at places where their value may have been moved out or
+        // their storage may have ended.
+        ReusedLocalsFixer { tcx, reused: &state.reused }.visit_body_preserves_cfg(body);
+    }
+}
+
+/// The locals which are assigned exactly once, by a plain assignment or as the destination of a
+/// call, and which are never borrowed. Arguments are only SSA if they are never assigned.
+fn ssa_locals(body: &Body<'_>) -> BitSet<Local> {
+    struct AssignmentCounter {
+        assignments: IndexVec<Local, usize>,
+    }
+
+    impl<'tcx> Visitor<'tcx> for AssignmentCounter {
+        fn visit_local(&mut self, local: Local, context: PlaceContext, _: Location) {
+            match context {
+                PlaceContext::MutatingUse(MutatingUseContext::Store | MutatingUseContext::Call) => {
+                    self.assignments[local] += 1
+                }
+                // Any other mutation, including the ones through a projection.
+                PlaceContext::MutatingUse(_) => self.assignments[local] += 2,
+                PlaceContext::NonMutatingUse(_) | PlaceContext::NonUse(_) => {}
+            }
+        }
+    }
+
+    let mut counter = AssignmentCounter { assignments: IndexVec::from_elem(0, &body.local_decls) };
+    counter.visit_body(body);
+
+    let borrowed = borrowed_locals(body);
+    let mut ssa = BitSet::new_empty(body.local_decls.len());
+    for (local, &assignments) in counter.assignments.iter_enumerated() {
+        if local == RETURN_PLACE || borrowed.contains(local) {
+            continue;
+        }
+        let expected_assignments = if local.as_usize() <= body.arg_count { 0 } else { 1 };
+        if assignments == expected_assignments {
+            ssa.insert(local);
+        }
+    }
+    ssa
+}
+
+rustc_index::newtype_index! {
+    struct VnIndex {
+        DEBUG_FORMAT = "_v{}"
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Value<'tcx> {
+    /// The value of an SSA local that isn't computed by one of the expressions below, e.g. an
+    /// argument or the result of a call.
+    Opaque(Local),
+    Constant(ConstantKind<'tcx>),
+    /// The length of the slice behind a pointer.
+    Len(VnIndex),
+    Discriminant(VnIndex),
+    Cast(CastKind, VnIndex, Ty<'tcx>),
+    BinaryOp(BinOp, VnIndex, VnIndex),
+    CheckedBinaryOp(BinOp, VnIndex, VnIndex),
+    UnaryOp(UnOp, VnIndex),
+}
+
+struct VnState<'a, 'tcx> {
+    ssa: &'a BitSet<Local>,
+    values: FxIndexSet<Value<'tcx>>,
+    /// The value number of each SSA local, once its definition has been visited.
+    locals: IndexVec<Local, Option<VnIndex>>,
+    /// The SSA locals holding each value, with the location of their definition.
+    holders: IndexVec<VnIndex, Vec<(Local, Location)>>,
+    /// The locals that replace a computation somewhere in the body.
+    reused: BitSet<Local>,
+}
+
+impl<'a, 'tcx> VnState<'a, 'tcx> {
+    fn new(body: &Body<'tcx>, ssa: &'a BitSet<Local>) -> Self {
+        VnState {
+            ssa,
+            values: FxIndexSet::default(),
+            locals: IndexVec::from_elem(None, &body.local_decls),
+            holders: IndexVec::new(),
+            reused: BitSet::new_empty(body.local_decls.len()),
+        }
+    }
+
+    fn insert(&mut self, value: Value<'tcx>) -> VnIndex {
+        let (index, new) = self.values.insert_full(value);
+        let index = VnIndex::from_usize(index);
+        if new {
+            self.holders.push(Vec::new());
+        }
+        index
+    }
+
+    fn local_value(&mut self, local: Local) -> Option<VnIndex> {
+        if !self.ssa.contains(local) {
+            return None;
+        }
+        if let Some(value) = self.locals[local] {
+            return Some(value);
+        }
+        let value = self.insert(Value::Opaque(local));
+        self.locals[local] = Some(value);
+        Some(value)
+    }
+
+    fn operand_value(&mut self, operand: &Operand<'tcx>) -> Option<VnIndex> {
+        match operand {
+            Operand::Constant(constant) => Some(self.insert(Value::Constant(constant.literal))),
+            Operand::Copy(place) | Operand::Move(place) => self.local_value(place.as_local()?),
+        }
+    }
+
+    fn rvalue_value(&mut self, rvalue: &Rvalue<'tcx>) -> Option<Value<'tcx>> {
+        let value = match *rvalue {
+            Rvalue::Len(place) => {
+                // The length of an array is a constant, only the length of a slice behind a
+                // pointer is worth numbering.
+                let [PlaceElem::Deref] = place.projection[..] else { return None };
+                Value::Len(self.local_value(place.local)?)
+            }
+            Rvalue::Discriminant(place) => {
+                Value::Discriminant(self.local_value(place.as_local()?)?)
+            }
+            Rvalue::Cast(kind, ref operand, ty) => match kind {
+                // Exposing the address of a pointer is a side effect.
+                CastKind::PointerExposeAddress | CastKind::PointerFromExposedAddress => {
+                    return None;
+                }
+                CastKind::Pointer(_) | CastKind::Misc => {
+                    Value::Cast(kind, self.operand_value(operand)?, ty)
+                }
+            },
+            Rvalue::BinaryOp(op, box (ref lhs, ref rhs)) => {
+                Value::BinaryOp(op, self.operand_value(lhs)?, self.operand_value(rhs)?)
+            }
+            Rvalue::CheckedBinaryOp(op, box (ref lhs, ref rhs)) => {
+                Value::CheckedBinaryOp(op, self.operand_value(lhs)?, self.operand_value(rhs)?)
+            }
+            Rvalue::UnaryOp(op, ref operand) => Value::UnaryOp(op, self.operand_value(operand)?),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Numbers the value assigned to the SSA `local` by `rvalue`, and replaces `rvalue` by a copy
+    /// of another local holding the same value if that local is defined at a location dominating
+    /// `location`.
+    fn visit_assignment(
+        &mut self,
+        local_decls: &LocalDecls<'tcx>,
+        dominators: &Dominators<BasicBlock>,
+        local: Local,
+        rvalue: &mut Rvalue<'tcx>,
+        location: Location,
+    ) {
+        if let Rvalue::Use(operand) = rvalue {
+            self.locals[local] = self.operand_value(operand);
+            return;
+        }
+
+        let Some(value) = self.rvalue_value(rvalue) else { return };
+        let value = self.insert(value);
+        self.locals[local] = Some(value);
+
+        let ty = local_decls[local].ty;
+        let holder = self.holders[value].iter().find(|&&(holder, holder_location)| {
+            holder_location.dominates(location, dominators) && local_decls[holder].ty == ty
+        });
+        if let Some(&(holder, _)) = holder {
+            *rvalue = Rvalue::Use(Operand::Copy(holder.into()));
+            self.reused.insert(holder);
+        } else {
+            self.holders[value].push((local, location));
+        }
+    }
+}
+
+/// Turns the moves of the reused locals into copies, and removes their storage statements.
+struct ReusedLocalsFixer<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    reused: &'a BitSet<Local>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for ReusedLocalsFixer<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_operand(&mut self, operand: &mut Operand<'tcx>, location: Location) {
+        if let Operand::Move(place) = *operand
+            && self.reused.contains(place.local)
+        {
+            *operand = Operand::Copy(place);
+        }
+        self.super_operand(operand, location);
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement<'tcx>, location: Location) {
+        if let StatementKind::StorageLive(local) | StatementKind::StorageDead(local) =
+            statement.kind
+            && self.reused.contains(local)
+        {
+            statement.make_nop();
+            return;
+        }
+        self.super_statement(statement, location);
+    }
+}
@@ -0,0 +1,245 @@
+//! A jump threading pass, which redirects the edges to a `SwitchInt` along which the value being
+//! switched over is known straight to the target the switch would take.
+//!
+//! ```text
+//!     discriminant(x) = 1 ---                                   ---> something
+//!                            \                               / 0
+//!                             --> d = discriminant(x); switch d
+//!                            /                               \ 1
+//!     discriminant(x) = 0 ---                                   ---> something else
+//! ```
+//! becomes
+//! ```text
+//!     discriminant(x) = 1 ---> d = discriminant(x) ----------------> something
+//!                                                               / 0
+//!                                 d = discriminant(x); switch d
+//!                                                               \ 1
+//!     discriminant(x) = 0 ---> d = discriminant(x) ----------------> something else
+//! ```
+//! after which the switch block usually becomes unreachable.
+//!
+//! The value switched over is known along an edge when the predecessor ends with a `Goto` and
+//! either assigns a constant to the switched local, or sets the discriminant of the enum local
+//! the switched local is the discriminant of. This covers the patterns handled by
+//! `SeparateConstSwitch`, `ConstGoto` and `EarlyOtherwiseBranch`, and in particular the cascades
+//! of discriminant switches of the `?` operator and of `Option` combinators once they are inlined.
+//!
+//! The statements of the switch block are duplicated for each threaded edge, so blocks with too
+//! many statements are not threaded. Only locals which are never borrowed are tracked, so that
+//! their value can't change behind our back through a pointer.
+
+use rustc_index::bit_set::BitSet;
+use rustc_middle::mir::visit::{PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_mir_dataflow::impls::borrowed_locals;
+
+use crate::MirPass;
+
+/// The maximum number of statements the switch block can have to be duplicated.
+const MAX_DUPLICATED_STATEMENTS: usize = 8;
+
+pub struct JumpThreading;
+
+impl<'tcx> MirPass<'tcx> for JumpThreading {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        sess.mir_opt_level() >= 2
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        let threads = find_threads(tcx, body);
+        if threads.is_empty() {
+            return;
+        }
+        debug!(?threads);
+
+        let basic_blocks = body.basic_blocks.as_mut();
+        for ThreadingOpportunity { predecessor, switch_block, target } in threads {
+            let switch_data = &basic_blocks[switch_block];
+            let new_target = if switch_data.statements.is_empty() {
+                target
+            } else {
+                let source_info = switch_data.terminator().source_info;
+                let new_block = BasicBlockData {
+                    statements: switch_data.statements.clone(),
+                    terminator: Some(Terminator {
+                        source_info,
+                        kind: TerminatorKind::Goto { target },
+                    }),
+                    is_cleanup: switch_data.is_cleanup,
+                };
+                basic_blocks.push(new_block)
+            };
+            basic_blocks[predecessor].terminator_mut().kind =
+                TerminatorKind::Goto { target: new_target };
+        }
+
+        super::simplify::simplify_cfg(tcx, body);
+    }
+}
+
+#[derive(Debug)]
+struct ThreadingOpportunity {
+    /// The block ending with a `Goto` to `switch_block`.
+    predecessor: BasicBlock,
+    switch_block: BasicBlock,
+    /// The target of the switch when coming from `predecessor`.
+    target: BasicBlock,
+}
+
+/// What the value switched over depends on.
+#[derive(Copy, Clone, Debug)]
+enum Condition {
+    /// The value of the switched local, which is not assigned in the switch block.
+    Local(Local),
+    /// The discriminant of an enum local, which is read in the switch block.
+    Discriminant(Local),
+}
+
+fn find_threads<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> Vec<ThreadingOpportunity> {
+    let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+    let borrowed = borrowed_locals(body);
+    let predecessors = body.basic_blocks.predecessors();
+
+    let mut threads = Vec::new();
+    for (switch_block, switch_data) in body.basic_blocks.iter_enumerated() {
+        let TerminatorKind::SwitchInt {
+            discr: Operand::Copy(switch_place) | Operand::Move(switch_place),
+            ref targets,
+            ..
+        } = switch_data.terminator().kind else {
+            continue;
+        };
+        let Some(switch_local) = switch_place.as_local() else { continue };
+        if switch_data.statements.len() > MAX_DUPLICATED_STATEMENTS {
+            continue;
+        }
+        let Some(condition) = find_condition(&borrowed, switch_local, switch_data) else {
+            continue;
+        };
+
+        for &predecessor in &predecessors[switch_block] {
+            let predecessor_data = &body.basic_blocks[predecessor];
+            if predecessor == switch_block
+                || predecessor_data.is_cleanup != switch_data.is_cleanup
+                || !matches!(predecessor_data.terminator().kind, TerminatorKind::Goto { .. })
+            {
+                continue;
+            }
+
+            let Some(value) = known_value(tcx, param_env, body, condition, predecessor_data) else {
+                continue;
+            };
+            let target = targets.target_for_value(value);
+            threads.push(ThreadingOpportunity { predecessor, switch_block, target });
+        }
+    }
+    threads
+}
+
+/// Finds what the value of `switch_local` at the end of `switch_data` depends on, if it only
+/// depends on a local whose value can be known in the predecessors of the block.
+fn find_condition(
+    borrowed: &BitSet<Local>,
+    switch_local: Local,
+    switch_data: &BasicBlockData<'_>,
+) -> Option<Condition> {
+    if borrowed.contains(switch_local) {
+        return None;
+    }
+
+    let definition = switch_data.statements.iter().position(|statement| {
+        matches!(
+            &statement.kind,
+            StatementKind::Assign(box (place, _)) if place.as_local() == Some(switch_local)
+        )
+    });
+    let Some(definition) = definition else {
+        // The switched local must keep the value it has at the end of the predecessor.
+        if switch_data.statements.iter().any(|statement| mutates(statement, switch_local)) {
+            return None;
+        }
+        return Some(Condition::Local(switch_local));
+    };
+
+    let StatementKind::Assign(box (_, Rvalue::Discriminant(enum_place))) =
+        &switch_data.statements[definition].kind else {
+        return None;
+    };
+    let enum_local = enum_place.as_local()?;
+    if borrowed.contains(enum_local) {
+        return None;
+    }
+
+    // The enum must keep the discriminant it has at the end of the predecessor, and the switched
+    // local must keep the discriminant read from it.
+    for (index, statement) in switch_data.statements.iter().enumerate() {
+        if index == definition {
+            continue;
+        }
+        if mutates(statement, enum_local)
+            || (index > definition && mutates(statement, switch_local))
+        {
+            return None;
+        }
+    }
+    Some(Condition::Discriminant(enum_local))
+}
+
+/// Finds the value of `condition` at the end of `predecessor_data`, if it is set by one of its
+/// statements.
+fn known_value<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    body: &Body<'tcx>,
+    condition: Condition,
+    predecessor_data: &BasicBlockData<'tcx>,
+) -> Option<u128> {
+    let (Condition::Local(local) | Condition::Discriminant(local)) = condition;
+    let ty: Ty<'tcx> = body.local_decls[local].ty;
+
+    for statement in predecessor_data.statements.iter().rev() {
+        match (condition, &statement.kind) {
+            (
+                Condition::Local(_),
+                StatementKind::Assign(box (place, Rvalue::Use(Operand::Constant(constant)))),
+            ) if place.as_local() == Some(local) => {
+                return constant.literal.try_eval_bits(tcx, param_env, ty);
+            }
+            (
+                Condition::Discriminant(_),
+                StatementKind::SetDiscriminant { box place, variant_index },
+            ) if place.as_local() == Some(local) => {
+                return Some(ty.discriminant_for_variant(tcx, *variant_index)?.val);
+            }
+            _ => {}
+        }
+
+        // Any other write to the local makes its value unknown. Writes through a pointer can't
+        // change it, as it's never borrowed.
+        if mutates(statement, local) {
+            return None;
+        }
+    }
+    None
+}
+
+/// Whether `statement` writes to `local`, or starts or ends its storage.
+fn mutates(statement: &Statement<'_>, local: Local) -> bool {
+    struct MutationFinder {
+        local: Local,
+        found: bool,
+    }
+
+    impl<'tcx> Visitor<'tcx> for MutationFinder {
+        fn visit_local(&mut self, local: Local, context: PlaceContext, _: Location) {
+            if local == self.local && (context.is_mutating_use() || context.is_storage_marker()) {
+                self.found = true;
+            }
+        }
+    }
+
+    let mut finder = MutationFinder { local, found: false };
+    finder.visit_statement(statement, Location::START);
+    finder.found
+}
@@ -25,7 +25,7 @@
 use rustc_hir::intravisit::{self, Visitor};
 use rustc_index::vec::IndexVec;
 use rustc_middle::mir::visit::Visitor as _;
-use rustc_middle::mir::{traversal, Body, ConstQualifs, MirPass, MirPhase, Promoted};
+use rustc_middle::mir::{traversal, Body, ConstQualifs, MirPass, MirPassPoint, MirPhase, Promoted};
 use rustc_middle::ty::query::Providers;
 use rustc_middle::ty::{self, TyCtxt, TypeVisitable};
 
@@ -60,8 +60,10 @@
 mod ffi_unwind_calls;
 mod function_item_references;
 mod generator;
+mod gvn;
 mod inline;
 mod instcombine;
+mod jump_threading;
 mod lower_intrinsics;
 mod lower_slice_len;
 mod marker;
@@ -85,6 +87,7 @@
 mod simplify_branches;
 mod simplify_comparison_integral;
 mod simplify_try;
+mod sroa;
 mod uninhabited_enum_branching;
 mod unreachable_prop;
 
@@ -380,6 +383,8 @@ fn mir_drops_elaborated_and_const_checked<'tcx>(
         body.tainted_by_errors = Some(error_reported);
     }
 
+    pm::run_registered_passes(tcx, &mut body, MirPassPoint::AfterBorrowck);
+
     // IMPORTANT
     pm::run_passes(tcx, &mut body, &[&remove_false_edges::RemoveFalseEdges]);
 
@@ -398,6 +403,7 @@ fn mir_drops_elaborated_and_const_checked<'tcx>(
 
     run_post_borrowck_cleanup_passes(tcx, &mut body);
     assert!(body.phase == MirPhase::Deaggregated);
+    pm::run_registered_passes(tcx, &mut body, MirPassPoint::DropsElaborated);
     tcx.alloc_steal_mir(body)
 }
 
@@ -442,6 +448,8 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
         WithMinOptLevel(1, x)
     }
 
+    pm::run_registered_passes(tcx, body, MirPassPoint::BeforeOptimizations);
+
     // Lowering generator control-flow and variables has to happen before we do anything else
     // to them. We run some optimizations before that, because they may be harder to do on the state
     // machine than on MIR with async primitives.
@@ -461,6 +469,7 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
     );
 
     assert!(body.phase == MirPhase::GeneratorsLowered);
+    pm::run_registered_passes(tcx, body, MirPassPoint::GeneratorsLowered);
 
     // The main optimizations that we do on MIR.
     pm::run_passes(
@@ -471,11 +480,13 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             &remove_zsts::RemoveZsts,
             &const_goto::ConstGoto,
             &remove_unneeded_drops::RemoveUnneededDrops,
+            &sroa::ScalarReplacementOfAggregates,
             &match_branches::MatchBranchSimplification,
             // inst combine is after MatchBranchSimplification to clean up Ne(_1, false)
             &multiple_return_terminators::MultipleReturnTerminators,
             &instcombine::InstCombine,
             &separate_const_switch::SeparateConstSwitch,
+            &jump_threading::JumpThreading,
             //
             // FIXME(#70073): This pass is responsible for both optimization as well as some lints.
             &const_prop::ConstProp,
@@ -483,6 +494,7 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             // Const-prop runs unconditionally, but doesn't mutate the MIR at mir-opt-level=0.
             &const_debuginfo::ConstDebugInfo,
             &o1(simplify_branches::SimplifyConstCondition::new("after-const-prop")),
+            &gvn::GVN,
             &early_otherwise_branch::EarlyOtherwiseBranch,
             &simplify_comparison_integral::SimplifyComparisonIntegral,
             &simplify_try::SimplifyArmIdentity,
@@ -496,6 +508,15 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             &simplify::SimplifyLocals,
             &multiple_return_terminators::MultipleReturnTerminators,
             &deduplicate_blocks::DeduplicateBlocks,
+        ],
+    );
+
+    pm::run_registered_passes(tcx, body, MirPassPoint::AfterOptimizations);
+
+    pm::run_passes(
+        tcx,
+        body,
+        &[
             // Some cleanup necessary at least for LLVM and potentially other codegen backends.
             &add_call_guards::CriticalCallEdges,
             &marker::PhaseChange(MirPhase::Optimized),
@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use rustc_middle::mir::{self, Body, MirPhase};
+use rustc_middle::mir::{self, Body, MirPassPoint, MirPhase};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
 
@@ -132,6 +132,26 @@ pub fn run_passes<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>, passes: &[&dyn
     }
 }
 
+/// Runs the passes the driver registered at `point`, if any.
+pub fn run_registered_passes<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>, point: MirPassPoint) {
+    let registry = tcx.mir_pass_registry.clone();
+    let passes: Vec<&dyn MirPass<'tcx>> =
+        registry.passes(point).map(|pass| pass as &dyn MirPass<'tcx>).collect();
+    if passes.is_empty() {
+        return;
+    }
+
+    for pass in &passes {
+        assert!(
+            pass.phase_change().is_none(),
+            "registered MIR pass `{}` must not change the MIR phase",
+            pass.name(),
+        );
+    }
+
+    run_passes(tcx, body, &passes);
+}
+
 pub fn validate_body<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>, when: String) {
     validate::Validator { when, mir_phase: body.phase }.run_pass(tcx, body);
 }
@@ -0,0 +1,349 @@
+//! Scalar replacement of aggregates (SROA).
+//!
+//! This pass splits locals of struct, tuple and closure type into one local per field, when the
+//! aggregate itself is only ever accessed through its fields. For example
+//!
+//! ```ignore (MIR)
+//! Deinit(_1);
+//! (_1.0: u32) = const 1_u32;
+//! (_1.1: u64) = const 2_u64;
+//! _2 = (_1.1: u64);
+//! ```
+//!
+//! becomes
+//!
+//! ```ignore (MIR)
+//! Deinit(_3);
+//! Deinit(_4);
+//! _3 = const 1_u32;
+//! _4 = const 2_u64;
+//! _2 = _4;
+//! ```
+//!
+//! The pass runs after `Deaggregator`, so aggregates are built field by field and a local that is
+//! never moved, copied, dropped or borrowed as a whole can be split. Locals of nested aggregates
+//! are split level by level until no more local can be split. Splitting an aggregate often makes
+//! the stores to the fields that are never read dead, so they are removed right away, the unused
+//! locals being left to `SimplifyLocals`.
+//!
+//! The debuginfo of a split variable becomes a composite of the locals replacing its fields, e.g.
+//! `debug x => _1;` becomes `debug x => (u32, u64){ .0 => _3, .1 => _4, };`.
+//!
+//! The pass only runs from `-Zmir-opt-level=2` on, i.e. in optimized builds: debug builds, which
+//! use `-Zmir-opt-level=1`, keep their aggregates as they are.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_index::bit_set::BitSet;
+use rustc_index::vec::IndexVec;
+use rustc_middle::mir::patch::MirPatch;
+use rustc_middle::mir::visit::{MutVisitor, NonUseContext, PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_mir_dataflow::impls::borrowed_locals;
+
+use crate::dead_store_elimination;
+use crate::MirPass;
+
+pub struct ScalarReplacementOfAggregates;
+
+impl<'tcx> MirPass<'tcx> for ScalarReplacementOfAggregates {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        sess.mir_opt_level() >= 2
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        let mut changed = false;
+        loop {
+            let splittable = splittable_locals(body);
+            if splittable.is_empty() {
+                break;
+            }
+
+            let replacements = compute_replacements(body, &splittable);
+            debug!(?replacements);
+            replace_fields(tcx, body, &splittable, replacements);
+            changed = true;
+        }
+
+        if changed {
+            let borrowed = borrowed_locals(body);
+            dead_store_elimination::eliminate(tcx, body, &borrowed);
+        }
+    }
+}
+
+/// Whether locals of type `ty` can be split into their fields.
+fn is_splittable_ty(ty: Ty<'_>) -> bool {
+    match ty.kind() {
+        ty::Adt(adt_def, _) => adt_def.is_struct() && !ty.is_box() && !ty.is_simd(),
+        ty::Tuple(..) | ty::Closure(..) => true,
+        _ => false,
+    }
+}
+
+/// The locals which are only ever accessed through their fields.
+///
+/// Arguments and the return place are never split, as their layout is part of the ABI of the
+/// function. A local can't be split either once it is borrowed, as a pointer to any field could
+/// be used to access the other fields, or once it is used as a whole anywhere else than in its
+/// storage and `Deinit` statements and in the debuginfo of a variable.
+fn splittable_locals(body: &Body<'_>) -> BitSet<Local> {
+    let mut escaping = borrowed_locals(body);
+    escaping.insert_range(RETURN_PLACE..=Local::from_usize(body.arg_count));
+
+    let mut visitor = EscapeVisitor { escaping };
+    visitor.visit_body(body);
+    let escaping = visitor.escaping;
+
+    let mut splittable = BitSet::new_empty(body.local_decls.len());
+    for (local, decl) in body.local_decls.iter_enumerated() {
+        if !escaping.contains(local) && is_splittable_ty(decl.ty) {
+            splittable.insert(local);
+        }
+    }
+
+    // Only split the locals whose fields are actually used, the others are removed by
+    // `SimplifyLocals` anyway.
+    let used = BitSet::new_empty(body.local_decls.len());
+    let mut visitor = UsedFieldsVisitor { splittable: &splittable, used };
+    visitor.visit_body(body);
+    visitor.used
+}
+
+struct EscapeVisitor {
+    escaping: BitSet<Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for EscapeVisitor {
+    fn visit_local(&mut self, local: Local, _: PlaceContext, _: Location) {
+        self.escaping.insert(local);
+    }
+
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        // A field access doesn't use the local as a whole. There can't be any index to visit in
+        // the rest of the projection that would need to escape, as indices are `usize`.
+        if let [PlaceElem::Field(..), ..] = place.projection[..] {
+            return;
+        }
+        self.super_place(place, context, location);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement<'tcx>, location: Location) {
+        match statement.kind {
+            // These statements are split together with the local.
+            StatementKind::StorageLive(..) | StatementKind::StorageDead(..) => {}
+            StatementKind::Deinit(box place) if place.as_local().is_some() => {}
+            _ => self.super_statement(statement, location),
+        }
+    }
+
+    fn visit_var_debug_info(&mut self, var_debug_info: &VarDebugInfo<'tcx>) {
+        // The debuginfo of a whole local is split together with the local, see
+        // `replace_debuginfo`.
+        match &var_debug_info.value {
+            VarDebugInfoContents::Place(place) if place.as_local().is_some() => {}
+            VarDebugInfoContents::Composite { fragments, .. } => {
+                for fragment in fragments {
+                    if fragment.contents.as_local().is_none() {
+                        self.visit_place(
+                            &fragment.contents,
+                            PlaceContext::NonUse(NonUseContext::VarDebugInfo),
+                            START_BLOCK.start_location(),
+                        );
+                    }
+                }
+            }
+            _ => self.super_var_debug_info(var_debug_info),
+        }
+    }
+}
+
+struct UsedFieldsVisitor<'a> {
+    splittable: &'a BitSet<Local>,
+    used: BitSet<Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for UsedFieldsVisitor<'_> {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if let [PlaceElem::Field(..), ..] = place.projection[..]
+            && self.splittable.contains(place.local)
+        {
+            self.used.insert(place.local);
+        }
+        self.super_place(place, context, location);
+    }
+}
+
+#[derive(Debug)]
+struct ReplacementMap<'tcx> {
+    /// The local replacing each used field of the split locals.
+    fields: FxHashMap<(Local, Field), Local>,
+    /// The replacements of each split local with the field they replace, in the order of their
+    /// creation.
+    locals: FxHashMap<Local, Vec<(Field, Local)>>,
+    new_locals: IndexVec<Local, LocalDecl<'tcx>>,
+}
+
+/// Creates a new local for each field of the split locals that is used in the body, with the type
+/// of the projections to this field.
+fn compute_replacements<'tcx>(
+    body: &Body<'tcx>,
+    splittable: &BitSet<Local>,
+) -> ReplacementMap<'tcx> {
+    let mut visitor = ReplacementCollector {
+        body,
+        splittable,
+        replacements: ReplacementMap {
+            fields: Default::default(),
+            locals: Default::default(),
+            new_locals: IndexVec::new(),
+        },
+    };
+    visitor.visit_body(body);
+    visitor.replacements
+}
+
+struct ReplacementCollector<'a, 'tcx> {
+    body: &'a Body<'tcx>,
+    splittable: &'a BitSet<Local>,
+    replacements: ReplacementMap<'tcx>,
+}
+
+impl<'tcx> Visitor<'tcx> for ReplacementCollector<'_, 'tcx> {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if let [PlaceElem::Field(field, field_ty), ..] = place.projection[..]
+            && self.splittable.contains(place.local)
+        {
+            let ReplacementMap { fields, locals, new_locals } = &mut self.replacements;
+            let local_decls = &self.body.local_decls;
+            fields.entry((place.local, field)).or_insert_with(|| {
+                let span = local_decls[place.local].source_info.span;
+                let new_local = Local::new(local_decls.len() + new_locals.len());
+                new_locals.push(LocalDecl::new(field_ty, span));
+                locals.entry(place.local).or_default().push((field, new_local));
+                new_local
+            });
+        }
+        self.super_place(place, context, location);
+    }
+}
+
+fn replace_fields<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &mut Body<'tcx>,
+    splittable: &BitSet<Local>,
+    replacements: ReplacementMap<'tcx>,
+) {
+    let ReplacementMap { fields, locals, new_locals } = replacements;
+    body.local_decls.extend(new_locals);
+
+    // Split the storage and `Deinit` statements of the split locals.
+    let mut patch = MirPatch::new(body);
+    let basic_blocks = body.basic_blocks.as_mut_preserves_cfg();
+    for (block, data) in basic_blocks.iter_enumerated_mut() {
+        for (statement_index, statement) in data.statements.iter_mut().enumerate() {
+            let local = match statement.kind {
+                StatementKind::StorageLive(local) | StatementKind::StorageDead(local) => local,
+                StatementKind::Deinit(box place) => match place.as_local() {
+                    Some(local) => local,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if !splittable.contains(local) {
+                continue;
+            }
+
+            let location = Location { block, statement_index };
+            for &(_, new_local) in locals.get(&local).into_iter().flatten() {
+                let kind = match statement.kind {
+                    StatementKind::StorageLive(_) => StatementKind::StorageLive(new_local),
+                    StatementKind::StorageDead(_) => StatementKind::StorageDead(new_local),
+                    _ => StatementKind::Deinit(Box::new(new_local.into())),
+                };
+                patch.add_statement(location, kind);
+            }
+            statement.make_nop();
+        }
+    }
+    patch.apply(body);
+
+    replace_debuginfo(body, splittable, &locals);
+    ReplacementVisitor { tcx, fields: &fields }.visit_body_preserves_cfg(body);
+}
+
+/// Replaces the debuginfo of the split locals by a composite of the locals replacing their fields.
+/// The fields that are never used have no replacement: they are left out of the composite, and
+/// show up as optimized out in debuggers. The debuginfo of a single field is replaced together
+/// with the other uses of the field.
+fn replace_debuginfo<'tcx>(
+    body: &mut Body<'tcx>,
+    splittable: &BitSet<Local>,
+    locals: &FxHashMap<Local, Vec<(Field, Local)>>,
+) {
+    let local_decls = &body.local_decls;
+    let field_fragments = |projection: &[PlaceElem<'tcx>], local: Local| {
+        let mut fields = locals.get(&local).cloned().unwrap_or_default();
+        fields.sort_by_key(|&(field, _)| field);
+        fields
+            .into_iter()
+            .map(|(field, new_local)| {
+                let mut projection = projection.to_vec();
+                projection.push(PlaceElem::Field(field, local_decls[new_local].ty));
+                VarDebugInfoFragment { projection, contents: new_local.into() }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    for var_debug_info in &mut body.var_debug_info {
+        match &mut var_debug_info.value {
+            VarDebugInfoContents::Place(place) => {
+                let Some(local) = place.as_local() else { continue };
+                if !splittable.contains(local) {
+                    continue;
+                }
+                var_debug_info.value = VarDebugInfoContents::Composite {
+                    ty: local_decls[local].ty,
+                    fragments: field_fragments(&[], local),
+                };
+            }
+            // A fragment can itself be a local that is split, when nested aggregates are split
+            // level by level.
+            VarDebugInfoContents::Composite { ty: _, fragments } => {
+                let mut new_fragments = Vec::with_capacity(fragments.len());
+                for fragment in fragments.drain(..) {
+                    match fragment.contents.as_local() {
+                        Some(local) if splittable.contains(local) => {
+                            new_fragments.extend(field_fragments(&fragment.projection, local));
+                        }
+                        _ => new_fragments.push(fragment),
+                    }
+                }
+                *fragments = new_fragments;
+            }
+            VarDebugInfoContents::Const(_) => {}
+        }
+    }
+}
+
+struct ReplacementVisitor<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    fields: &'a FxHashMap<(Local, Field), Local>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for ReplacementVisitor<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_place(&mut self, place: &mut Place<'tcx>, context: PlaceContext, location: Location) {
+        let projection = place.projection;
+        if let [PlaceElem::Field(field, _), rest @ ..] = &projection[..]
+            && let Some(&new_local) = self.fields.get(&(place.local, *field))
+        {
+            *place = Place { local: new_local, projection: self.tcx.intern_place_elems(rest) };
+            return;
+        }
+        self.super_place(place, context, location);
+    }
+}
@@ -97,7 +97,7 @@
 
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync;
-use rustc_hir::def_id::DefIdSet;
+use rustc_hir::def_id::{CrateNum, DefId, DefIdSet, LOCAL_CRATE};
 use rustc_middle::mir;
 use rustc_middle::mir::mono::MonoItem;
 use rustc_middle::mir::mono::{CodegenUnit, Linkage};
@@ -465,9 +465,97 @@ fn collect_and_partition_mono_items<'tcx>(
         }
     }
 
+    if tcx.sess.opts.unstable_opts.print_mono_item_stats {
+        print_mono_item_stats(tcx, &items, &inlining_map);
+    }
+
     (tcx.arena.alloc(mono_items), codegen_units)
 }
 
+#[derive(Default)]
+struct MonoItemStats {
+    instances: usize,
+    size_estimate: usize,
+    // The number of instances required by the code of each crate.
+    required_by: FxHashMap<CrateNum, usize>,
+}
+
+/// Groups the mono items by the definition they were instantiated from and
+/// prints, for each definition, how many instances there are, their estimated
+/// MIR size and the crates containing the code that requires them. This shows
+/// which generic functions are responsible for most of the code to generate.
+///
+/// An instance required by code from several crates is counted for each of
+/// them. Roots of the collection are attributed to the local crate.
+fn print_mono_item_stats<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    inlining_map: &InliningMap<'tcx>,
+) {
+    let item_def_id = |item: MonoItem<'tcx>| match item {
+        MonoItem::Fn(instance) => instance.def_id(),
+        MonoItem::Static(def_id) => def_id,
+        MonoItem::GlobalAsm(item_id) => item_id.def_id.to_def_id(),
+    };
+
+    // A mono item is required by the crate defining the code that references it,
+    // which isn't necessarily the crate it is codegened in.
+    let mut required_by: FxHashMap<MonoItem<'tcx>, FxHashSet<CrateNum>> = Default::default();
+    inlining_map.iter_accesses(|accessor, accessees| {
+        let krate = item_def_id(accessor).krate;
+        for &accessee in accessees {
+            required_by.entry(accessee).or_default().insert(krate);
+        }
+    });
+
+    let mut stats: FxHashMap<DefId, MonoItemStats> = Default::default();
+    for &item in items {
+        let def_stats = stats.entry(item_def_id(item)).or_default();
+        def_stats.instances += 1;
+        def_stats.size_estimate += item.size_estimate(tcx);
+        match required_by.get(&item) {
+            Some(crates) => {
+                for &krate in crates {
+                    *def_stats.required_by.entry(krate).or_default() += 1;
+                }
+            }
+            None => *def_stats.required_by.entry(LOCAL_CRATE).or_default() += 1,
+        }
+    }
+
+    let mut lines: Vec<_> = stats
+        .into_iter()
+        .map(|(def_id, def_stats)| {
+            let mut required_by: Vec<_> = def_stats
+                .required_by
+                .into_iter()
+                .map(|(krate, count)| (tcx.crate_name(krate), count))
+                .collect();
+            required_by.sort_by(|(a_name, a_count), (b_name, b_count)| {
+                b_count.cmp(a_count).then_with(|| a_name.as_str().cmp(b_name.as_str()))
+            });
+
+            let mut output = format!(
+                "{} {} {} @@",
+                def_stats.size_estimate,
+                def_stats.instances,
+                with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+            );
+            for (crate_name, count) in required_by {
+                output.push_str(&format!(" {}[{}]", crate_name, count));
+            }
+            (def_stats.size_estimate, output)
+        })
+        .collect();
+
+    // Largest first, ties are broken by the rest of the line to keep the output deterministic.
+    lines.sort_by(|(a_size, a), (b_size, b)| b_size.cmp(a_size).then_with(|| a.cmp(b)));
+
+    for (_, line) in lines {
+        println!("MONO_ITEM_STATS {}", line);
+    }
+}
+
 fn codegened_and_inlined_items<'tcx>(tcx: TyCtxt<'tcx>, (): ()) -> &'tcx DefIdSet {
     let (items, cgus) = tcx.collect_and_partition_mono_items(());
     let mut visited = DefIdSet::default();
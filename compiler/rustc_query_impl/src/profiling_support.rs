@@ -236,6 +236,15 @@ fn alloc_self_profile_query_strings_for_query_cache<'tcx, C>(
     C::Key: Debug + Clone,
 {
     tcx.prof.with_profiler(|profiler| {
+        if profiler.query_summary_enabled() {
+            let mut query_invocation_ids = Vec::new();
+            query_cache.iter(&mut |_, _, i| query_invocation_ids.push(i.into()));
+            profiler.map_query_invocation_ids_to_query_name(
+                query_invocation_ids.into_iter(),
+                query_name,
+            );
+        }
+
         let event_id_builder = profiler.event_id_builder();
 
         // Walk the entire query cache and allocate the appropriate
@@ -553,6 +553,18 @@ pub fn dep_node_debug_str(&self, dep_node: DepNode<K>) -> Option<String> {
         self.data.as_ref()?.dep_node_debug.borrow().get(&dep_node).cloned()
     }
 
+    /// Returns the dependency graph of the previous compilation session, if
+    /// incremental compilation is enabled.
+    pub fn previous_graph(&self) -> Option<&SerializedDepGraph<K>> {
+        self.data.as_ref().map(|data| &data.previous)
+    }
+
+    /// Returns the color a node of the previous dependency graph has been
+    /// marked with so far in the current compilation session, if any.
+    pub fn previous_node_color(&self, prev_index: SerializedDepNodeIndex) -> Option<DepNodeColor> {
+        self.data.as_ref().and_then(|data| data.colors.get(prev_index))
+    }
+
     fn node_color(&self, dep_node: &DepNode<K>) -> Option<DepNodeColor> {
         if let Some(ref data) = self.data {
             if let Some(prev_index) = data.previous.node_to_index_opt(dep_node) {
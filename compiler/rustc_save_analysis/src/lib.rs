@@ -6,6 +6,7 @@
 
 mod dump_visitor;
 mod dumper;
+mod scip;
 #[macro_use]
 mod span_utils;
 mod sig;
@@ -37,6 +38,7 @@
 use std::path::{Path, PathBuf};
 
 use dump_visitor::DumpVisitor;
+pub use scip::ScipHandler;
 use span_utils::SpanUtils;
 
 use rls_data::config::Config;
@@ -101,6 +103,9 @@ pub fn compilation_output(&self, crate_name: &str) -> PathBuf {
             filename_for_metadata(sess, crate_name, outputs)
         } else if outputs.outputs.should_codegen() {
             out_filename(sess, crate_type, outputs, crate_name)
+        } else if outputs.outputs.contains_key(&OutputType::Scip) {
+            // Only the SCIP index is emitted, so it stands for the output.
+            outputs.path(OutputType::Scip)
         } else {
             // Otherwise it's only a DepInfo, in which case we return early and
             // not even reach the analysis stage.
@@ -0,0 +1,408 @@
+//! Writes the results of save-analysis as a [SCIP] index, for `--emit=scip`.
+//!
+//! SCIP is a protobuf format in which each source file is a document listing
+//! the occurrences of symbols in it, and the information about the symbols
+//! it defines: their documentation and their relationships to other symbols.
+//! Symbols are named after their def path, so that the occurrences of the
+//! items of a dependency in one index match their definitions in the index of
+//! that dependency:
+//!
+//! ```text
+//! rustc cargo mycrate 1b4a03e9c8f0d2a7 module/Type#method().
+//! ```
+//!
+//! The version of a package is the `StableCrateId` of the crate, which is the
+//! same in the indexes of the crate and of the crates depending on it.
+//!
+//! [SCIP]: https://github.com/sourcegraph/scip/blob/main/scip.proto
+
+use crate::{SaveContext, SaveHandler};
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def::DefKind as HirDefKind;
+use rustc_hir::def_id::{CrateNum, DefId, DefIndex, LOCAL_CRATE};
+use rustc_hir::definitions::DefPathData;
+use rustc_middle::ty::TyCtxt;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, PathBuf};
+
+use rls_data::config::Config;
+use rls_data::{Analysis, Def, DefKind, Id, RelationKind, SpanData};
+
+/// The `SymbolRole` bit of the occurrences defining a symbol.
+const ROLE_DEFINITION: u64 = 0x1;
+/// The `SymbolRole` bit of the occurrences importing a symbol.
+const ROLE_IMPORT: u64 = 0x2;
+/// The `TextEncoding::UTF8` value of `Metadata::text_document_encoding`.
+const TEXT_ENCODING_UTF8: u64 = 1;
+/// The `PositionEncoding::UTF32CodeUnitOffsetFromLineStart` value of
+/// `Document::position_encoding`, as the columns of save-analysis count
+/// chars.
+const POSITION_ENCODING_UTF32: u64 = 3;
+
+/// Writes the save-analysis results to a SCIP index.
+pub struct ScipHandler {
+    path: PathBuf,
+}
+
+impl ScipHandler {
+    pub fn new(path: PathBuf) -> ScipHandler {
+        ScipHandler { path }
+    }
+
+    /// The save-analysis config providing what the index needs: signatures
+    /// for hovers, and all the definitions with their full docs.
+    pub fn config() -> Config {
+        Config { full_docs: true, signatures: true, ..Config::default() }
+    }
+}
+
+impl SaveHandler for ScipHandler {
+    fn save(&mut self, save_ctxt: &SaveContext<'_>, analysis: &Analysis) {
+        let sess = &save_ctxt.tcx.sess;
+        let index = IndexBuilder::new(save_ctxt.tcx).build(analysis);
+        if let Err(e) = fs::write(&self.path, index.bytes) {
+            sess.err(&format!("failed to write SCIP index `{}`: {}", self.path.display(), e));
+            return;
+        }
+
+        if sess.opts.json_artifact_notifications {
+            sess.parse_sess.span_diagnostic.emit_artifact_notification(&self.path, "scip");
+        }
+    }
+}
+
+/// An encoded protobuf message.
+#[derive(Default)]
+struct Message {
+    bytes: Vec<u8>,
+}
+
+impl Message {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.varint(u64::from(field) << 3 | u64::from(wire_type));
+    }
+
+    fn bytes_field(&mut self, field: u32, bytes: &[u8]) {
+        self.key(field, 2);
+        self.varint(bytes.len() as u64);
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// Writes an integer field, omitting it if it is zero, as proto3 does.
+    fn uint(&mut self, field: u32, value: u64) {
+        if value != 0 {
+            self.key(field, 0);
+            self.varint(value);
+        }
+    }
+
+    fn bool(&mut self, field: u32, value: bool) {
+        self.uint(field, value as u64);
+    }
+
+    fn string(&mut self, field: u32, value: &str) {
+        if !value.is_empty() {
+            self.bytes_field(field, value.as_bytes());
+        }
+    }
+
+    fn message(&mut self, field: u32, message: &Message) {
+        self.bytes_field(field, &message.bytes);
+    }
+
+    fn packed_uints(&mut self, field: u32, values: &[u32]) {
+        let mut packed = Message::default();
+        for &value in values {
+            packed.varint(u64::from(value));
+        }
+        self.message(field, &packed);
+    }
+}
+
+/// The occurrences and symbols of a source file.
+#[derive(Default)]
+struct Document {
+    occurrences: Vec<Message>,
+    symbols: Vec<Message>,
+}
+
+struct IndexBuilder<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The directory that the paths of the documents are relative to.
+    project_root: PathBuf,
+    /// The number of definitions of the local crate. Local variables don't
+    /// have a `DefId`, and save-analysis gives them ids past this number.
+    local_def_count: usize,
+    /// The ids of the local variables.
+    locals: FxHashSet<Id>,
+    symbols: FxHashMap<DefId, String>,
+    /// The relationships of each symbol, as `Relationship` messages.
+    relationships: BTreeMap<String, Vec<Message>>,
+    documents: BTreeMap<String, Document>,
+}
+
+impl<'tcx> IndexBuilder<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> IndexBuilder<'tcx> {
+        IndexBuilder {
+            tcx,
+            project_root: tcx.sess.opts.working_dir.remapped_path_if_available().to_path_buf(),
+            local_def_count: tcx.definitions_untracked().def_index_count(),
+            locals: Default::default(),
+            symbols: Default::default(),
+            relationships: Default::default(),
+            documents: Default::default(),
+        }
+    }
+
+    fn build(mut self, analysis: &Analysis) -> Message {
+        self.locals = analysis
+            .defs
+            .iter()
+            .filter(|def| matches!(def.kind, DefKind::Local))
+            .map(|def| def.id)
+            .collect();
+
+        // Relationships are collected first, to be attached to the symbols
+        // when their definition is found.
+        for def in &analysis.defs {
+            if let (Some(symbol), Some(decl)) =
+                (self.symbol(def.id), def.decl_id.and_then(|id| self.symbol(id)))
+            {
+                self.add_implementation(symbol, decl);
+            }
+        }
+        for relation in &analysis.relations {
+            if let RelationKind::Impl { .. } = relation.kind {
+                if let (Some(from), Some(to)) =
+                    (self.symbol(relation.from), self.symbol(relation.to))
+                {
+                    self.add_implementation(from, to);
+                }
+            }
+        }
+
+        for def in &analysis.defs {
+            self.add_def(def);
+        }
+        for r in &analysis.refs {
+            if let Some(symbol) = self.symbol(r.ref_id) {
+                self.add_occurrence(&r.span, &symbol, 0);
+            }
+        }
+        for import in &analysis.imports {
+            if let Some(symbol) = import.ref_id.and_then(|id| self.symbol(id)) {
+                self.add_occurrence(&import.span, &symbol, ROLE_IMPORT);
+            }
+        }
+
+        let mut index = Message::default();
+        index.message(1, &self.metadata());
+        for (relative_path, document) in &self.documents {
+            let mut message = Message::default();
+            message.string(1, relative_path);
+            for occurrence in &document.occurrences {
+                message.message(2, occurrence);
+            }
+            for symbol in &document.symbols {
+                message.message(3, symbol);
+            }
+            message.string(4, "rust");
+            message.uint(6, POSITION_ENCODING_UTF32);
+            index.message(2, &message);
+        }
+        // The symbols defined outside of the crate, such as the foreign types
+        // implementing local traits, only carry their relationships.
+        for (symbol, relationships) in &self.relationships {
+            let mut message = Message::default();
+            message.string(1, symbol);
+            for relationship in relationships {
+                message.message(4, relationship);
+            }
+            index.message(3, &message);
+        }
+        index
+    }
+
+    fn metadata(&self) -> Message {
+        let mut tool_info = Message::default();
+        tool_info.string(1, "rustc");
+        tool_info.string(2, option_env!("CFG_VERSION").unwrap_or("unknown version"));
+
+        let mut metadata = Message::default();
+        metadata.message(2, &tool_info);
+        metadata.string(3, &format!("file://{}", self.project_root.display()));
+        metadata.uint(4, TEXT_ENCODING_UTF8);
+        metadata
+    }
+
+    fn add_implementation(&mut self, symbol: String, implemented: String) {
+        let mut relationship = Message::default();
+        relationship.string(1, &implemented);
+        relationship.bool(3, true);
+        self.relationships.entry(symbol).or_default().push(relationship);
+    }
+
+    fn add_def(&mut self, def: &Def) {
+        let Some(symbol) = self.symbol(def.id) else { return };
+        let Some(relative_path) = self.relative_path(&def.span) else { return };
+
+        let mut info = Message::default();
+        info.string(1, &symbol);
+        // The hover of the symbol: its signature, then its docs.
+        let signature = def.sig.as_ref().map_or(&def.value, |sig| &sig.text);
+        if !signature.is_empty() {
+            info.string(3, &format!("```rust\n{}\n```", signature));
+        }
+        if !def.docs.is_empty() {
+            info.string(3, &def.docs);
+        }
+        for relationship in self.relationships.remove(&symbol).unwrap_or_default() {
+            info.message(4, &relationship);
+        }
+        info.string(6, &def.name);
+        self.documents.entry(relative_path).or_default().symbols.push(info);
+
+        self.add_occurrence(&def.span, &symbol, ROLE_DEFINITION);
+    }
+
+    fn add_occurrence(&mut self, span: &SpanData, symbol: &str, roles: u64) {
+        let Some(relative_path) = self.relative_path(span) else { return };
+
+        let start_line = span.line_start.zero_indexed().0;
+        let start_column = span.column_start.zero_indexed().0;
+        let end_line = span.line_end.zero_indexed().0;
+        let end_column = span.column_end.zero_indexed().0;
+        // Ranges on a single line omit their end line.
+        let range = if start_line == end_line {
+            vec![start_line, start_column, end_column]
+        } else {
+            vec![start_line, start_column, end_line, end_column]
+        };
+
+        let mut occurrence = Message::default();
+        occurrence.packed_uints(1, &range);
+        occurrence.string(2, symbol);
+        occurrence.uint(3, roles);
+        self.documents.entry(relative_path).or_default().occurrences.push(occurrence);
+    }
+
+    /// Returns the path of the file of `span` relative to the project root,
+    /// if that file is in the project root.
+    fn relative_path(&self, span: &SpanData) -> Option<String> {
+        let path = &span.file_name;
+        let relative_path = if path.is_absolute() {
+            path.strip_prefix(&self.project_root).ok()?
+        } else {
+            path.as_path()
+        };
+        if !relative_path.components().all(|c| matches!(c, Component::Normal(_))) {
+            return None;
+        }
+        Some(relative_path.to_str()?.replace('\\', "/"))
+    }
+
+    /// Returns the SCIP symbol of the definition with the save-analysis id
+    /// `id`.
+    fn symbol(&mut self, id: Id) -> Option<String> {
+        if self.locals.contains(&id) {
+            return Some(format!("local {}", id.index));
+        }
+        let krate = CrateNum::from_u32(id.krate);
+        if id.krate == u32::MAX || krate == LOCAL_CRATE && id.index as usize >= self.local_def_count
+        {
+            return None;
+        }
+        let def_id = DefId { krate, index: DefIndex::from_u32(id.index) };
+        if let Some(symbol) = self.symbols.get(&def_id) {
+            return Some(symbol.clone());
+        }
+
+        let mut symbol = format!(
+            "rustc cargo {} {:016x} ",
+            self.tcx.crate_name(krate),
+            self.tcx.stable_crate_id(krate).to_u64()
+        );
+        let descriptors_start = symbol.len();
+        self.push_descriptors(def_id, &mut symbol);
+        if symbol.len() == descriptors_start {
+            // The crate root.
+            symbol.push_str("crate/");
+        }
+        self.symbols.insert(def_id, symbol.clone());
+        Some(symbol)
+    }
+
+    /// Appends the descriptors of the def path of `def_id` to `symbol`.
+    fn push_descriptors(&self, def_id: DefId, symbol: &mut String) {
+        let key = self.tcx.def_key(def_id);
+        if let Some(parent) = key.parent {
+            self.push_descriptors(DefId { krate: def_id.krate, index: parent }, symbol);
+        }
+
+        match key.disambiguated_data.data {
+            // These don't have a name in paths, and the constructor of a
+            // struct or variant is referred to as the struct or variant.
+            DefPathData::CrateRoot | DefPathData::ForeignMod | DefPathData::Ctor => return,
+            _ => {}
+        }
+        let name = escape_name(&key.disambiguated_data.to_string());
+        match self.tcx.def_kind(def_id) {
+            HirDefKind::Mod => {
+                symbol.push_str(&name);
+                symbol.push('/');
+            }
+            HirDefKind::Struct
+            | HirDefKind::Union
+            | HirDefKind::Enum
+            | HirDefKind::Variant
+            | HirDefKind::Trait
+            | HirDefKind::TraitAlias
+            | HirDefKind::TyAlias
+            | HirDefKind::ForeignTy
+            | HirDefKind::AssocTy
+            | HirDefKind::OpaqueTy
+            | HirDefKind::Impl => {
+                symbol.push_str(&name);
+                symbol.push('#');
+            }
+            HirDefKind::Fn | HirDefKind::AssocFn => {
+                symbol.push_str(&name);
+                symbol.push_str("().");
+            }
+            HirDefKind::Macro(_) => {
+                symbol.push_str(&name);
+                symbol.push('!');
+            }
+            HirDefKind::TyParam | HirDefKind::ConstParam | HirDefKind::LifetimeParam => {
+                symbol.push('[');
+                symbol.push_str(&name);
+                symbol.push(']');
+            }
+            _ => {
+                symbol.push_str(&name);
+                symbol.push('.');
+            }
+        }
+    }
+}
+
+/// Escapes the names that aren't SCIP identifiers, such as `{impl#0}`, with
+/// backticks.
+fn escape_name(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || "_+-$".contains(c)) {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
@@ -1,6 +1,7 @@
 use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::sync::Lock;
-use rustc_span::Symbol;
+use rustc_errors::Handler;
+use rustc_span::{Span, Symbol};
 use rustc_target::abi::{Align, Size};
 use std::cmp::{self, Ordering};
 
@@ -46,9 +47,37 @@ pub struct TypeSizeInfo {
     pub variants: Vec<VariantInfo>,
 }
 
+/// The number of steps spent in a function, or in a call of a function, during the evaluation of
+/// a constant.
+#[derive(Clone, Debug)]
+pub struct ConstEvalFnStats {
+    pub description: String,
+    /// The span of the function, or of the call.
+    pub span: Span,
+    pub steps: u64,
+}
+
+/// What the evaluation of a constant cost, recorded with `-Zconst-eval-trace`.
+#[derive(Clone, Debug)]
+pub struct ConstEvalTrace {
+    pub description: String,
+    pub span: Span,
+    pub steps: u64,
+    pub bytes_allocated: u64,
+    /// Whether the evaluation was stopped by the `const_eval_limit`.
+    pub hit_limit: bool,
+    /// The functions the most steps were spent in, most expensive first. The steps of a function
+    /// don't include the steps of the functions it calls.
+    pub hottest_fns: Vec<ConstEvalFnStats>,
+    /// The call stack of the call the most steps were spent in, outermost call first, with the
+    /// steps spent in each of these calls themselves.
+    pub hottest_stack: Vec<ConstEvalFnStats>,
+}
+
 #[derive(Default)]
 pub struct CodeStats {
     type_sizes: Lock<FxHashSet<TypeSizeInfo>>,
+    const_eval_traces: Lock<Vec<ConstEvalTrace>>,
 }
 
 impl CodeStats {
@@ -179,4 +208,50 @@ pub fn print_type_sizes(&self) {
             }
         }
     }
+
+    pub fn record_const_eval_trace(&self, trace: ConstEvalTrace) {
+        self.const_eval_traces.borrow_mut().push(trace);
+    }
+
+    /// Reports the constants whose evaluation took the most steps, with the functions and the
+    /// call stack these steps were spent in.
+    pub fn report_const_eval_traces(&self, handler: &Handler) {
+        const MAX_REPORTED_CONSTS: usize = 10;
+        const MAX_REPORTED_FRAMES: usize = 8;
+
+        let traces = self.const_eval_traces.borrow();
+        let mut sorted: Vec<_> = traces.iter().collect();
+        sorted.sort_by(|trace1, trace2| {
+            trace2
+                .steps
+                .cmp(&trace1.steps)
+                .then_with(|| trace1.description.cmp(&trace2.description))
+        });
+
+        for trace in sorted.into_iter().take(MAX_REPORTED_CONSTS) {
+            let ConstEvalTrace { description, span, steps, bytes_allocated, hit_limit, .. } = trace;
+            let limit = if *hit_limit { ", and hit the const eval limit" } else { "" };
+            let mut diag = handler.struct_note_without_error(format!(
+                "evaluating `{description}` took {steps} steps and allocated {bytes_allocated} bytes{limit}"
+            ));
+            diag.set_span(*span);
+            for ConstEvalFnStats { description, steps, .. } in &trace.hottest_fns {
+                diag.note(format!("{steps} steps were spent in `{description}`"));
+            }
+
+            // The outermost frame is the constant itself.
+            let frames = trace.hottest_stack.get(1..).unwrap_or_default();
+            let omitted = frames.len().saturating_sub(MAX_REPORTED_FRAMES);
+            if omitted > 0 {
+                diag.note(format!("{omitted} outer calls of the most expensive call are omitted"));
+            }
+            for ConstEvalFnStats { description, span, steps } in &frames[omitted..] {
+                diag.span_note(
+                    *span,
+                    format!("in this call to `{description}`, which took {steps} steps itself"),
+                );
+            }
+            diag.emit();
+        }
+    }
 }
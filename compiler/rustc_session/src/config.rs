@@ -3,6 +3,7 @@
 
 pub use crate::options::*;
 
+use crate::lint_config::LintConfig;
 use crate::search_paths::SearchPath;
 use crate::utils::{CanonicalizedPath, NativeLib, NativeLibKind};
 use crate::{early_error, early_warn, Session};
@@ -140,6 +141,37 @@ pub enum MirSpanview {
     Block,
 }
 
+/// The different settings that the `-Z dump-dep-graph-format` flag can have.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum DepGraphDumpFormat {
+    /// Default `-Z dump-dep-graph-format=dot`, writes the edges between dep kinds to `.txt` and
+    /// graphviz `.dot` files.
+    Dot,
+    /// `-Z dump-dep-graph-format=json`, writes every dep node with its color and edges to a
+    /// `.json` file.
+    Json,
+}
+
+/// The different settings that the `-Z dump-mir-format` flag can have.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum MirDumpFormat {
+    /// Default `-Z dump-mir-format=text`, writes the pretty-printed MIR to `.mir` files.
+    Text,
+    /// `-Z dump-mir-format=json`, writes the MIR as JSON to `.json` files.
+    Json,
+}
+
+/// The different settings that the `-Z nll-facts-format` flag can have.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum NllFactsFormat {
+    /// Default `-Z nll-facts-format=tsv`, writes the Polonius input facts to one `.facts` file
+    /// per relation.
+    Tsv,
+    /// `-Z nll-facts-format=json`, writes the facts, the source spans of their points, loans and
+    /// regions, the inferred region values and the borrowck errors to a single `facts.json` file.
+    Json,
+}
+
 /// The different settings that the `-C instrument-coverage` flag can have.
 ///
 /// Coverage instrumentation now supports combining `-C instrument-coverage`
@@ -284,6 +316,7 @@ pub enum OutputType {
     Object,
     Exe,
     DepInfo,
+    Scip,
 }
 
 impl<HCX: HashStableContext> ToStableHashKey<HCX> for OutputType {
@@ -297,7 +330,7 @@ fn to_stable_hash_key(&self, _: &HCX) -> Self::KeyType {
 impl OutputType {
     fn is_compatible_with_codegen_units_and_single_output_file(&self) -> bool {
         match *self {
-            OutputType::Exe | OutputType::DepInfo | OutputType::Metadata => true,
+            OutputType::Exe | OutputType::DepInfo | OutputType::Metadata | OutputType::Scip => true,
             OutputType::Bitcode
             | OutputType::Assembly
             | OutputType::LlvmAssembly
@@ -316,6 +349,7 @@ fn shorthand(&self) -> &'static str {
             OutputType::Metadata => "metadata",
             OutputType::Exe => "link",
             OutputType::DepInfo => "dep-info",
+            OutputType::Scip => "scip",
         }
     }
 
@@ -329,13 +363,14 @@ fn from_shorthand(shorthand: &str) -> Option<Self> {
             "metadata" => OutputType::Metadata,
             "link" => OutputType::Exe,
             "dep-info" => OutputType::DepInfo,
+            "scip" => OutputType::Scip,
             _ => return None,
         })
     }
 
     fn shorthands_display() -> String {
         format!(
-            "`{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`",
+            "`{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`",
             OutputType::Bitcode.shorthand(),
             OutputType::Assembly.shorthand(),
             OutputType::LlvmAssembly.shorthand(),
@@ -344,6 +379,7 @@ fn shorthands_display() -> String {
             OutputType::Metadata.shorthand(),
             OutputType::Exe.shorthand(),
             OutputType::DepInfo.shorthand(),
+            OutputType::Scip.shorthand(),
         )
     }
 
@@ -356,6 +392,7 @@ pub fn extension(&self) -> &'static str {
             OutputType::Object => "o",
             OutputType::Metadata => "rmeta",
             OutputType::DepInfo => "d",
+            OutputType::Scip => "scip",
             OutputType::Exe => "",
         }
     }
@@ -374,6 +411,8 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A single SARIF 2.1.0 log, consumed by code-review and security dashboards.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -435,7 +474,7 @@ pub fn should_codegen(&self) -> bool {
             | OutputType::Mir
             | OutputType::Object
             | OutputType::Exe => true,
-            OutputType::Metadata | OutputType::DepInfo => false,
+            OutputType::Metadata | OutputType::DepInfo | OutputType::Scip => false,
         })
     }
 
@@ -448,7 +487,8 @@ pub fn should_link(&self) -> bool {
             | OutputType::Mir
             | OutputType::Metadata
             | OutputType::Object
-            | OutputType::DepInfo => false,
+            | OutputType::DepInfo
+            | OutputType::Scip => false,
             OutputType::Exe => true,
         })
     }
@@ -716,6 +756,7 @@ fn default() -> Options {
             lint_opts: Vec::new(),
             lint_cap: None,
             describe_lints: false,
+            lint_config: None,
             output_types: OutputTypes(BTreeMap::new()),
             search_paths: vec![],
             maybe_sysroot: None,
@@ -742,6 +783,8 @@ fn default() -> Options {
             json_artifact_notifications: false,
             json_unused_externs: JsonUnusedExterns::No,
             json_future_incompat: false,
+            json_dependency_usage: false,
+            apply_suggestions: None,
             pretty: None,
             working_dir: RealFileName::LocalPath(std::env::current_dir().unwrap()),
         }
@@ -791,6 +834,8 @@ pub fn diagnostic_handler_flags(&self, can_emit_warnings: bool) -> HandlerFlags
             report_delayed_bugs: self.report_delayed_bugs,
             macro_backtrace: self.macro_backtrace,
             deduplicate_diagnostics: self.deduplicate_diagnostics,
+            // Set from `--apply-suggestions`, which isn't an unstable option.
+            collect_suggestions: false,
         }
     }
 }
@@ -1337,7 +1382,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "emit",
             "Comma separated list of types of output for \
              the compiler to emit",
-            "[asm|llvm-bc|llvm-ir|obj|metadata|link|dep-info|mir]",
+            "[asm|llvm-bc|llvm-ir|obj|metadata|link|dep-info|mir|scip]",
         ),
         opt::multi_s(
             "",
@@ -1405,9 +1450,22 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
             "",
             "error-format",
             "How errors and other messages are produced",
-            "human|json|short",
+            "human|json|sarif|short",
         ),
         opt::multi_s("", "json", "Configure the JSON output of the compiler", "CONFIG"),
+        opt::opt(
+            "",
+            "lint-config",
+            "Read lint levels for the crate and for some of its source files from a TOML file",
+            "PATH",
+        ),
+        opt::opt(
+            "",
+            "apply-suggestions",
+            "Apply the machine-applicable suggestions of the emitted diagnostics to the source \
+             files, or only print them as a unified diff with `dry-run`",
+            "machine-applicable[,dry-run]",
+        ),
         opt::opt_s(
             "",
             "color",
@@ -1490,6 +1548,7 @@ pub struct JsonConfig {
     pub json_artifact_notifications: bool,
     pub json_unused_externs: JsonUnusedExterns,
     pub json_future_incompat: bool,
+    pub json_dependency_usage: bool,
 }
 
 /// Report unused externs in event stream
@@ -1530,6 +1589,7 @@ pub fn parse_json(matches: &getopts::Matches) -> JsonConfig {
     let mut json_artifact_notifications = false;
     let mut json_unused_externs = JsonUnusedExterns::No;
     let mut json_future_incompat = false;
+    let mut json_dependency_usage = false;
     for option in matches.opt_strs("json") {
         // For now conservatively forbid `--color` with `--json` since `--json`
         // won't actually be emitting any colors and anything colorized is
@@ -1549,6 +1609,7 @@ pub fn parse_json(matches: &getopts::Matches) -> JsonConfig {
                 "unused-externs" => json_unused_externs = JsonUnusedExterns::Loud,
                 "unused-externs-silent" => json_unused_externs = JsonUnusedExterns::Silent,
                 "future-incompat" => json_future_incompat = true,
+                "dependency-usage" => json_dependency_usage = true,
                 s => early_error(
                     ErrorOutputType::default(),
                     &format!("unknown `--json` option `{s}`"),
@@ -1562,7 +1623,40 @@ pub fn parse_json(matches: &getopts::Matches) -> JsonConfig {
         json_artifact_notifications,
         json_unused_externs,
         json_future_incompat,
+        json_dependency_usage,
+    }
+}
+
+/// The settings of `--apply-suggestions`.
+#[derive(Copy, Clone, PartialEq, Hash, Debug)]
+pub struct ApplySuggestions {
+    /// Print the changes as a unified diff instead of writing them to the source files.
+    pub dry_run: bool,
+}
+
+/// Parses the `--apply-suggestions` flag.
+pub fn parse_apply_suggestions(
+    matches: &getopts::Matches,
+    error_format: ErrorOutputType,
+) -> Option<ApplySuggestions> {
+    let arg = matches.opt_str("apply-suggestions")?;
+    let mut sub_options = arg.split(',');
+    if sub_options.next() != Some("machine-applicable") {
+        early_error(
+            error_format,
+            "`--apply-suggestions` must start with `machine-applicable`, the only \
+             applicability of suggestions that can be applied automatically",
+        );
+    }
+
+    let mut apply = ApplySuggestions { dry_run: false };
+    for sub_option in sub_options {
+        match sub_option {
+            "dry-run" => apply.dry_run = true,
+            s => early_error(error_format, &format!("unknown `--apply-suggestions` option `{s}`")),
+        }
     }
+    Some(apply)
 }
 
 /// Parses the `--error-format` flag.
@@ -1585,12 +1679,13 @@ pub fn parse_error_format(
             }
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
+            Some("sarif") => ErrorOutputType::Sarif,
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
 
             Some(arg) => early_error(
                 ErrorOutputType::HumanReadable(HumanReadableErrorType::Default(color)),
                 &format!(
-                    "argument for `--error-format` must be `human`, `json` or \
+                    "argument for `--error-format` must be `human`, `json`, `sarif` or \
                      `short` (instead was `{arg}`)"
                 ),
             ),
@@ -1668,6 +1763,12 @@ fn check_error_format_stability(
                 "`--error-format=human-annotate-rs` is unstable",
             );
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_error(
+                ErrorOutputType::Json { pretty: false, json_rendered },
+                "`--error-format=sarif` is unstable",
+            );
+        }
     }
 }
 
@@ -1693,6 +1794,9 @@ fn parse_output_types(
                         ),
                     )
                 });
+                if output_type == OutputType::Scip && !unstable_opts.unstable_options {
+                    early_error(error_format, "`--emit=scip` is unstable");
+                }
                 output_types.insert(output_type, path);
             }
         }
@@ -2209,6 +2313,7 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         json_artifact_notifications,
         json_unused_externs,
         json_future_incompat,
+        json_dependency_usage,
     } = parse_json(matches);
 
     let error_format = parse_error_format(matches, color, json_rendered);
@@ -2223,6 +2328,9 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
 
     let mut unstable_opts = UnstableOptions::build(matches, error_format);
     let (lint_opts, describe_lints, lint_cap) = get_cmd_lint_options(matches, error_format);
+    let lint_config = matches.opt_str("lint-config").map(|path| {
+        LintConfig::load(Path::new(&path)).unwrap_or_else(|err| early_error(error_format, &err))
+    });
 
     check_error_format_stability(&unstable_opts, error_format, json_rendered);
 
@@ -2234,6 +2342,14 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         );
     }
 
+    if !unstable_opts.unstable_options && json_dependency_usage {
+        early_error(
+            error_format,
+            "the `-Z unstable-options` flag must also be passed to enable \
+            the flag `--json=dependency-usage`",
+        );
+    }
+
     let output_types = parse_output_types(&unstable_opts, matches, error_format);
 
     let mut cg = CodegenOptions::build(matches, error_format);
@@ -2250,6 +2366,16 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
 
     let assert_incr_state = parse_assert_incr_state(&unstable_opts.assert_incr_state, error_format);
 
+    if unstable_opts.explain_dep_graph.is_some()
+        && unstable_opts.explain_saved_dep_graphs.is_none()
+        && incremental.is_none()
+    {
+        early_warn(
+            error_format,
+            "-Z explain-dep-graph is ignored unless compiling incrementally with -C incremental",
+        );
+    }
+
     if unstable_opts.profile && incremental.is_some() {
         early_error(
             error_format,
@@ -2423,6 +2549,8 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
 
     let pretty = parse_pretty(&unstable_opts, error_format);
 
+    let apply_suggestions = parse_apply_suggestions(matches, error_format);
+
     if !unstable_opts.unstable_options
         && !target_triple.triple().contains("apple")
         && cg.split_debuginfo.is_some()
@@ -2478,6 +2606,7 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         lint_opts,
         lint_cap,
         describe_lints,
+        lint_config,
         output_types,
         search_paths,
         maybe_sysroot: sysroot_opt,
@@ -2504,6 +2633,8 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         json_artifact_notifications,
         json_unused_externs,
         json_future_incompat,
+        json_dependency_usage,
+        apply_suggestions,
         pretty,
         working_dir,
     }
@@ -2751,6 +2882,7 @@ pub(crate) mod dep_tracking {
         SwitchWithOptPath, SymbolManglingVersion, TrimmedDefPaths,
     };
     use crate::lint;
+    use crate::lint_config::LintConfig;
     use crate::options::WasiExecModel;
     use crate::utils::{NativeLib, NativeLibKind};
     use rustc_errors::LanguageIdentifier;
@@ -2811,6 +2943,7 @@ fn hash(
         String,
         PathBuf,
         lint::Level,
+        LintConfig,
         WasiExecModel,
         u32,
         RelocModel,
@@ -0,0 +1,150 @@
+//! Lint levels read from the file given with `--lint-config`.
+//!
+//! The file is a TOML document setting lint levels for the whole crate in
+//! its `lints` table, and for the source files under some paths in its
+//! `overrides` array of tables:
+//!
+//! ```toml
+//! [lints]
+//! unused = "deny"
+//! missing-docs = "warn"
+//! "clippy::pedantic" = "warn"
+//!
+//! [[overrides]]
+//! paths = ["src/generated"]
+//! lints = { warnings = "allow", missing-docs = "allow" }
+//! ```
+//!
+//! Levels from the `lints` table can be overridden by `-A/-W/-D/-F` flags,
+//! which can be overridden by the levels of the `overrides` matching the
+//! source file of an item, which can be overridden by lint attributes.
+
+use crate::lint::Level;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The contents of a `--lint-config` file.
+#[derive(Clone, Debug, Hash)]
+pub struct LintConfig {
+    /// The path of the config file.
+    pub path: PathBuf,
+    /// The lint levels for the whole crate.
+    pub lints: Vec<(String, Level)>,
+    /// The lint levels for the source files under some paths, in the order
+    /// of the file.
+    pub overrides: Vec<LintConfigOverride>,
+}
+
+/// Lint levels for the source files under some paths.
+#[derive(Clone, Debug, Hash)]
+pub struct LintConfigOverride {
+    /// The files and directories the levels apply to, as written in the file.
+    pub paths: Vec<String>,
+    /// The paths joined to the directory of the config file, as they are
+    /// relative to it.
+    pub resolved_paths: Vec<PathBuf>,
+    pub lints: Vec<(String, Level)>,
+}
+
+impl LintConfig {
+    /// Reads and parses the config file at `path`.
+    pub fn load(path: &Path) -> Result<LintConfig, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read `{}`: {}", path.display(), err))?;
+        LintConfig::parse(&contents, path)
+            .map_err(|err| format!("invalid lint config `{}`: {}", path.display(), err))
+    }
+
+    fn parse(contents: &str, path: &Path) -> Result<LintConfig, String> {
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let document: toml::Value = contents.parse().map_err(|err| format!("{}", err))?;
+        let toml::Value::Table(mut document) = document else {
+            return Err("expected a table".to_string());
+        };
+
+        let lints = match document.remove("lints") {
+            Some(lints) => parse_lint_levels(lints)?,
+            None => Vec::new(),
+        };
+
+        let mut overrides = Vec::new();
+        if let Some(value) = document.remove("overrides") {
+            let toml::Value::Array(values) = value else {
+                return Err("`overrides` must be an array of tables".to_string());
+            };
+            for value in values {
+                let toml::Value::Table(mut table) = value else {
+                    return Err("`overrides` must be an array of tables".to_string());
+                };
+                let paths = match table.remove("paths") {
+                    Some(toml::Value::Array(paths)) if !paths.is_empty() => paths
+                        .into_iter()
+                        .map(|path| match path {
+                            toml::Value::String(path) => Ok(path),
+                            _ => Err("`paths` of an override must be strings".to_string()),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                    _ => return Err("overrides need a non-empty `paths` array".to_string()),
+                };
+                let lints = match table.remove("lints") {
+                    Some(lints) => parse_lint_levels(lints)?,
+                    None => Vec::new(),
+                };
+                if let Some(key) = table.keys().next() {
+                    return Err(format!("unknown key `{}` in override", key));
+                }
+                let resolved_paths = paths.iter().map(|path| base_dir.join(path)).collect();
+                overrides.push(LintConfigOverride { paths, resolved_paths, lints });
+            }
+        }
+
+        if let Some(key) = document.keys().next() {
+            return Err(format!("unknown key `{}`", key));
+        }
+        Ok(LintConfig { path: path.to_path_buf(), lints, overrides })
+    }
+
+    /// Returns the index of the last override that applies to the source
+    /// file at `path`. Relative paths are relative to `working_dir`.
+    pub fn override_for(&self, path: &Path, working_dir: &Path) -> Option<usize> {
+        let path = working_dir.join(path);
+        self.overrides.iter().rposition(|config_override| {
+            config_override
+                .resolved_paths
+                .iter()
+                .any(|prefix| path.starts_with(working_dir.join(prefix)))
+        })
+    }
+}
+
+/// Parses a table of lint names to levels, such as `{ unused = "deny" }`.
+/// Lint names are normalized to use underscores, as on the command line.
+fn parse_lint_levels(value: toml::Value) -> Result<Vec<(String, Level)>, String> {
+    let toml::Value::Table(table) = value else {
+        return Err("`lints` must be a table of lint names to levels".to_string());
+    };
+    table
+        .into_iter()
+        .map(|(name, level)| {
+            let level = match level.as_str() {
+                Some("allow") => Level::Allow,
+                Some("warn") => Level::Warn,
+                Some("force-warn") => Level::ForceWarn(None),
+                Some("deny") => Level::Deny,
+                Some("forbid") => Level::Forbid,
+                _ => {
+                    return Err(format!(
+                        "the level of `{}` must be one of \"allow\", \"warn\", \
+                         \"force-warn\", \"deny\" or \"forbid\"",
+                        name
+                    ));
+                }
+            };
+            Ok((name.replace('-', "_"), level))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
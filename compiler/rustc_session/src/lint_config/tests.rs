@@ -0,0 +1,62 @@
+use super::*;
+
+#[test]
+fn parse_lints_and_overrides() {
+    let config = LintConfig::parse(
+        r#"
+        [lints]
+        unused = "deny"
+        missing-docs = "warn"
+        "clippy::pedantic" = "allow"
+
+        [[overrides]]
+        paths = ["src/generated", "src/bindings.rs"]
+        lints = { warnings = "allow" }
+        "#,
+        Path::new("crate/lints.toml"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.lints,
+        [
+            ("clippy::pedantic".to_string(), Level::Allow),
+            ("missing_docs".to_string(), Level::Warn),
+            ("unused".to_string(), Level::Deny),
+        ]
+    );
+    assert_eq!(config.overrides.len(), 1);
+    assert_eq!(config.overrides[0].paths, ["src/generated", "src/bindings.rs"]);
+    assert_eq!(config.overrides[0].lints, [("warnings".to_string(), Level::Allow)]);
+}
+
+#[test]
+fn parse_errors() {
+    let parse = |contents| LintConfig::parse(contents, Path::new("lints.toml")).unwrap_err();
+    assert!(parse("[lints]\nunused = \"loud\"").contains("must be one of"));
+    assert!(parse("lints = [\"unused\"]").contains("table of lint names"));
+    assert!(parse("[[overrides]]\nlints = {}").contains("`paths`"));
+    assert!(parse("[lint]\nunused = \"deny\"").contains("unknown key `lint`"));
+}
+
+#[test]
+fn override_for() {
+    let config = LintConfig::parse(
+        r#"
+        [[overrides]]
+        paths = ["src/generated"]
+        [[overrides]]
+        paths = ["src/generated/special.rs"]
+        "#,
+        Path::new("crate/lints.toml"),
+    )
+    .unwrap();
+
+    let working_dir = Path::new("/work");
+    let override_for = |path| config.override_for(Path::new(path), working_dir);
+    assert_eq!(override_for("crate/src/lib.rs"), None);
+    assert_eq!(override_for("crate/src/generated/mod.rs"), Some(0));
+    assert_eq!(override_for("/work/crate/src/generated/mod.rs"), Some(0));
+    assert_eq!(override_for("crate/src/generated/special.rs"), Some(1));
+    assert_eq!(override_for("crate/src/generated_by_hand.rs"), None);
+}
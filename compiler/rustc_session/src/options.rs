@@ -2,8 +2,10 @@
 
 use crate::early_error;
 use crate::lint;
+use crate::lint_config::LintConfig;
 use crate::search_paths::SearchPath;
 use crate::utils::NativeLib;
+use rustc_data_structures::profiling::SelfProfileSummaryFormat;
 use rustc_errors::LanguageIdentifier;
 use rustc_target::spec::{CodeModel, LinkerFlavor, MergeFunctions, PanicStrategy, SanitizerSet};
 use rustc_target::spec::{
@@ -140,6 +142,8 @@ pub struct Options {
         debuginfo: DebugInfo [TRACKED],
         lint_opts: Vec<(String, lint::Level)> [TRACKED_NO_CRATE_HASH],
         lint_cap: Option<lint::Level> [TRACKED_NO_CRATE_HASH],
+        /// Lint levels read from the file given with `--lint-config`.
+        lint_config: Option<LintConfig> [TRACKED_NO_CRATE_HASH],
         describe_lints: bool [UNTRACKED],
         output_types: OutputTypes [TRACKED],
         search_paths: Vec<SearchPath> [UNTRACKED],
@@ -206,6 +210,14 @@ pub struct Options {
         /// `true` if we're emitting a JSON job containing a future-incompat report for lints
         json_future_incompat: bool [TRACKED],
 
+        /// `true` if we're emitting a JSON blob describing how each `--extern`
+        /// dependency is used
+        json_dependency_usage: bool [UNTRACKED],
+
+        /// If `Some`, apply the machine-applicable suggestions of the emitted
+        /// diagnostics to the source files.
+        apply_suggestions: Option<ApplySuggestions> [UNTRACKED],
+
         pretty: Option<PpMode> [UNTRACKED],
 
         /// The (potentially remapped) working directory
@@ -385,6 +397,11 @@ mod desc {
     pub const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavor::one_of();
     pub const parse_optimization_fuel: &str = "crate=integer";
     pub const parse_mir_spanview: &str = "`statement` (default), `terminator`, or `block`";
+    pub const parse_dep_graph_dump_format: &str = "`dot` (default) or `json`";
+    pub const parse_session_dir_pair: &str = "two comma-separated paths";
+    pub const parse_mir_dump_format: &str = "`text` (default) or `json`";
+    pub const parse_nll_facts_format: &str = "`tsv` (default) or `json`";
+    pub const parse_self_profile_summary: &str = "either no value, `text` or `json`";
     pub const parse_instrument_coverage: &str =
         "`all` (default), `except-unused-generics`, `except-unused-functions`, or `off`";
     pub const parse_unpretty: &str = "`string` or `string=string`";
@@ -827,6 +844,61 @@ pub(crate) fn parse_mir_spanview(slot: &mut Option<MirSpanview>, v: Option<&str>
         true
     }
 
+    pub(crate) fn parse_dep_graph_dump_format(
+        slot: &mut DepGraphDumpFormat,
+        v: Option<&str>,
+    ) -> bool {
+        *slot = match v {
+            Some("dot") => DepGraphDumpFormat::Dot,
+            Some("json") => DepGraphDumpFormat::Json,
+            _ => return false,
+        };
+        true
+    }
+
+    pub(crate) fn parse_session_dir_pair(
+        slot: &mut Option<(PathBuf, PathBuf)>,
+        v: Option<&str>,
+    ) -> bool {
+        match v.and_then(|s| s.split_once(',')) {
+            Some((previous, current)) => {
+                *slot = Some((PathBuf::from(previous), PathBuf::from(current)));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn parse_mir_dump_format(slot: &mut MirDumpFormat, v: Option<&str>) -> bool {
+        *slot = match v {
+            Some("text") => MirDumpFormat::Text,
+            Some("json") => MirDumpFormat::Json,
+            _ => return false,
+        };
+        true
+    }
+
+    pub(crate) fn parse_nll_facts_format(slot: &mut NllFactsFormat, v: Option<&str>) -> bool {
+        *slot = match v {
+            Some("tsv") => NllFactsFormat::Tsv,
+            Some("json") => NllFactsFormat::Json,
+            _ => return false,
+        };
+        true
+    }
+
+    pub(crate) fn parse_self_profile_summary(
+        slot: &mut Option<SelfProfileSummaryFormat>,
+        v: Option<&str>,
+    ) -> bool {
+        *slot = match v {
+            None | Some("text") => Some(SelfProfileSummaryFormat::Text),
+            Some("json") => Some(SelfProfileSummaryFormat::Json),
+            _ => return false,
+        };
+        true
+    }
+
     pub(crate) fn parse_instrument_coverage(
         slot: &mut Option<InstrumentCoverage>,
         v: Option<&str>,
@@ -1250,6 +1322,10 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "the backend to use"),
     combine_cgu: bool = (false, parse_bool, [TRACKED],
         "combine CGUs into a single one"),
+    // Tracked so that enabling it invalidates the cached results of the evaluations to trace.
+    const_eval_trace: bool = (false, parse_bool, [TRACKED],
+        "record the steps, allocated memory and call stacks of the evaluation of each constant, \
+        and report the most expensive ones (default: no)"),
     crate_attr: Vec<String> = (Vec::new(), parse_string_push, [TRACKED],
         "inject the given attribute in the crate"),
     debug_info_for_profiling: bool = (false, parse_bool, [TRACKED],
@@ -1264,6 +1340,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
     dep_tasks: bool = (false, parse_bool, [UNTRACKED],
         "print tasks that execute and the color their dep node gets (requires debug build) \
         (default: no)"),
+    describe_lints: bool = (false, parse_bool, [UNTRACKED],
+        "print the effective level of every lint at the crate root and in the files matched by \
+        `--lint-config` overrides, and where it was set (default: no)"),
     dlltool: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
         "import library generation tool (windows-gnu only)"),
     dont_buffer_diagnostics: bool = (false, parse_bool, [UNTRACKED],
@@ -1276,6 +1355,11 @@ pub(crate) fn parse_proc_macro_execution_strategy(
     dump_dep_graph: bool = (false, parse_bool, [UNTRACKED],
         "dump the dependency graph to $RUST_DEP_GRAPH (default: /tmp/dep_graph.gv) \
         (default: no)"),
+    dump_dep_graph_format: DepGraphDumpFormat = (DepGraphDumpFormat::Dot,
+        parse_dep_graph_dump_format, [UNTRACKED],
+        "the format `-Z dump-dep-graph` writes: `dot` writes the edges between dep kinds to \
+        `.txt` and `.dot` files, `json` writes every dep node with its color to a `.json` file \
+        (default: `dot`)"),
     dump_drop_tracking_cfg: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "dump drop-tracking control-flow graph as a `.dot` file (default: no)"),
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
@@ -1292,6 +1376,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "the directory the MIR is dumped into (default: `mir_dump`)"),
     dump_mir_exclude_pass_number: bool = (false, parse_bool, [UNTRACKED],
         "exclude the pass number when dumping MIR (used in tests) (default: no)"),
+    dump_mir_format: MirDumpFormat = (MirDumpFormat::Text, parse_mir_dump_format, [UNTRACKED],
+        "the format MIR is dumped in: `text` writes pretty-printed `.mir` files, \
+        `json` writes `.json` files (default: `text`)"),
     dump_mir_graphviz: bool = (false, parse_bool, [UNTRACKED],
         "in addition to `.mir` files, create graphviz `.dot` files (and with \
         `-Z instrument-coverage`, also create a `.dot` file for the MIR-derived \
@@ -1308,6 +1395,18 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "emit a section containing stack size metadata (default: no)"),
     emit_thin_lto: bool = (true, parse_bool, [TRACKED],
         "emit the bc module with thin LTO info (default: yes)"),
+    explain_borrowck: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "write the region constraint graph of the bodies of the given function (e.g. \
+        `my_mod::my_fn`) to `-Z dump-mir-dir` as graphviz, highlighting the constraints blamed \
+        for its borrowck errors (default: no)"),
+    explain_dep_graph: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "explain why the queries of the previous incremental session matching the given \
+        dep-node filter (e.g. `typeck & my_fn`) were re-executed (default: no)"),
+    explain_saved_dep_graphs: Option<(PathBuf, PathBuf)> = (None, parse_session_dir_pair,
+        [UNTRACKED],
+        "explain with the `-Z explain-dep-graph` filter, without compiling, why queries were \
+        re-executed from the dependency graphs saved in two incremental session directories \
+        (`previous,current`) (default: no)"),
     export_executable_symbols: bool = (false, parse_bool, [TRACKED],
         "export symbols from executables, as if they were dynamic libraries"),
     extra_const_ub_checks: bool = (false, parse_bool, [TRACKED],
@@ -1413,6 +1512,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "dump facts from NLL analysis into side files (default: no)"),
     nll_facts_dir: String = ("nll-facts".to_string(), parse_string, [UNTRACKED],
         "the directory the NLL facts are dumped into (default: `nll-facts`)"),
+    nll_facts_format: NllFactsFormat = (NllFactsFormat::Tsv, parse_nll_facts_format, [UNTRACKED],
+        "the format NLL facts are dumped in: `tsv` writes a `.facts` file per relation, \
+        `json` writes a `facts.json` file with source spans and results (default: `tsv`)"),
     no_analysis: bool = (false, parse_no_flag, [UNTRACKED],
         "parse and expand the source, but run no analysis"),
     no_codegen: bool = (false, parse_no_flag, [TRACKED_NO_CRATE_HASH],
@@ -1470,6 +1572,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
     #[rustc_lint_opt_deny_field_access("use `Session::print_llvm_passes` instead of this field")]
     print_llvm_passes: bool = (false, parse_bool, [UNTRACKED],
         "print the LLVM optimization passes being run (default: no)"),
+    print_mono_item_stats: bool = (false, parse_bool, [UNTRACKED],
+        "print the number and estimated MIR size of the mono items of each definition, \
+        along with the crates whose code required them (default: no)"),
     print_mono_items: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "print the result of the monomorphization collection pass"),
     print_type_sizes: bool = (false, parse_bool, [UNTRACKED],
@@ -1534,6 +1639,11 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         `instructions:u` (retired instructions, userspace-only)
         `instructions-minus-irqs:u` (subtracting hardware interrupt counts for extra accuracy)"
     ),
+    self_profile_summary: Option<SelfProfileSummaryFormat> = (None,
+        parse_self_profile_summary, [UNTRACKED],
+        "run the self profiler and summarize the time spent in queries at the end of the \
+        session: `text` prints a table to stderr, `json` writes a `.summary.json` file next to \
+        the raw event data (default: `text`); implies `-Z self-profile`"),
     share_generics: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "make the current crate share its generic instantiations"),
     show_span: Option<String> = (None, parse_opt_string, [TRACKED],
@@ -1,5 +1,6 @@
 use crate::cgu_reuse_tracker::CguReuseTracker;
 use crate::code_stats::CodeStats;
+pub use crate::code_stats::{ConstEvalFnStats, ConstEvalTrace};
 pub use crate::code_stats::{DataTypeKind, FieldInfo, SizeKind, VariantInfo};
 use crate::config::{self, CrateType, InstrumentCoverage, OptLevel, OutputType, SwitchWithOptPath};
 use crate::parse::{add_feature_diagnostics, ParseSess};
@@ -16,17 +17,19 @@
     self, AtomicU64, AtomicUsize, Lock, Lrc, OnceCell, OneThread, Ordering, Ordering::SeqCst,
 };
 use rustc_errors::annotate_snippet_emitter_writer::AnnotateSnippetEmitterWriter;
+use rustc_errors::apply_suggestions::resolve_suggestions;
 use rustc_errors::emitter::{Emitter, EmitterWriter, HumanReadableErrorType};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_errors::{
-    fallback_fluent_bundle, DiagnosticBuilder, DiagnosticId, DiagnosticMessage, EmissionGuarantee,
-    ErrorGuaranteed, FluentBundle, LazyFallbackBundle, MultiSpan,
+    fallback_fluent_bundle, pluralize, DiagnosticBuilder, DiagnosticId, DiagnosticMessage,
+    EmissionGuarantee, ErrorGuaranteed, FluentBundle, HandlerFlags, LazyFallbackBundle, MultiSpan,
 };
 use rustc_macros::HashStable_Generic;
 pub use rustc_span::def_id::StableCrateId;
 use rustc_span::edition::Edition;
-use rustc_span::source_map::{FileLoader, RealFileLoader, SourceMap, Span};
+use rustc_span::source_map::{FileLoader, FilePathMapping, RealFileLoader, SourceMap, Span};
 use rustc_span::{sym, SourceFileHashAlgorithm, Symbol};
 use rustc_target::asm::InlineAsmArch;
 use rustc_target::spec::{CodeModel, PanicStrategy, RelocModel, RelroLevel};
@@ -255,10 +258,72 @@ fn check_miri_unleashed_features(&self) {
     /// Invoked all the way at the end to finish off diagnostics printing.
     pub fn finish_diagnostics(&self, registry: &Registry) {
         self.check_miri_unleashed_features();
+        self.apply_suggestions();
         self.diagnostic().print_error_count(registry);
         self.emit_future_breakage();
     }
 
+    /// Applies the machine-applicable suggestions of the emitted diagnostics
+    /// to the source files for `--apply-suggestions`, and reports what was done.
+    fn apply_suggestions(&self) {
+        let Some(apply) = self.opts.apply_suggestions else {
+            return;
+        };
+
+        let suggestions = self.diagnostic().take_machine_applicable_suggestions();
+        let fixes = resolve_suggestions(self.source_map(), &suggestions);
+        let mut fixed_files = Vec::new();
+        for file in &fixes.files {
+            if apply.dry_run {
+                print!("{}", file.unified_diff());
+            } else if let Err(err) = std::fs::write(&file.path, file.fixed_source()) {
+                self.err(&format!(
+                    "failed to apply suggestions to `{}`: {}",
+                    file.path.display(),
+                    err
+                ));
+                continue;
+            }
+            fixed_files.push(file);
+        }
+
+        let applied: usize = fixed_files.iter().map(|file| file.applied).sum();
+        let mut diag = self.struct_note_without_error(&format!(
+            "{} {} machine-applicable suggestion{} to {} file{}",
+            if apply.dry_run { "would apply" } else { "applied" },
+            applied,
+            pluralize!(applied),
+            fixed_files.len(),
+            pluralize!(fixed_files.len()),
+        ));
+        for file in fixed_files {
+            diag.note(&format!(
+                "{}: {} suggestion{}",
+                file.path.display(),
+                file.applied,
+                pluralize!(file.applied)
+            ));
+        }
+        if fixes.overlapping > 0 {
+            diag.note(&format!(
+                "skipped {} suggestion{} overlapping with other suggestions, \
+                 compile again to apply {}",
+                fixes.overlapping,
+                pluralize!(fixes.overlapping),
+                if fixes.overlapping == 1 { "it" } else { "them" },
+            ));
+        }
+        if fixes.unapplicable > 0 {
+            diag.note(&format!(
+                "skipped {} suggestion{} for files that aren't local source files \
+                 or changed during compilation",
+                fixes.unapplicable,
+                pluralize!(fixes.unapplicable),
+            ));
+        }
+        diag.emit();
+    }
+
     fn emit_future_breakage(&self) {
         if !self.opts.json_future_incompat {
             return;
@@ -1251,6 +1316,21 @@ fn default_emitter(
             )
             .ui_testing(sopts.unstable_opts.ui_testing),
         ),
+        (config::ErrorOutputType::Sarif, None) => Box::new(SarifEmitter::stderr(
+            Some(registry),
+            source_map,
+            bundle,
+            fallback_bundle,
+            false,
+        )),
+        (config::ErrorOutputType::Sarif, Some(dst)) => Box::new(SarifEmitter::new(
+            dst,
+            Some(registry),
+            source_map,
+            bundle,
+            fallback_bundle,
+            false,
+        )),
     }
 }
 
@@ -1324,21 +1404,29 @@ pub fn build_session(
     let emitter =
         default_emitter(&sopts, registry, source_map.clone(), bundle, fallback_bundle, write_dest);
 
-    let span_diagnostic = rustc_errors::Handler::with_emitter_and_flags(
-        emitter,
-        sopts.unstable_opts.diagnostic_handler_flags(can_emit_warnings),
-    );
+    let handler_flags = HandlerFlags {
+        collect_suggestions: sopts.apply_suggestions.is_some(),
+        ..sopts.unstable_opts.diagnostic_handler_flags(can_emit_warnings)
+    };
+    let span_diagnostic = rustc_errors::Handler::with_emitter_and_flags(emitter, handler_flags);
 
-    let self_profiler = if let SwitchWithOptPath::Enabled(ref d) = sopts.unstable_opts.self_profile
-    {
-        let directory =
-            if let Some(ref directory) = d { directory } else { std::path::Path::new(".") };
+    // `-Z self-profile-summary` implies `-Z self-profile`.
+    let self_profile = match sopts.unstable_opts.self_profile {
+        SwitchWithOptPath::Enabled(ref d) => Some(d.as_deref()),
+        SwitchWithOptPath::Disabled if sopts.unstable_opts.self_profile_summary.is_some() => {
+            Some(None)
+        }
+        SwitchWithOptPath::Disabled => None,
+    };
+    let self_profiler = if let Some(d) = self_profile {
+        let directory = d.unwrap_or(std::path::Path::new("."));
 
         let profiler = SelfProfiler::new(
             directory,
             sopts.crate_name.as_deref(),
             sopts.unstable_opts.self_profile_events.as_ref().map(|xs| &xs[..]),
             &sopts.unstable_opts.self_profile_counter,
+            sopts.unstable_opts.self_profile_summary,
         );
         match profiler {
             Ok(profiler) => Some(Arc::new(profiler)),
@@ -1353,6 +1441,11 @@ pub fn build_session(
 
     let mut parse_sess = ParseSess::with_span_handler(span_diagnostic, source_map);
     parse_sess.assume_incomplete_release = sopts.unstable_opts.assume_incomplete_release;
+    if let Some(lint_config) = &sopts.lint_config {
+        // Lint levels depend on the config file, so it's an input of the crate.
+        let path = Symbol::intern(&lint_config.path.to_string_lossy());
+        parse_sess.file_depinfo.get_mut().insert(path);
+    }
 
     let host_triple = config::host_triple();
     let target_triple = sopts.target_triple.triple();
@@ -1582,6 +1675,13 @@ fn early_error_handler(output: config::ErrorOutputType) -> rustc_errors::Handler
         config::ErrorOutputType::Json { pretty, json_rendered } => {
             Box::new(JsonEmitter::basic(pretty, json_rendered, None, fallback_bundle, None, false))
         }
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::stderr(
+            None,
+            Lrc::new(SourceMap::new(FilePathMapping::empty())),
+            None,
+            fallback_bundle,
+            false,
+        )),
     };
     rustc_errors::Handler::with_emitter(true, None, emitter)
 }
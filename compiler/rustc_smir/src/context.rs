@@ -0,0 +1,499 @@
+//! Conversion from the compiler's internal data structures to the stable ones.
+
+use std::collections::HashMap;
+
+use crate::mir;
+use crate::ty::{DefId, FloatTy, GenericArg, IntTy, Movability, Mutability, Span, Ty, UintTy};
+use crate::very_unstable::hir;
+use crate::very_unstable::middle::mir as rustc_mir;
+use crate::very_unstable::middle::ty::{self as rustc_ty, adjustment::PointerCast, TyCtxt};
+use crate::very_unstable::span;
+
+/// An item of the local crate that has a MIR body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrateItem {
+    pub def_id: DefId,
+    pub name: String,
+    pub span: Option<Span>,
+}
+
+/// Converts the compiler's data structures of one compilation session to their stable
+/// counterparts.
+///
+/// The [`DefId`]s handed out by a `Context` are only meaningful to that `Context`.
+pub struct Context<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    def_ids: Vec<hir::def_id::DefId>,
+    def_id_indices: HashMap<hir::def_id::DefId, usize>,
+}
+
+impl<'tcx> Context<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Context { tcx, def_ids: Vec::new(), def_id_indices: HashMap::new() }
+    }
+
+    pub fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    /// Returns all items of the local crate that have a MIR body, including
+    /// closures and constants.
+    pub fn crate_items(&mut self) -> Vec<CrateItem> {
+        self.tcx
+            .mir_keys(())
+            .iter()
+            .map(|&local_def_id| {
+                let def_id = local_def_id.to_def_id();
+                CrateItem {
+                    def_id: self.def_id(def_id),
+                    name: self.tcx.def_path_str(def_id),
+                    span: self.span(self.tcx.def_span(def_id)),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the path of the item, e.g. `std::vec::Vec`.
+    pub fn def_path(&self, def_id: DefId) -> String {
+        self.tcx.def_path_str(self.internal_def_id(def_id))
+    }
+
+    /// Returns the `DefId` the compiler uses for `def_id`.
+    pub fn internal_def_id(&self, def_id: DefId) -> hir::def_id::DefId {
+        self.def_ids[def_id.0]
+    }
+
+    /// Returns the MIR body of the item, if it has one. Functions get their optimized
+    /// MIR, constants and statics the MIR used for const evaluation.
+    pub fn mir_body(&mut self, def_id: DefId) -> Option<mir::Body> {
+        let def_id = self.internal_def_id(def_id);
+        if !self.tcx.is_mir_available(def_id) {
+            return None;
+        }
+        let body = self.tcx.instance_mir(rustc_ty::InstanceDef::Item(
+            rustc_ty::WithOptConstParam::unknown(def_id),
+        ));
+        Some(self.body(body))
+    }
+
+    fn def_id(&mut self, def_id: hir::def_id::DefId) -> DefId {
+        let def_ids = &mut self.def_ids;
+        let index = *self.def_id_indices.entry(def_id).or_insert_with(|| {
+            def_ids.push(def_id);
+            def_ids.len() - 1
+        });
+        DefId(index)
+    }
+
+    fn span(&self, span: span::Span) -> Option<Span> {
+        if span.is_dummy() {
+            return None;
+        }
+        let source_map = self.tcx.sess.source_map();
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        Some(Span {
+            file: lo.file.name.prefer_remapped().to_string(),
+            start_line: lo.line,
+            start_col: lo.col.0 + 1,
+            end_line: hi.line,
+            end_col: hi.col.0 + 1,
+        })
+    }
+
+    fn body(&mut self, body: &rustc_mir::Body<'tcx>) -> mir::Body {
+        mir::Body {
+            blocks: body
+                .basic_blocks()
+                .iter()
+                .map(|block| mir::BasicBlock {
+                    statements: block
+                        .statements
+                        .iter()
+                        .map(|statement| mir::Statement {
+                            kind: self.statement_kind(&statement.kind),
+                            span: self.span(statement.source_info.span),
+                        })
+                        .collect(),
+                    terminator: mir::Terminator {
+                        kind: self.terminator_kind(&block.terminator().kind),
+                        span: self.span(block.terminator().source_info.span),
+                    },
+                    is_cleanup: block.is_cleanup,
+                })
+                .collect(),
+            locals: body
+                .local_decls
+                .iter()
+                .map(|decl| mir::LocalDecl {
+                    ty: self.ty(decl.ty),
+                    mutability: mutability(decl.mutability),
+                    span: self.span(decl.source_info.span),
+                })
+                .collect(),
+            arg_count: body.arg_count,
+            span: self.span(body.span),
+        }
+    }
+
+    fn statement_kind(&mut self, kind: &rustc_mir::StatementKind<'tcx>) -> mir::StatementKind {
+        use rustc_mir::StatementKind::*;
+        match kind {
+            Assign(box (place, rvalue)) => {
+                mir::StatementKind::Assign(self.place(place), self.rvalue(rvalue))
+            }
+            FakeRead(box (_, place)) => mir::StatementKind::FakeRead(self.place(place)),
+            SetDiscriminant { place, variant_index } => mir::StatementKind::SetDiscriminant {
+                place: self.place(place),
+                variant_index: variant_index.as_usize(),
+            },
+            Deinit(place) => mir::StatementKind::Deinit(self.place(place)),
+            StorageLive(local) => mir::StatementKind::StorageLive(local.as_usize()),
+            StorageDead(local) => mir::StatementKind::StorageDead(local.as_usize()),
+            Retag(_, place) => mir::StatementKind::Retag(self.place(place)),
+            AscribeUserType(box (place, _), _) => {
+                mir::StatementKind::AscribeUserType(self.place(place))
+            }
+            Coverage(_) => mir::StatementKind::Coverage,
+            CopyNonOverlapping(copy) => mir::StatementKind::CopyNonOverlapping {
+                src: self.operand(&copy.src),
+                dst: self.operand(&copy.dst),
+                count: self.operand(&copy.count),
+            },
+            Nop => mir::StatementKind::Nop,
+        }
+    }
+
+    fn terminator_kind(&mut self, kind: &rustc_mir::TerminatorKind<'tcx>) -> mir::TerminatorKind {
+        use rustc_mir::TerminatorKind::*;
+        match kind {
+            Goto { target } => mir::TerminatorKind::Goto { target: target.as_usize() },
+            SwitchInt { discr, switch_ty, targets } => mir::TerminatorKind::SwitchInt {
+                discr: self.operand(discr),
+                switch_ty: self.ty(*switch_ty),
+                targets: targets.iter().map(|(value, target)| (value, target.as_usize())).collect(),
+                otherwise: targets.otherwise().as_usize(),
+            },
+            Resume => mir::TerminatorKind::Resume,
+            Abort => mir::TerminatorKind::Abort,
+            Return => mir::TerminatorKind::Return,
+            Unreachable => mir::TerminatorKind::Unreachable,
+            Drop { place, target, unwind } => mir::TerminatorKind::Drop {
+                place: self.place(place),
+                target: target.as_usize(),
+                unwind: unwind.map(|bb| bb.as_usize()),
+            },
+            DropAndReplace { place, value, target, unwind } => {
+                mir::TerminatorKind::DropAndReplace {
+                    place: self.place(place),
+                    value: self.operand(value),
+                    target: target.as_usize(),
+                    unwind: unwind.map(|bb| bb.as_usize()),
+                }
+            }
+            Call { func, args, destination, target, cleanup, from_hir_call: _, fn_span: _ } => {
+                mir::TerminatorKind::Call {
+                    func: self.operand(func),
+                    args: args.iter().map(|arg| self.operand(arg)).collect(),
+                    destination: self.place(destination),
+                    target: target.map(|bb| bb.as_usize()),
+                    cleanup: cleanup.map(|bb| bb.as_usize()),
+                }
+            }
+            Assert { cond, expected, msg, target, cleanup } => mir::TerminatorKind::Assert {
+                cond: self.operand(cond),
+                expected: *expected,
+                msg: self.assert_message(msg),
+                target: target.as_usize(),
+                cleanup: cleanup.map(|bb| bb.as_usize()),
+            },
+            Yield { value, resume, resume_arg, drop } => mir::TerminatorKind::Yield {
+                value: self.operand(value),
+                resume: resume.as_usize(),
+                resume_arg: self.place(resume_arg),
+                drop: drop.map(|bb| bb.as_usize()),
+            },
+            GeneratorDrop => mir::TerminatorKind::GeneratorDrop,
+            FalseEdge { real_target, imaginary_target } => mir::TerminatorKind::FalseEdge {
+                real_target: real_target.as_usize(),
+                imaginary_target: imaginary_target.as_usize(),
+            },
+            FalseUnwind { real_target, unwind } => mir::TerminatorKind::FalseUnwind {
+                real_target: real_target.as_usize(),
+                unwind: unwind.map(|bb| bb.as_usize()),
+            },
+            InlineAsm { destination, cleanup, .. } => mir::TerminatorKind::InlineAsm {
+                asm: format!("{:?}", kind),
+                destination: destination.map(|bb| bb.as_usize()),
+                cleanup: cleanup.map(|bb| bb.as_usize()),
+            },
+        }
+    }
+
+    fn assert_message(&mut self, msg: &rustc_mir::AssertMessage<'tcx>) -> mir::AssertMessage {
+        use rustc_mir::AssertKind::*;
+        match msg {
+            BoundsCheck { len, index } => mir::AssertMessage::BoundsCheck {
+                len: self.operand(len),
+                index: self.operand(index),
+            },
+            Overflow(op, l, r) => {
+                mir::AssertMessage::Overflow(bin_op(*op), self.operand(l), self.operand(r))
+            }
+            OverflowNeg(op) => mir::AssertMessage::OverflowNeg(self.operand(op)),
+            DivisionByZero(op) => mir::AssertMessage::DivisionByZero(self.operand(op)),
+            RemainderByZero(op) => mir::AssertMessage::RemainderByZero(self.operand(op)),
+            ResumedAfterReturn(_) => mir::AssertMessage::ResumedAfterReturn,
+            ResumedAfterPanic(_) => mir::AssertMessage::ResumedAfterPanic,
+        }
+    }
+
+    fn place(&mut self, place: &rustc_mir::Place<'tcx>) -> mir::Place {
+        mir::Place {
+            local: place.local.as_usize(),
+            projection: place.projection.iter().map(|elem| self.projection_elem(elem)).collect(),
+        }
+    }
+
+    fn projection_elem(&mut self, elem: rustc_mir::PlaceElem<'tcx>) -> mir::ProjectionElem {
+        use rustc_mir::ProjectionElem::*;
+        match elem {
+            Deref => mir::ProjectionElem::Deref,
+            Field(field, ty) => mir::ProjectionElem::Field(field.as_usize(), self.ty(ty)),
+            Index(local) => mir::ProjectionElem::Index(local.as_usize()),
+            ConstantIndex { offset, min_length, from_end } => {
+                mir::ProjectionElem::ConstantIndex { offset, min_length, from_end }
+            }
+            Subslice { from, to, from_end } => mir::ProjectionElem::Subslice { from, to, from_end },
+            Downcast(_, variant_index) => mir::ProjectionElem::Downcast(variant_index.as_usize()),
+        }
+    }
+
+    fn operand(&mut self, operand: &rustc_mir::Operand<'tcx>) -> mir::Operand {
+        use rustc_mir::Operand::*;
+        match operand {
+            Copy(place) => mir::Operand::Copy(self.place(place)),
+            Move(place) => mir::Operand::Move(self.place(place)),
+            Constant(constant) => mir::Operand::Constant(mir::Constant {
+                ty: self.ty(constant.ty()),
+                literal: constant.literal.to_string(),
+                span: self.span(constant.span),
+            }),
+        }
+    }
+
+    fn rvalue(&mut self, rvalue: &rustc_mir::Rvalue<'tcx>) -> mir::Rvalue {
+        use rustc_mir::Rvalue::*;
+        match rvalue {
+            Use(op) => mir::Rvalue::Use(self.operand(op)),
+            Repeat(op, count) => mir::Rvalue::Repeat(self.operand(op), count.to_string()),
+            Ref(_, kind, place) => mir::Rvalue::Ref(borrow_kind(*kind), self.place(place)),
+            ThreadLocalRef(def_id) => mir::Rvalue::ThreadLocalRef(self.def_id(*def_id)),
+            AddressOf(mutbl, place) => {
+                mir::Rvalue::AddressOf(mutability(*mutbl), self.place(place))
+            }
+            Len(place) => mir::Rvalue::Len(self.place(place)),
+            Cast(kind, op, ty) => {
+                mir::Rvalue::Cast(cast_kind(*kind), self.operand(op), self.ty(*ty))
+            }
+            BinaryOp(op, box (l, r)) => {
+                mir::Rvalue::BinaryOp(bin_op(*op), self.operand(l), self.operand(r))
+            }
+            CheckedBinaryOp(op, box (l, r)) => {
+                mir::Rvalue::CheckedBinaryOp(bin_op(*op), self.operand(l), self.operand(r))
+            }
+            NullaryOp(op, ty) => {
+                let op = match op {
+                    rustc_mir::NullOp::SizeOf => mir::NullOp::SizeOf,
+                    rustc_mir::NullOp::AlignOf => mir::NullOp::AlignOf,
+                };
+                mir::Rvalue::NullaryOp(op, self.ty(*ty))
+            }
+            UnaryOp(op, operand) => {
+                let op = match op {
+                    rustc_mir::UnOp::Not => mir::UnOp::Not,
+                    rustc_mir::UnOp::Neg => mir::UnOp::Neg,
+                };
+                mir::Rvalue::UnaryOp(op, self.operand(operand))
+            }
+            Discriminant(place) => mir::Rvalue::Discriminant(self.place(place)),
+            Aggregate(box kind, operands) => {
+                let kind = self.aggregate_kind(kind);
+                mir::Rvalue::Aggregate(kind, operands.iter().map(|op| self.operand(op)).collect())
+            }
+            ShallowInitBox(op, ty) => mir::Rvalue::ShallowInitBox(self.operand(op), self.ty(*ty)),
+            CopyForDeref(place) => mir::Rvalue::CopyForDeref(self.place(place)),
+        }
+    }
+
+    fn aggregate_kind(&mut self, kind: &rustc_mir::AggregateKind<'tcx>) -> mir::AggregateKind {
+        use rustc_mir::AggregateKind::*;
+        match *kind {
+            Array(ty) => mir::AggregateKind::Array(self.ty(ty)),
+            Tuple => mir::AggregateKind::Tuple,
+            Adt(def_id, variant_index, substs, _, active_field) => mir::AggregateKind::Adt(
+                self.def_id(def_id),
+                variant_index.as_usize(),
+                self.generic_args(substs),
+                active_field,
+            ),
+            Closure(def_id, substs) => mir::AggregateKind::Closure(
+                self.def_id(def_id.to_def_id()),
+                self.generic_args(substs),
+            ),
+            Generator(def_id, substs, movability) => mir::AggregateKind::Generator(
+                self.def_id(def_id.to_def_id()),
+                self.generic_args(substs),
+                self.movability(movability),
+            ),
+        }
+    }
+
+    fn movability(&self, movability: hir::Movability) -> Movability {
+        match movability {
+            hir::Movability::Static => Movability::Static,
+            hir::Movability::Movable => Movability::Movable,
+        }
+    }
+
+    fn generic_args(&mut self, substs: rustc_ty::SubstsRef<'tcx>) -> Vec<GenericArg> {
+        substs
+            .iter()
+            .map(|arg| match arg.unpack() {
+                rustc_ty::subst::GenericArgKind::Lifetime(_) => GenericArg::Lifetime,
+                rustc_ty::subst::GenericArgKind::Type(ty) => GenericArg::Type(self.ty(ty)),
+                rustc_ty::subst::GenericArgKind::Const(ct) => GenericArg::Const(ct.to_string()),
+            })
+            .collect()
+    }
+
+    fn ty(&mut self, ty: rustc_ty::Ty<'tcx>) -> Ty {
+        match *ty.kind() {
+            rustc_ty::Bool => Ty::Bool,
+            rustc_ty::Char => Ty::Char,
+            rustc_ty::Int(int_ty) => Ty::Int(match int_ty {
+                rustc_ty::IntTy::Isize => IntTy::Isize,
+                rustc_ty::IntTy::I8 => IntTy::I8,
+                rustc_ty::IntTy::I16 => IntTy::I16,
+                rustc_ty::IntTy::I32 => IntTy::I32,
+                rustc_ty::IntTy::I64 => IntTy::I64,
+                rustc_ty::IntTy::I128 => IntTy::I128,
+            }),
+            rustc_ty::Uint(uint_ty) => Ty::Uint(match uint_ty {
+                rustc_ty::UintTy::Usize => UintTy::Usize,
+                rustc_ty::UintTy::U8 => UintTy::U8,
+                rustc_ty::UintTy::U16 => UintTy::U16,
+                rustc_ty::UintTy::U32 => UintTy::U32,
+                rustc_ty::UintTy::U64 => UintTy::U64,
+                rustc_ty::UintTy::U128 => UintTy::U128,
+            }),
+            rustc_ty::Float(float_ty) => Ty::Float(match float_ty {
+                rustc_ty::FloatTy::F32 => FloatTy::F32,
+                rustc_ty::FloatTy::F64 => FloatTy::F64,
+            }),
+            rustc_ty::Str => Ty::Str,
+            rustc_ty::Never => Ty::Never,
+            rustc_ty::Adt(adt_def, substs) => {
+                Ty::Adt(self.def_id(adt_def.did()), self.generic_args(substs))
+            }
+            rustc_ty::Foreign(def_id) => Ty::Foreign(self.def_id(def_id)),
+            rustc_ty::Array(elem_ty, len) => Ty::Array(
+                Box::new(self.ty(elem_ty)),
+                len.try_eval_usize(self.tcx, rustc_ty::ParamEnv::reveal_all()),
+            ),
+            rustc_ty::Slice(elem_ty) => Ty::Slice(Box::new(self.ty(elem_ty))),
+            rustc_ty::RawPtr(rustc_ty::TypeAndMut { ty, mutbl }) => {
+                Ty::RawPtr(mutability(mutbl), Box::new(self.ty(ty)))
+            }
+            rustc_ty::Ref(_, ty, mutbl) => Ty::Ref(mutability(mutbl), Box::new(self.ty(ty))),
+            rustc_ty::FnDef(def_id, substs) => {
+                Ty::FnDef(self.def_id(def_id), self.generic_args(substs))
+            }
+            rustc_ty::FnPtr(sig) => {
+                let sig = sig.skip_binder();
+                Ty::FnPtr {
+                    inputs: sig.inputs().iter().map(|&ty| self.ty(ty)).collect(),
+                    output: Box::new(self.ty(sig.output())),
+                }
+            }
+            rustc_ty::Closure(def_id, substs) => {
+                Ty::Closure(self.def_id(def_id), self.generic_args(substs))
+            }
+            rustc_ty::Generator(def_id, substs, movability) => Ty::Generator(
+                self.def_id(def_id),
+                self.generic_args(substs),
+                self.movability(movability),
+            ),
+            rustc_ty::Tuple(tys) => Ty::Tuple(tys.iter().map(|ty| self.ty(ty)).collect()),
+            rustc_ty::Param(param) => {
+                Ty::Param { index: param.index, name: param.name.to_string() }
+            }
+            rustc_ty::Dynamic(..)
+            | rustc_ty::GeneratorWitness(_)
+            | rustc_ty::Projection(_)
+            | rustc_ty::Opaque(..)
+            | rustc_ty::Bound(..)
+            | rustc_ty::Placeholder(_)
+            | rustc_ty::Infer(_)
+            | rustc_ty::Error(_) => Ty::Unsupported(ty.to_string()),
+        }
+    }
+}
+
+fn mutability(mutbl: rustc_mir::Mutability) -> Mutability {
+    match mutbl {
+        rustc_mir::Mutability::Not => Mutability::Not,
+        rustc_mir::Mutability::Mut => Mutability::Mut,
+    }
+}
+
+fn borrow_kind(kind: rustc_mir::BorrowKind) -> mir::BorrowKind {
+    match kind {
+        rustc_mir::BorrowKind::Shared => mir::BorrowKind::Shared,
+        rustc_mir::BorrowKind::Shallow => mir::BorrowKind::Shallow,
+        rustc_mir::BorrowKind::Unique => mir::BorrowKind::Unique,
+        rustc_mir::BorrowKind::Mut { allow_two_phase_borrow } => {
+            mir::BorrowKind::Mut { allow_two_phase_borrow }
+        }
+    }
+}
+
+fn cast_kind(kind: rustc_mir::CastKind) -> mir::CastKind {
+    match kind {
+        rustc_mir::CastKind::PointerExposeAddress => mir::CastKind::PointerExposeAddress,
+        rustc_mir::CastKind::PointerFromExposedAddress => mir::CastKind::PointerFromExposedAddress,
+        rustc_mir::CastKind::Pointer(cast) => mir::CastKind::Pointer(match cast {
+            PointerCast::ReifyFnPointer => mir::PointerCast::ReifyFnPointer,
+            PointerCast::UnsafeFnPointer => mir::PointerCast::UnsafeFnPointer,
+            PointerCast::ClosureFnPointer(unsafety) => {
+                mir::PointerCast::ClosureFnPointer { is_unsafe: unsafety == hir::Unsafety::Unsafe }
+            }
+            PointerCast::MutToConstPointer => mir::PointerCast::MutToConstPointer,
+            PointerCast::ArrayToPointer => mir::PointerCast::ArrayToPointer,
+            PointerCast::Unsize => mir::PointerCast::Unsize,
+        }),
+        rustc_mir::CastKind::Misc => mir::CastKind::Misc,
+    }
+}
+
+fn bin_op(op: rustc_mir::BinOp) -> mir::BinOp {
+    match op {
+        rustc_mir::BinOp::Add => mir::BinOp::Add,
+        rustc_mir::BinOp::Sub => mir::BinOp::Sub,
+        rustc_mir::BinOp::Mul => mir::BinOp::Mul,
+        rustc_mir::BinOp::Div => mir::BinOp::Div,
+        rustc_mir::BinOp::Rem => mir::BinOp::Rem,
+        rustc_mir::BinOp::BitXor => mir::BinOp::BitXor,
+        rustc_mir::BinOp::BitAnd => mir::BinOp::BitAnd,
+        rustc_mir::BinOp::BitOr => mir::BinOp::BitOr,
+        rustc_mir::BinOp::Shl => mir::BinOp::Shl,
+        rustc_mir::BinOp::Shr => mir::BinOp::Shr,
+        rustc_mir::BinOp::Eq => mir::BinOp::Eq,
+        rustc_mir::BinOp::Lt => mir::BinOp::Lt,
+        rustc_mir::BinOp::Le => mir::BinOp::Le,
+        rustc_mir::BinOp::Ne => mir::BinOp::Ne,
+        rustc_mir::BinOp::Ge => mir::BinOp::Ge,
+        rustc_mir::BinOp::Gt => mir::BinOp::Gt,
+        rustc_mir::BinOp::Offset => mir::BinOp::Offset,
+    }
+}
@@ -0,0 +1,48 @@
+//! Entry point for tools that want to analyze a crate through the stable MIR.
+
+use crate::context::{Context, CrateItem};
+use crate::very_unstable::driver::{Callbacks, Compilation, RunCompiler};
+use crate::very_unstable::interface::{interface, Queries};
+
+/// Runs the compiler with the given command line arguments and, once the crate
+/// has been successfully analyzed, hands `callback` all items of the crate
+/// that have a MIR body. Compilation stops after the callback returns.
+///
+/// ```ignore (needs a compiler session)
+/// rustc_smir::run(&args, |cx, items| {
+///     for item in items {
+///         let body = cx.mir_body(item.def_id).unwrap();
+///         println!("{}: {} blocks", item.name, body.blocks.len());
+///     }
+/// })
+/// ```
+pub fn run<F>(args: &[String], callback: F) -> interface::Result<()>
+where
+    F: for<'tcx> FnOnce(&mut Context<'tcx>, &[CrateItem]) + Send,
+{
+    let mut callbacks = StableMirCallbacks { callback: Some(callback) };
+    RunCompiler::new(args, &mut callbacks).run()
+}
+
+struct StableMirCallbacks<F> {
+    callback: Option<F>,
+}
+
+impl<F> Callbacks for StableMirCallbacks<F>
+where
+    F: for<'tcx> FnOnce(&mut Context<'tcx>, &[CrateItem]) + Send,
+{
+    fn after_analysis<'tcx>(
+        &mut self,
+        compiler: &interface::Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        compiler.session().abort_if_errors();
+        queries.global_ctxt().unwrap().peek_mut().enter(|tcx| {
+            let mut cx = Context::new(tcx);
+            let items = cx.crate_items();
+            (self.callback.take().unwrap())(&mut cx, &items);
+        });
+        Compilation::Stop
+    }
+}
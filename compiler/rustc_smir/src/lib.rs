@@ -11,7 +11,14 @@
     test(attr(allow(unused_variables), deny(warnings)))
 )]
 #![cfg_attr(not(feature = "default"), feature(rustc_private))]
+#![feature(box_patterns)]
 
+mod context;
+mod driver;
 pub mod mir;
+pub mod ty;
 
 pub mod very_unstable;
+
+pub use crate::context::{Context, CrateItem};
+pub use crate::driver::run;
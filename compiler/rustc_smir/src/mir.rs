@@ -1,10 +1,257 @@
-pub use crate::very_unstable::middle::mir::{
-    visit::MutVisitor, AggregateKind, AssertKind, BasicBlock, BasicBlockData, BinOp, BindingForm,
-    BlockTailInfo, Body, BorrowKind, CastKind, ClearCrossCrate, Constant, ConstantKind,
-    CopyNonOverlapping, Coverage, FakeReadCause, Field, GeneratorInfo, ImplicitSelfKind,
-    InlineAsmOperand, Local, LocalDecl, LocalInfo, LocalKind, Location, MirPhase, MirSource,
-    NullOp, Operand, Place, PlaceRef, ProjectionElem, ProjectionKind, Promoted, RetagKind, Rvalue,
-    Safety, SourceInfo, SourceScope, SourceScopeData, SourceScopeLocalData, Statement,
-    StatementKind, UnOp, UserTypeProjection, UserTypeProjections, VarBindingForm, VarDebugInfo,
-    VarDebugInfoContents,
-};
+//! Stable, owned representation of MIR.
+//!
+//! Bodies are converted from the compiler's MIR by [`Context::mir_body`](crate::Context::mir_body)
+//! and don't borrow from the compiler, so they can outlive the compilation session.
+
+use crate::ty::{DefId, GenericArg, Movability, Mutability, Span, Ty};
+
+/// Index of a [`BasicBlock`] in [`Body::blocks`].
+pub type BasicBlockIdx = usize;
+
+/// Index of a [`LocalDecl`] in [`Body::locals`].
+pub type Local = usize;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Body {
+    pub blocks: Vec<BasicBlock>,
+    /// The return place is local 0, followed by the `arg_count` arguments.
+    pub locals: Vec<LocalDecl>,
+    pub arg_count: usize,
+    pub span: Option<Span>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalDecl {
+    pub ty: Ty,
+    pub mutability: Mutability,
+    pub span: Option<Span>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BasicBlock {
+    pub statements: Vec<Statement>,
+    pub terminator: Terminator,
+    pub is_cleanup: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Option<Span>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatementKind {
+    Assign(Place, Rvalue),
+    FakeRead(Place),
+    SetDiscriminant { place: Place, variant_index: usize },
+    Deinit(Place),
+    StorageLive(Local),
+    StorageDead(Local),
+    Retag(Place),
+    AscribeUserType(Place),
+    Coverage,
+    CopyNonOverlapping { src: Operand, dst: Operand, count: Operand },
+    Nop,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Terminator {
+    pub kind: TerminatorKind,
+    pub span: Option<Span>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TerminatorKind {
+    Goto {
+        target: BasicBlockIdx,
+    },
+    SwitchInt {
+        discr: Operand,
+        switch_ty: Ty,
+        targets: Vec<(u128, BasicBlockIdx)>,
+        otherwise: BasicBlockIdx,
+    },
+    Resume,
+    Abort,
+    Return,
+    Unreachable,
+    Drop {
+        place: Place,
+        target: BasicBlockIdx,
+        unwind: Option<BasicBlockIdx>,
+    },
+    DropAndReplace {
+        place: Place,
+        value: Operand,
+        target: BasicBlockIdx,
+        unwind: Option<BasicBlockIdx>,
+    },
+    Call {
+        func: Operand,
+        args: Vec<Operand>,
+        destination: Place,
+        target: Option<BasicBlockIdx>,
+        cleanup: Option<BasicBlockIdx>,
+    },
+    Assert {
+        cond: Operand,
+        expected: bool,
+        msg: AssertMessage,
+        target: BasicBlockIdx,
+        cleanup: Option<BasicBlockIdx>,
+    },
+    Yield {
+        value: Operand,
+        resume: BasicBlockIdx,
+        resume_arg: Place,
+        drop: Option<BasicBlockIdx>,
+    },
+    GeneratorDrop,
+    FalseEdge {
+        real_target: BasicBlockIdx,
+        imaginary_target: BasicBlockIdx,
+    },
+    FalseUnwind {
+        real_target: BasicBlockIdx,
+        unwind: Option<BasicBlockIdx>,
+    },
+    InlineAsm {
+        /// The inline assembly, rendered as source code.
+        asm: String,
+        destination: Option<BasicBlockIdx>,
+        cleanup: Option<BasicBlockIdx>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssertMessage {
+    BoundsCheck { len: Operand, index: Operand },
+    Overflow(BinOp, Operand, Operand),
+    OverflowNeg(Operand),
+    DivisionByZero(Operand),
+    RemainderByZero(Operand),
+    ResumedAfterReturn,
+    ResumedAfterPanic,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Place {
+    pub local: Local,
+    pub projection: Vec<ProjectionElem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProjectionElem {
+    Deref,
+    Field(usize, Ty),
+    Index(Local),
+    ConstantIndex { offset: u64, min_length: u64, from_end: bool },
+    Subslice { from: u64, to: u64, from_end: bool },
+    Downcast(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Copy(Place),
+    Move(Place),
+    Constant(Constant),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constant {
+    pub ty: Ty,
+    /// The value, rendered as source code.
+    pub literal: String,
+    pub span: Option<Span>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rvalue {
+    Use(Operand),
+    /// The operand and the number of repetitions, rendered as source code.
+    Repeat(Operand, String),
+    Ref(BorrowKind, Place),
+    ThreadLocalRef(DefId),
+    AddressOf(Mutability, Place),
+    Len(Place),
+    Cast(CastKind, Operand, Ty),
+    BinaryOp(BinOp, Operand, Operand),
+    CheckedBinaryOp(BinOp, Operand, Operand),
+    NullaryOp(NullOp, Ty),
+    UnaryOp(UnOp, Operand),
+    Discriminant(Place),
+    Aggregate(AggregateKind, Vec<Operand>),
+    ShallowInitBox(Operand, Ty),
+    CopyForDeref(Place),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BorrowKind {
+    Shared,
+    Shallow,
+    Unique,
+    Mut { allow_two_phase_borrow: bool },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CastKind {
+    PointerExposeAddress,
+    PointerFromExposedAddress,
+    Pointer(PointerCast),
+    Misc,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PointerCast {
+    ReifyFnPointer,
+    UnsafeFnPointer,
+    ClosureFnPointer { is_unsafe: bool },
+    MutToConstPointer,
+    ArrayToPointer,
+    Unsize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    BitXor,
+    BitAnd,
+    BitOr,
+    Shl,
+    Shr,
+    Eq,
+    Lt,
+    Le,
+    Ne,
+    Ge,
+    Gt,
+    Offset,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UnOp {
+    Not,
+    Neg,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NullOp {
+    SizeOf,
+    AlignOf,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AggregateKind {
+    Array(Ty),
+    Tuple,
+    /// The ADT, the index of the variant, its generic arguments, and for unions
+    /// the index of the initialized field.
+    Adt(DefId, usize, Vec<GenericArg>, Option<usize>),
+    Closure(DefId, Vec<GenericArg>),
+    Generator(DefId, Vec<GenericArg>, Movability),
+}
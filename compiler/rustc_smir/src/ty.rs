@@ -0,0 +1,98 @@
+//! Stable, owned representation of types.
+//!
+//! Types only cover what tools analyzing MIR need to know. Anything without a
+//! stable representation yet is kept as [`Ty::Unsupported`] with its rendering.
+
+/// Identifies an item, such as a function or an ADT, within one run of the compiler.
+///
+/// Use [`Context::def_path`](crate::Context::def_path) to get the path of the item.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DefId(pub(crate) usize);
+
+/// A region of source code. Lines and columns are 1-based, the end is exclusive.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub file: String,
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Mutability {
+    Not,
+    Mut,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IntTy {
+    Isize,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UintTy {
+    Usize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FloatTy {
+    F32,
+    F64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Movability {
+    Static,
+    Movable,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GenericArg {
+    Lifetime,
+    Type(Ty),
+    /// A const argument, rendered as source code.
+    Const(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Ty {
+    Bool,
+    Char,
+    Int(IntTy),
+    Uint(UintTy),
+    Float(FloatTy),
+    Str,
+    Never,
+    Adt(DefId, Vec<GenericArg>),
+    Foreign(DefId),
+    /// An array with its length, if the length is known.
+    Array(Box<Ty>, Option<u64>),
+    Slice(Box<Ty>),
+    RawPtr(Mutability, Box<Ty>),
+    Ref(Mutability, Box<Ty>),
+    FnDef(DefId, Vec<GenericArg>),
+    FnPtr {
+        inputs: Vec<Ty>,
+        output: Box<Ty>,
+    },
+    Closure(DefId, Vec<GenericArg>),
+    Generator(DefId, Vec<GenericArg>, Movability),
+    Tuple(Vec<Ty>),
+    Param {
+        index: u32,
+        name: String,
+    },
+    /// A type without a stable representation, rendered as source code.
+    Unsupported(String),
+}
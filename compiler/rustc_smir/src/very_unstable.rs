@@ -23,5 +23,6 @@ macro_rules! crates {
     rustc_mir_dataflow -> dataflow,
     rustc_mir_transform -> transform,
     rustc_serialize -> serialize,
+    rustc_span -> span,
     rustc_trait_selection -> trait_selection,
 }
@@ -304,6 +304,9 @@ pub fn check_intrinsic_type(tcx: TyCtxt<'_>, it: &hir::ForeignItem<'_>) {
                 vec![tcx.mk_mut_ptr(tcx.types.u8), tcx.types.usize, tcx.types.usize],
                 tcx.mk_unit(),
             ),
+            sym::const_make_global => {
+                (0, vec![tcx.mk_mut_ptr(tcx.types.u8)], tcx.mk_imm_ptr(tcx.types.u8))
+            }
 
             sym::ptr_offset_from => {
                 (1, vec![tcx.mk_imm_ptr(param(0)), tcx.mk_imm_ptr(param(0))], tcx.types.isize)
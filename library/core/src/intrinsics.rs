@@ -2062,6 +2062,24 @@ mod atomics {
     #[rustc_const_unstable(feature = "const_heap", issue = "79597")]
     pub fn const_deallocate(ptr: *mut u8, size: usize, align: usize);
 
+    /// Marks a block of memory allocated by `intrinsics::const_allocate` at compile time as
+    /// global: it becomes immutable, and can be part of the final value of the constant, e.g.
+    /// as the buffer of a `Vec`-like value built at compile time.
+    /// At runtime, just returns `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// - The `ptr` must point to the beginning of a block allocated by
+    ///   `intrinsics::const_allocate` in the evaluation of the current constant, which was
+    ///   not made global yet.
+    ///    - At compile time, a compile error occurs if this constraint is violated.
+    ///    - At runtime, it is not checked.
+    /// - The memory must not be written to or deallocated afterwards, including at runtime
+    ///   through pointers in the final value of the constant. At compile time, a compile
+    ///   error occurs if this constraint is violated.
+    #[rustc_const_unstable(feature = "const_heap", issue = "79597")]
+    pub fn const_make_global(ptr: *mut u8) -> *const u8;
+
     /// Determines whether the raw bytes of the two values are equal.
     ///
     /// This is particularly handy for arrays, since it allows things like just
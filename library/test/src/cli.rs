@@ -2,9 +2,10 @@
 
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use super::helpers::isatty;
-use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
+use super::options::{ColorConfig, Options, OutputFormat, RunIgnored, Shard};
 use super::time::TestTimeOptions;
 
 #[derive(Debug)]
@@ -25,7 +26,13 @@ pub struct TestOpts {
     pub shuffle_seed: Option<u64>,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
+    pub shard: Option<Shard>,
     pub time_options: Option<TestTimeOptions>,
+    /// Fail (and, for tests run in a subprocess, kill) tests that run for longer
+    /// than this.
+    pub test_timeout: Option<Duration>,
+    /// How many times a failing test is run again before it is reported as failed.
+    pub retries: usize,
     pub options: Options,
 }
 
@@ -98,8 +105,9 @@ fn optgroups() -> getopts::Options {
             pretty = Print verbose output;
             terse  = Display one character per test;
             json   = Output a json document;
-            junit  = Output a JUnit document",
-            "pretty|terse|json|junit",
+            junit  = Output a JUnit document;
+            tap    = Output a TAP version 14 stream",
+            "pretty|terse|json|junit|tap",
         )
         .optflag("", "show-output", "Show captured stdout of successful tests")
         .optopt(
@@ -138,6 +146,37 @@ fn optgroups() -> getopts::Options {
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
         )
+        .optopt(
+            "",
+            "test-timeout",
+            "Fail tests that run for longer than SECS seconds.
+
+            Tests run in a subprocess (when panic=abort) are killed once they
+            time out. Tests run in-process can't be interrupted, so they are
+            reported as failed and left running in the background.",
+            "SECS",
+        )
+        .optopt(
+            "",
+            "shard-count",
+            "Split the tests into N shards by a hash of their names, and only run
+            the shard selected with --shard-index",
+            "N",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Run only the tests of the shard with index N, which must be less
+            than the value passed to --shard-count",
+            "N",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run each failing test up to N more times. Tests that pass on a retry
+            are reported as flaky instead of failed",
+            "N",
+        )
         .optflag("", "shuffle", "Run tests in random order")
         .optopt(
             "",
@@ -255,8 +294,11 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let force_run_in_process = unstable_optflag!(matches, allow_unstable, "force-run-in-process");
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -294,7 +336,10 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         shuffle_seed,
         test_threads,
         skip,
+        shard,
         time_options,
+        test_timeout,
+        retries,
         options,
     };
 
@@ -330,6 +375,42 @@ fn get_time_options(
     Ok(options)
 }
 
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match unstable_optopt!(matches, allow_unstable, "test-timeout") {
+        Some(secs_str) => match secs_str.parse::<u64>() {
+            Ok(0) => return Err("argument for --test-timeout must not be 0".to_string()),
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --test-timeout must be a number > 0 \
+                     (error: {})",
+                    e
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!("argument for --retries must be a number (error: {})", e));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
 fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
     let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
     if !shuffle && allow_unstable {
@@ -370,6 +451,40 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<Shard>> {
+    let parse = |option_name: &str, n_str: String| {
+        n_str
+            .parse::<usize>()
+            .map_err(|e| format!("argument for --{} must be a number (error: {})", option_name, e))
+    };
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let count = unstable_optopt!(matches, allow_unstable, "shard-count");
+
+    let shard = match (index, count) {
+        (Some(index), Some(count)) => {
+            let index = parse("shard-index", index)?;
+            let count = parse("shard-count", count)?;
+            if count == 0 {
+                return Err("argument for --shard-count must not be 0".to_string());
+            }
+            if index >= count {
+                return Err(format!(
+                    "argument for --shard-index must be less than --shard-count ({}), \
+                     but was {}",
+                    count, index
+                ));
+            }
+            Some(Shard { index, count })
+        }
+        (None, None) => None,
+        _ => {
+            return Err("the options --shard-index and --shard-count must be used together".into());
+        }
+    };
+
+    Ok(shard)
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -410,9 +525,15 @@ fn get_format(
             }
             OutputFormat::Junit
         }
+        Some("tap") => {
+            if !allow_unstable {
+                return Err("The \"tap\" format is only accepted on the nightly compiler".into());
+            }
+            OutputFormat::Tap
+        }
         Some(v) => {
             return Err(format!(
-                "argument for --format must be pretty, terse, json or junit (was \
+                "argument for --format must be pretty, terse, json, junit or tap (was \
                  {})",
                 v
             ));
@@ -10,9 +10,12 @@
     cli::TestOpts,
     event::{CompletedTest, TestEvent},
     filter_tests,
-    formatters::{JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter},
+    formatters::{
+        JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TapFormatter,
+        TerseFormatter,
+    },
     helpers::{concurrency::get_concurrency, metrics::MetricMap},
-    options::{Options, OutputFormat},
+    options::{Options, OutputFormat, Shard},
     run_tests, term,
     test_result::TestResult,
     time::{TestExecTime, TestSuiteExecTime},
@@ -46,6 +49,7 @@ pub struct ConsoleTestState {
     pub total: usize,
     pub passed: usize,
     pub failed: usize,
+    pub flaky: usize,
     pub ignored: usize,
     pub filtered_out: usize,
     pub measured: usize,
@@ -54,6 +58,7 @@ pub struct ConsoleTestState {
     pub failures: Vec<(TestDesc, Vec<u8>)>,
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub flaky_tests: Vec<(TestDesc, Vec<u8>)>,
     pub options: Options,
 }
 
@@ -69,6 +74,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             total: 0,
             passed: 0,
             failed: 0,
+            flaky: 0,
             ignored: 0,
             filtered_out: 0,
             measured: 0,
@@ -77,6 +83,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             failures: Vec::new(),
             not_failures: Vec::new(),
             time_failures: Vec::new(),
+            flaky_tests: Vec::new(),
             options: opts.options,
         })
     }
@@ -119,6 +126,7 @@ pub fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrFlaky(attempts) => format!("flaky: failed {attempts} time(s)"),
                 },
                 name,
             )
@@ -130,7 +138,7 @@ pub fn write_log_result(
     }
 
     fn current_test_count(&self) -> usize {
-        self.passed + self.failed + self.ignored + self.measured
+        self.passed + self.failed + self.flaky + self.ignored + self.measured
     }
 }
 
@@ -179,7 +187,18 @@ fn plural(count: u32, s: &str) -> String {
             writeln!(output)?;
         }
 
-        writeln!(output, "{}, {}", plural(ntest, "test"), plural(nbench, "benchmark"))?;
+        let shard_msg = if let Some(Shard { index, count }) = opts.shard {
+            format!(" in shard index {index} of {count}")
+        } else {
+            String::new()
+        };
+        writeln!(
+            output,
+            "{}, {}{}",
+            plural(ntest, "test"),
+            plural(nbench, "benchmark"),
+            shard_msg
+        )?;
     }
 
     Ok(())
@@ -217,6 +236,10 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrFlaky(_) => {
+            st.flaky += 1;
+            st.flaky_tests.push((test, stdout));
+        }
     }
 }
 
@@ -228,9 +251,9 @@ fn on_test_event(
     out: &mut dyn OutputFormatter,
 ) -> io::Result<()> {
     match (*event).clone() {
-        TestEvent::TeFiltered(ref filtered_tests, shuffle_seed) => {
+        TestEvent::TeFiltered(ref filtered_tests, shuffle_seed, shard) => {
             st.total = filtered_tests.len();
-            out.write_run_start(filtered_tests.len(), shuffle_seed)?;
+            out.write_run_start(filtered_tests.len(), shuffle_seed, shard)?;
         }
         TestEvent::TeFilteredOut(filtered_out) => {
             st.filtered_out = filtered_out;
@@ -281,6 +304,7 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         }
         OutputFormat::Json => Box::new(JsonFormatter::new(output)),
         OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
+        OutputFormat::Tap => Box::new(TapFormatter::new(output)),
     };
     let mut st = ConsoleTestState::new(opts)?;
 
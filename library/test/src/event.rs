@@ -1,6 +1,7 @@
 //! Module containing different events that can occur
 //! during tests execution process.
 
+use super::options::Shard;
 use super::test_result::TestResult;
 use super::time::TestExecTime;
 use super::types::{TestDesc, TestId};
@@ -28,7 +29,7 @@ pub fn new(
 
 #[derive(Debug, Clone)]
 pub enum TestEvent {
-    TeFiltered(Vec<TestDesc>, Option<u64>),
+    TeFiltered(Vec<TestDesc>, Option<u64>, Option<Shard>),
     TeWait(TestDesc),
     TeResult(CompletedTest),
     TeTimeout(TestDesc),
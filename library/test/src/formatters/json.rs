@@ -3,6 +3,7 @@
 use super::OutputFormatter;
 use crate::{
     console::{ConsoleTestState, OutputLocation},
+    options::Shard,
     test_result::TestResult,
     time,
     types::TestDesc,
@@ -60,15 +61,25 @@ fn write_event(
 }
 
 impl<T: Write> OutputFormatter for JsonFormatter<T> {
-    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+    fn write_run_start(
+        &mut self,
+        test_count: usize,
+        shuffle_seed: Option<u64>,
+        shard: Option<Shard>,
+    ) -> io::Result<()> {
         let shuffle_seed_json = if let Some(shuffle_seed) = shuffle_seed {
             format!(r#", "shuffle_seed": {}"#, shuffle_seed)
         } else {
             String::new()
         };
+        let shard_json = if let Some(Shard { index, count }) = shard {
+            format!(r#", "shard_index": {}, "shard_count": {}"#, index, count)
+        } else {
+            String::new()
+        };
         self.writeln_message(&*format!(
-            r#"{{ "type": "suite", "event": "started", "test_count": {}{} }}"#,
-            test_count, shuffle_seed_json
+            r#"{{ "type": "suite", "event": "started", "test_count": {}{}{} }}"#,
+            test_count, shard_json, shuffle_seed_json
         ))
     }
 
@@ -111,6 +122,15 @@ fn write_result(
                 Some(r#""reason": "time limit exceeded""#),
             ),
 
+            TestResult::TrFlaky(attempts) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""failed_attempts": {attempts}"#)),
+            ),
+
             TestResult::TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
@@ -181,6 +201,10 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             state.filtered_out,
         ))?;
 
+        if state.flaky > 0 {
+            self.write_message(&*format!(", \"flaky\": {}", state.flaky))?;
+        }
+
         if let Some(ref exec_time) = state.exec_time {
             let time_str = format!(", \"exec_time\": {}", exec_time.0.as_secs_f64());
             self.write_message(&time_str)?;
@@ -194,7 +218,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
 /// A formatting utility used to print strings with characters in need of escaping.
 /// Base code taken form `libserialize::json::escape_str`
-struct EscapedString<S: AsRef<str>>(S);
+pub(super) struct EscapedString<S: AsRef<str>>(pub(super) S);
 
 impl<S: AsRef<str>> std::fmt::Display for EscapedString<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> ::std::fmt::Result {
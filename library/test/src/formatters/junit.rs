@@ -4,6 +4,7 @@
 use super::OutputFormatter;
 use crate::{
     console::{ConsoleTestState, OutputLocation},
+    options::Shard,
     test_result::TestResult,
     time,
     types::{TestDesc, TestType},
@@ -31,6 +32,7 @@ fn write_run_start(
         &mut self,
         _test_count: usize,
         _shuffle_seed: Option<u64>,
+        _shard: Option<Shard>,
     ) -> io::Result<()> {
         // We write xml header on run start
         self.write_message("<?xml version=\"1.0\" encoding=\"UTF-8\"?>")
@@ -113,6 +115,23 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                     self.write_message("</testcase>")?;
                 }
 
+                TestResult::TrFlaky(attempts) => {
+                    // Flaky tests passed in the end, so they are reported the
+                    // way surefire does: a passing testcase with the earlier
+                    // failures attached.
+                    self.write_message(&*format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    for _ in 0..attempts {
+                        self.write_message("<flakyFailure type=\"assert\"/>")?;
+                    }
+                    self.write_message("</testcase>")?;
+                }
+
                 TestResult::TrBench(ref b) => {
                     self.write_message(&*format!(
                         "<testcase classname=\"benchmark::{}\" \
@@ -0,0 +1,215 @@
+use std::{borrow::Cow, io, io::prelude::Write};
+
+use super::json::EscapedString;
+use super::OutputFormatter;
+use crate::{
+    bench::fmt_thousands_sep,
+    console::{ConsoleTestState, OutputLocation},
+    options::Shard,
+    test_result::TestResult,
+    time,
+    types::TestDesc,
+};
+
+/// Streams the results as [TAP version 14](https://testanything.org/tap-version-14-specification.html).
+pub(crate) struct TapFormatter<T> {
+    out: OutputLocation<T>,
+    test_number: usize,
+}
+
+impl<T: Write> TapFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out, test_number: 0 }
+    }
+
+    fn writeln_message(&mut self, s: &str) -> io::Result<()> {
+        assert!(!s.contains('\n'));
+
+        self.out.write_all(s.as_ref())?;
+        self.out.write_all(b"\n")
+    }
+
+    fn write_test_point(
+        &mut self,
+        ok: bool,
+        desc: &TestDesc,
+        directive: Option<&str>,
+        diagnostics: &[(&str, Cow<'_, str>)],
+    ) -> io::Result<()> {
+        self.test_number += 1;
+        let mut line = format!(
+            "{} {} - {}",
+            if ok { "ok" } else { "not ok" },
+            self.test_number,
+            EscapedDescription(desc.name.as_slice())
+        );
+        if let Some(directive) = directive {
+            line.push_str(&format!(" # {directive}"));
+        }
+        self.writeln_message(&line)?;
+
+        if diagnostics.is_empty() {
+            return Ok(());
+        }
+        self.writeln_message("  ---")?;
+        for (key, value) in diagnostics {
+            if value.contains('\n') {
+                // A literal block scalar with an explicit indentation indicator, so
+                // that output starting with spaces keeps its indentation.
+                self.writeln_message(&format!("  {key}: |2"))?;
+                for line in value.lines() {
+                    if line.is_empty() {
+                        self.writeln_message("")?;
+                    } else {
+                        self.writeln_message(&format!("    {line}"))?;
+                    }
+                }
+            } else {
+                self.writeln_message(&format!("  {key}: {value}"))?;
+            }
+        }
+        self.writeln_message("  ...")
+    }
+}
+
+impl<T: Write> OutputFormatter for TapFormatter<T> {
+    fn write_run_start(
+        &mut self,
+        test_count: usize,
+        shuffle_seed: Option<u64>,
+        shard: Option<Shard>,
+    ) -> io::Result<()> {
+        self.writeln_message("TAP version 14")?;
+        if let Some(Shard { index, count }) = shard {
+            self.writeln_message(&format!("# shard index {index} of {count}"))?;
+        }
+        if let Some(shuffle_seed) = shuffle_seed {
+            self.writeln_message(&format!("# shuffle seed: {shuffle_seed}"))?;
+        }
+        self.writeln_message(&format!("1..{test_count}"))
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // Test points are only written once the result is known.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.writeln_message(&format!(
+            "# test {} has been running for over {} seconds",
+            desc.name,
+            time::TEST_WARN_TIMEOUT_S
+        ))
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        let mut diagnostics = Vec::new();
+        match *result {
+            TestResult::TrFailedMsg(ref msg) => {
+                diagnostics.push(("message", format!("\"{}\"", EscapedString(msg)).into()))
+            }
+            TestResult::TrTimedFail => {
+                diagnostics.push(("message", "\"time limit exceeded\"".into()))
+            }
+            TestResult::TrFlaky(attempts) => {
+                diagnostics.push(("failed_attempts", attempts.to_string().into()))
+            }
+            _ => {}
+        }
+        if let Some(exec_time) = exec_time {
+            let duration_ms = exec_time.0.as_secs_f64() * 1000.0;
+            diagnostics.push(("duration_ms", format!("{duration_ms:.3}").into()));
+        }
+        let display_stdout = state.options.display_output || *result != TestResult::TrOk;
+        if display_stdout && !stdout.is_empty() {
+            let mut stdout = String::from_utf8_lossy(stdout).into_owned();
+            if !stdout.ends_with('\n') {
+                stdout.push('\n');
+            }
+            diagnostics.push(("stdout", stdout.into()));
+        }
+
+        match *result {
+            TestResult::TrOk | TestResult::TrFlaky(_) => {
+                self.write_test_point(true, desc, None, &diagnostics)
+            }
+            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => {
+                self.write_test_point(false, desc, None, &diagnostics)
+            }
+            TestResult::TrIgnored => {
+                let directive = match desc.ignore_message {
+                    Some(msg) => format!("SKIP {msg}"),
+                    None => "SKIP".to_owned(),
+                };
+                self.write_test_point(true, desc, Some(&directive), &diagnostics)
+            }
+            TestResult::TrBench(ref bs) => {
+                // Each measurement is reported as a test point of a subtest.
+                let median = bs.ns_iter_summ.median as usize;
+                let deviation = (bs.ns_iter_summ.max - bs.ns_iter_summ.min) as usize;
+                let mut measurements = vec![
+                    format!("median {} ns/iter", fmt_thousands_sep(median, ',')),
+                    format!("deviation +/- {}", fmt_thousands_sep(deviation, ',')),
+                ];
+                if bs.mb_s != 0 {
+                    measurements.push(format!("throughput {} MB/s", bs.mb_s));
+                }
+
+                self.writeln_message(&format!(
+                    "    # Subtest: {}",
+                    EscapedDescription(desc.name.as_slice())
+                ))?;
+                for (i, measurement) in measurements.iter().enumerate() {
+                    self.writeln_message(&format!("    ok {} - {measurement}", i + 1))?;
+                }
+                self.writeln_message(&format!("    1..{}", measurements.len()))?;
+                self.write_test_point(true, desc, None, &diagnostics)
+            }
+        }
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let mut summary = format!(
+            "# test result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+            if state.failed == 0 { "ok" } else { "FAILED" },
+            state.passed,
+            state.failed,
+            state.ignored,
+            state.measured,
+            state.filtered_out,
+        );
+        if state.flaky > 0 {
+            summary.push_str(&format!("; {} flaky", state.flaky));
+        }
+        if let Some(ref exec_time) = state.exec_time {
+            summary.push_str(&format!("; finished in {exec_time}"));
+        }
+        self.writeln_message(&summary)?;
+
+        Ok(state.failed == 0)
+    }
+}
+
+/// Escapes the characters that would otherwise end a test point description.
+struct EscapedDescription<S: AsRef<str>>(S);
+
+impl<S: AsRef<str>> std::fmt::Display for EscapedDescription<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        for c in self.0.as_ref().chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                '#' => f.write_str("\\#")?,
+                '\n' => f.write_str(" ")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+}
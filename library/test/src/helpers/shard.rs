@@ -0,0 +1,25 @@
+use crate::options::Shard;
+use crate::types::TestDescAndFn;
+
+/// Keeps only the tests that belong to `shard`.
+///
+/// Tests are assigned to shards by a hash of their name, so a test always runs on
+/// the same shard no matter which other tests exist, were filtered out or were
+/// added since the last run.
+pub fn shard_tests(shard: Shard, tests: &mut Vec<TestDescAndFn>) {
+    tests.retain(|test| shard_of(test.desc.name.as_slice(), shard.count) == shard.index);
+}
+
+/// Returns the shard in `0..shard_count` that the test named `name` runs on.
+pub fn shard_of(name: &str, shard_count: usize) -> usize {
+    (stable_hash(name) % shard_count as u64) as usize
+}
+
+// Unlike `DefaultHasher`, 64-bit FNV-1a is guaranteed to produce the same hash
+// with every Rust release, so shards stay stable across toolchain upgrades.
+fn stable_hash(name: &str) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    name.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
@@ -24,7 +24,7 @@
 // Public reexports
 pub use self::bench::{black_box, Bencher};
 pub use self::console::run_tests_console;
-pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic};
+pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, Shard, ShouldPanic};
 pub use self::types::TestName::*;
 pub use self::types::*;
 pub use self::ColorConfig::*;
@@ -80,6 +80,7 @@ pub mod test {
 use event::{CompletedTest, TestEvent};
 use helpers::concurrency::get_concurrency;
 use helpers::exit_code::get_exit_code;
+use helpers::shard::shard_tests;
 use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
 use options::{Concurrent, RunStrategy};
 use test_result::*;
@@ -215,9 +216,92 @@ struct TimeoutEntry {
         timeout: Instant,
     }
 
+    // A test function that can be run again by `--retries`.
+    #[derive(Clone, Copy)]
+    enum RetryFn {
+        Test(fn()),
+        // A static benchmark run as a test, see `convert_benchmarks_to_tests`.
+        Bench(fn(&mut Bencher)),
+    }
+
+    // Bookkeeping for `--retries`.
+    struct Retries {
+        max_retries: usize,
+        // Static tests and benchmarks can be run again, dynamic tests are
+        // consumed by their first run and are never retried.
+        testfns: HashMap<TestId, RetryFn>,
+        // The number of failed attempts and the output of the first failure.
+        failures: HashMap<TestId, (usize, Vec<u8>)>,
+    }
+
+    impl Retries {
+        fn is_retry(&self, id: TestId) -> bool {
+            self.failures.contains_key(&id)
+        }
+
+        // Queues the test to run again if it failed and has retries left, otherwise
+        // returns the result to report. Tests that pass after a failure are flaky.
+        fn on_completed(
+            &mut self,
+            mut completed_test: CompletedTest,
+            remaining: &mut Vec<(TestId, TestDescAndFn)>,
+        ) -> Option<CompletedTest> {
+            let id = completed_test.id;
+            match completed_test.result {
+                TrFailed | TrFailedMsg(_) | TrTimedFail => {
+                    let Some(&testfn) = self.testfns.get(&id) else {
+                        return Some(completed_test);
+                    };
+                    let (attempts, _) = self.failures.entry(id).or_insert_with(|| {
+                        let mut stdout = completed_test.stdout.clone();
+                        if let TrFailedMsg(ref msg) = completed_test.result {
+                            stdout.extend_from_slice(format!("note: {msg}").as_bytes());
+                        }
+                        (0, stdout)
+                    });
+                    *attempts += 1;
+                    if *attempts > self.max_retries {
+                        return Some(completed_test);
+                    }
+                    let desc = completed_test.desc;
+                    let testfn = match testfn {
+                        RetryFn::Test(f) => StaticTestFn(f),
+                        RetryFn::Bench(f) => static_bench_as_test(f),
+                    };
+                    // Pushed last, so the test is run again right away.
+                    remaining.push((id, TestDescAndFn { desc, testfn }));
+                    None
+                }
+                TrOk => {
+                    if let Some((attempts, stdout)) = self.failures.remove(&id) {
+                        completed_test.result = TrFlaky(attempts);
+                        completed_test.stdout = stdout;
+                    }
+                    Some(completed_test)
+                }
+                _ => Some(completed_test),
+            }
+        }
+    }
+
     let tests_len = tests.len();
 
     let mut filtered_tests = filter_tests(opts, tests);
+    // Benchmarks run as tests become dynamic tests, remember the static ones
+    // so that they can be retried. Converting them keeps the tests in order,
+    // so their index is the id they get below.
+    let static_benches: HashMap<TestId, fn(&mut Bencher)> = if opts.retries > 0 {
+        filtered_tests
+            .iter()
+            .enumerate()
+            .filter_map(|(i, test)| match test.testfn {
+                StaticBenchFn(f) => Some((TestId(i), f)),
+                _ => None,
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
     if !opts.bench_benchmarks {
         filtered_tests = convert_benchmarks_to_tests(filtered_tests);
     }
@@ -239,7 +323,7 @@ struct TimeoutEntry {
 
     let shuffle_seed = get_shuffle_seed(opts);
 
-    let event = TestEvent::TeFiltered(filtered_descs, shuffle_seed);
+    let event = TestEvent::TeFiltered(filtered_descs, shuffle_seed, opts.shard);
     notify_about_test_event(event)?;
 
     let (filtered_tests, filtered_benchs): (Vec<_>, _) = filtered_tests
@@ -250,6 +334,18 @@ struct TimeoutEntry {
 
     let concurrency = opts.test_threads.unwrap_or_else(get_concurrency);
 
+    let mut retries =
+        Retries { max_retries: opts.retries, testfns: HashMap::new(), failures: HashMap::new() };
+    if opts.retries > 0 {
+        for (id, test) in &filtered_tests {
+            if let StaticTestFn(f) = test.testfn {
+                retries.testfns.insert(*id, RetryFn::Test(f));
+            } else if let Some(&f) = static_benches.get(id) {
+                retries.testfns.insert(*id, RetryFn::Bench(f));
+            }
+        }
+    }
+
     let mut remaining = filtered_tests;
     if let Some(shuffle_seed) = shuffle_seed {
         shuffle_tests(shuffle_seed, &mut remaining);
@@ -268,10 +364,18 @@ struct TimeoutEntry {
     let mut running_tests: TestMap = HashMap::default();
     let mut timeout_queue: VecDeque<TimeoutEntry> = VecDeque::new();
 
+    // Subprocesses enforce `--test-timeout` themselves by killing the child, so
+    // the deadline queue below is only needed for tests that run in-process.
+    let test_timeout = match run_strategy {
+        RunStrategy::InProcess => opts.test_timeout,
+        RunStrategy::SpawnPrimary => None,
+    };
+    let mut deadline_queue: VecDeque<TimeoutEntry> = VecDeque::new();
+
     fn get_timed_out_tests(
         running_tests: &TestMap,
         timeout_queue: &mut VecDeque<TimeoutEntry>,
-    ) -> Vec<TestDesc> {
+    ) -> Vec<TimeoutEntry> {
         let now = Instant::now();
         let mut timed_out = Vec::new();
         while let Some(timeout_entry) = timeout_queue.front() {
@@ -280,7 +384,7 @@ fn get_timed_out_tests(
             }
             let timeout_entry = timeout_queue.pop_front().unwrap();
             if running_tests.contains_key(&timeout_entry.id) {
-                timed_out.push(timeout_entry.desc);
+                timed_out.push(timeout_entry);
             }
         }
         timed_out
@@ -293,18 +397,24 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         })
     }
 
-    if concurrency == 1 {
+    // An in-process test can only be abandoned once it times out if it runs on
+    // its own thread, so enforcing a timeout needs the concurrent runner below.
+    if concurrency == 1 && test_timeout.is_none() {
         while !remaining.is_empty() {
             let (id, test) = remaining.pop().unwrap();
-            let event = TestEvent::TeWait(test.desc.clone());
-            notify_about_test_event(event)?;
+            if !retries.is_retry(id) {
+                let event = TestEvent::TeWait(test.desc.clone());
+                notify_about_test_event(event)?;
+            }
             let join_handle =
                 run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone(), Concurrent::No);
             assert!(join_handle.is_none());
             let completed_test = rx.recv().unwrap();
 
-            let event = TestEvent::TeResult(completed_test);
-            notify_about_test_event(event)?;
+            if let Some(completed_test) = retries.on_completed(completed_test, &mut remaining) {
+                let event = TestEvent::TeResult(completed_test);
+                notify_about_test_event(event)?;
+            }
         }
     } else {
         while pending > 0 || !remaining.is_empty() {
@@ -313,8 +423,10 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 let timeout = time::get_default_test_timeout();
                 let desc = test.desc.clone();
 
-                let event = TestEvent::TeWait(desc.clone());
-                notify_about_test_event(event)?; //here no pad
+                if !retries.is_retry(id) {
+                    let event = TestEvent::TeWait(desc.clone());
+                    notify_about_test_event(event)?; //here no pad
+                }
                 let join_handle = run_test(
                     opts,
                     !opts.run_tests,
@@ -325,25 +437,61 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                     Concurrent::Yes,
                 );
                 running_tests.insert(id, RunningTest { join_handle });
+                if let Some(test_timeout) = test_timeout {
+                    let deadline = Instant::now() + test_timeout;
+                    deadline_queue.push_back(TimeoutEntry {
+                        id,
+                        desc: desc.clone(),
+                        timeout: deadline,
+                    });
+                }
                 timeout_queue.push_back(TimeoutEntry { id, desc, timeout });
                 pending += 1;
             }
 
             let mut res;
             loop {
-                if let Some(timeout) = calc_timeout(&timeout_queue) {
+                let next_timeout = calc_timeout(&timeout_queue)
+                    .into_iter()
+                    .chain(calc_timeout(&deadline_queue))
+                    .min();
+                if let Some(timeout) = next_timeout {
                     res = rx.recv_timeout(timeout);
-                    for test in get_timed_out_tests(&running_tests, &mut timeout_queue) {
-                        let event = TestEvent::TeTimeout(test);
+                    for entry in get_timed_out_tests(&running_tests, &mut timeout_queue) {
+                        let event = TestEvent::TeTimeout(entry.desc);
                         notify_about_test_event(event)?;
                     }
 
+                    let mut any_deadline_expired = false;
+                    for entry in get_timed_out_tests(&running_tests, &mut deadline_queue) {
+                        // There is no way to kill a thread, so leave the test running
+                        // and report it as failed. Its result is discarded if it ever
+                        // finishes.
+                        running_tests.remove(&entry.id);
+                        let exec_time = opts.time_options.and(test_timeout).map(TestExecTime);
+                        let completed_test = CompletedTest::new(
+                            entry.id,
+                            entry.desc,
+                            TrTimedFail,
+                            exec_time,
+                            Vec::new(),
+                        );
+                        if let Some(completed_test) =
+                            retries.on_completed(completed_test, &mut remaining)
+                        {
+                            notify_about_test_event(TestEvent::TeResult(completed_test))?;
+                        }
+                        pending -= 1;
+                        any_deadline_expired = true;
+                    }
+
                     match res {
-                        Err(RecvTimeoutError::Timeout) => {
+                        Err(RecvTimeoutError::Timeout) if !any_deadline_expired => {
                             // Result is not yet ready, continue waiting.
                         }
                         _ => {
-                            // We've got a result, stop the loop.
+                            // We've got a result, or freed up a slot for a new test, stop
+                            // the loop.
                             break;
                         }
                     }
@@ -353,8 +501,16 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 }
             }
 
-            let mut completed_test = res.unwrap();
-            let running_test = running_tests.remove(&completed_test.id).unwrap();
+            let mut completed_test = match res {
+                Ok(completed_test) => completed_test,
+                // Only timed out tests were reported, start new tests in their place.
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(e) => panic!("failed to receive test result: {e:?}"),
+            };
+            let Some(running_test) = running_tests.remove(&completed_test.id) else {
+                // This test has already been reported as timed out.
+                continue;
+            };
             if let Some(join_handle) = running_test.join_handle {
                 if let Err(_) = join_handle.join() {
                     if let TrOk = completed_test.result {
@@ -364,8 +520,10 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 }
             }
 
-            let event = TestEvent::TeResult(completed_test);
-            notify_about_test_event(event)?;
+            if let Some(completed_test) = retries.on_completed(completed_test, &mut remaining) {
+                let event = TestEvent::TeResult(completed_test);
+                notify_about_test_event(event)?;
+            }
             pending -= 1;
         }
     }
@@ -421,6 +579,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         RunIgnored::No => {}
     }
 
+    // Keep only the tests of the requested shard
+    if let Some(shard) = opts.shard {
+        shard_tests(shard, &mut filtered);
+    }
+
     // Sort the tests alphabetically
     filtered.sort_by(|t1, t2| t1.desc.name.as_slice().cmp(t2.desc.name.as_slice()));
 
@@ -436,9 +599,7 @@ pub fn convert_benchmarks_to_tests(tests: Vec<TestDescAndFn>) -> Vec<TestDescAnd
                 DynBenchFn(benchfn) => DynTestFn(Box::new(move || {
                     bench::run_once(|b| __rust_begin_short_backtrace(|| benchfn(b)))
                 })),
-                StaticBenchFn(benchfn) => DynTestFn(Box::new(move || {
-                    bench::run_once(|b| __rust_begin_short_backtrace(|| benchfn(b)))
-                })),
+                StaticBenchFn(benchfn) => static_bench_as_test(benchfn),
                 f => f,
             };
             TestDescAndFn { desc: x.desc, testfn }
@@ -446,6 +607,10 @@ pub fn convert_benchmarks_to_tests(tests: Vec<TestDescAndFn>) -> Vec<TestDescAnd
         .collect()
 }
 
+fn static_bench_as_test(benchfn: fn(&mut Bencher)) -> TestFn {
+    DynTestFn(Box::new(move || bench::run_once(|b| __rust_begin_short_backtrace(|| benchfn(b)))))
+}
+
 pub fn run_test(
     opts: &TestOpts,
     force_ignore: bool,
@@ -473,6 +638,7 @@ struct TestRunOpts {
         pub nocapture: bool,
         pub concurrency: Concurrent,
         pub time: Option<time::TestTimeOptions>,
+        pub timeout: Option<Duration>,
     }
 
     fn run_test_inner(
@@ -502,6 +668,7 @@ fn run_test_inner(
                 opts.time.is_some(),
                 monitor_ch,
                 opts.time,
+                opts.timeout,
             ),
         };
 
@@ -529,8 +696,13 @@ fn run_test_inner(
         }
     }
 
-    let test_run_opts =
-        TestRunOpts { strategy, nocapture: opts.nocapture, concurrency, time: opts.time_options };
+    let test_run_opts = TestRunOpts {
+        strategy,
+        nocapture: opts.nocapture,
+        concurrency,
+        time: opts.time_options,
+        timeout: opts.test_timeout,
+    };
 
     match testfn {
         DynBenchFn(benchfn) => {
@@ -616,6 +788,7 @@ fn spawn_test_subprocess(
     report_time: bool,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    timeout: Option<Duration>,
 ) {
     let (result, test_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
@@ -626,10 +799,19 @@ fn spawn_test_subprocess(
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            // This is what `Command::output` defaults to, but `output_with_timeout`
+            // spawns the child itself.
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match timeout {
+            Some(timeout) => output_with_timeout(&mut command, timeout),
+            None => command.output().map(|output| (output, false)),
+        };
+        let (output, timed_out) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
@@ -646,6 +828,10 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
+        if timed_out {
+            return (TrTimedFail, test_output, exec_time);
+        }
+
         let result = match (|| -> Result<TestResult, String> {
             let exit_code = get_exit_code(status)?;
             Ok(get_result_from_exit_code(&desc, exit_code, &time_opts, &exec_time))
@@ -664,6 +850,51 @@ fn spawn_test_subprocess(
     monitor_ch.send(message).unwrap();
 }
 
+/// Runs `command` like `Command::output`, but kills the child once `timeout` has
+/// elapsed. Returns the output collected so far, and whether the child was killed.
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+) -> io::Result<(process::Output, bool)> {
+    fn read_pipe<R: io::Read + Send + 'static>(
+        pipe: Option<R>,
+    ) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                pipe.read_to_end(&mut buf)?;
+            }
+            Ok(buf)
+        })
+    }
+
+    let mut child = command.stdin(process::Stdio::null()).spawn()?;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let mut poll_interval = Duration::from_millis(1);
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            // The child may have exited in the meantime, which is fine.
+            let _ = child.kill();
+            timed_out = true;
+            break child.wait()?;
+        }
+        thread::sleep(poll_interval.min(deadline - now));
+        poll_interval = (poll_interval * 2).min(Duration::from_millis(100));
+    };
+
+    let stdout = stdout.join().unwrap()?;
+    let stderr = stderr.join().unwrap()?;
+    Ok((process::Output { status, stdout, stderr }, timed_out))
+}
+
 fn run_test_in_spawned_subprocess(desc: TestDesc, testfn: Box<dyn FnOnce() + Send>) -> ! {
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicInfo<'_>>| {
@@ -41,6 +41,8 @@ pub enum OutputFormat {
     Json,
     /// JUnit output
     Junit,
+    /// TAP output
+    Tap,
 }
 
 /// Whether ignored test should be run or not
@@ -52,6 +54,15 @@ pub enum RunIgnored {
     Only,
 }
 
+/// The part of the test suite to run when it is split across several runs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// Which shard to run, in `0..count`.
+    pub index: usize,
+    /// Into how many shards the test suite is split.
+    pub count: usize,
+}
+
 #[derive(Clone, Copy)]
 pub enum RunStrategy {
     /// Runs the test in the current process, and sends the result back over the
@@ -21,6 +21,8 @@ pub enum TestResult {
     TrIgnored,
     TrBench(BenchSamples),
     TrTimedFail,
+    /// The test passed after failing the given number of times (see `--retries`).
+    TrFlaky(usize),
 }
 
 /// Creates a `TestResult` depending on the raw result of test execution
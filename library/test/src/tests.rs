@@ -49,7 +49,10 @@ fn new() -> TestOpts {
             shuffle_seed: None,
             test_threads: None,
             skip: vec![],
+            shard: None,
             time_options: None,
+            test_timeout: None,
+            retries: 0,
             options: Options::new(),
         }
     }
@@ -358,6 +361,159 @@ fn test_error_on_exceed() {
     assert_eq!(result, TestResult::TrOk);
 }
 
+#[test]
+fn test_timeout_fails_hung_test() {
+    fn hang() {
+        std::thread::sleep(Duration::from_secs(60));
+    }
+    let desc = TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName("whatever"),
+            ignore: false,
+            ignore_message: None,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+        },
+        testfn: DynTestFn(Box::new(hang)),
+    };
+
+    // The timeout has to be enforced even when tests are run serially.
+    let test_opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(1),
+        test_timeout: Some(Duration::from_secs(1)),
+        ..TestOpts::new()
+    };
+    let mut results = Vec::new();
+    run_tests(&test_opts, vec![desc], |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push(completed_test.result);
+        }
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(results, vec![TestResult::TrTimedFail]);
+}
+
+#[test]
+fn test_retries_report_flaky_tests() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn fails_twice() {
+        assert!(ATTEMPTS.fetch_add(1, Ordering::SeqCst) >= 2);
+    }
+    fn always_fails() {
+        panic!();
+    }
+    let test = |name, f| TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName(name),
+            ignore: false,
+            ignore_message: None,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+        },
+        testfn: StaticTestFn(f),
+    };
+
+    let test_opts = TestOpts { run_tests: true, retries: 2, ..TestOpts::new() };
+    let mut results = Vec::new();
+    run_tests(&test_opts, vec![test("a", fails_twice), test("b", always_fails)], |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push((completed_test.desc.name.to_string(), completed_test.result));
+        }
+        Ok(())
+    })
+    .unwrap();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 3);
+    assert_eq!(
+        results,
+        vec![("a".to_string(), TestResult::TrFlaky(2)), ("b".to_string(), TestResult::TrFailed)]
+    );
+}
+
+#[test]
+fn test_retries_run_dynamic_tests_once() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let test = TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName("dynamic"),
+            ignore: false,
+            ignore_message: None,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+        },
+        testfn: DynTestFn(Box::new({
+            let attempts = attempts.clone();
+            move || {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                panic!();
+            }
+        })),
+    };
+
+    let test_opts = TestOpts { run_tests: true, retries: 2, ..TestOpts::new() };
+    let mut results = Vec::new();
+    run_tests(&test_opts, vec![test], |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push(completed_test.result);
+        }
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    assert_eq!(results, vec![TestResult::TrFailed]);
+}
+
+#[test]
+fn test_retries_retry_benchmarks_run_as_tests() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn fails_once(_: &mut Bencher) {
+        assert!(ATTEMPTS.fetch_add(1, Ordering::SeqCst) >= 1);
+    }
+    let test = TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName("bench"),
+            ignore: false,
+            ignore_message: None,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+        },
+        testfn: StaticBenchFn(fails_once),
+    };
+
+    let test_opts = TestOpts { run_tests: true, retries: 2, ..TestOpts::new() };
+    let mut results = Vec::new();
+    run_tests(&test_opts, vec![test], |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push(completed_test.result);
+        }
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+    assert_eq!(results, vec![TestResult::TrFlaky(1)]);
+}
+
 fn typed_test_desc(test_type: TestType) -> TestDesc {
     TestDesc {
         name: StaticTestName("whatever"),
@@ -422,6 +578,38 @@ fn parse_show_output_flag() {
     assert!(opts.options.display_output);
 }
 
+#[test]
+fn parse_test_timeout_option() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout=30".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(30)));
+
+    let args = vec!["progname".to_string(), "--test-timeout=30".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout=0".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+fn parse_retries_option() {
+    let args =
+        vec!["progname".to_string(), "-Zunstable-options".to_string(), "--retries=3".to_string()];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.retries, 3);
+
+    let args = vec!["progname".to_string(), "--retries=3".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
 #[test]
 fn parse_include_ignored_flag() {
     let args = vec!["progname".to_string(), "filter".to_string(), "--include-ignored".to_string()];
@@ -627,6 +815,50 @@ pub fn sort_tests() {
     }
 }
 
+#[test]
+pub fn shard_tests() {
+    let opts = |shard| TestOpts { skip: vec!["sha1".into()], shard, ..TestOpts::new() };
+    let all: Vec<String> =
+        filter_tests(&opts(None), sample_tests()).iter().map(|t| t.desc.name.to_string()).collect();
+
+    // Every test runs on exactly one shard, and skipped tests run on none.
+    let mut sharded = Vec::new();
+    for index in 0..3 {
+        let shard = filter_tests(&opts(Some(Shard { index, count: 3 })), sample_tests());
+        assert!(shard.len() < all.len());
+        sharded.extend(shard.iter().map(|t| t.desc.name.to_string()));
+    }
+    sharded.sort();
+    assert_eq!(sharded, all);
+}
+
+#[test]
+pub fn shard_is_stable() {
+    // Shards are assigned by a hash of the test name alone, which must not change
+    // between releases or when other tests are added.
+    assert_eq!(helpers::shard::shard_of("isize::test_pow", 1), 0);
+    assert_eq!(helpers::shard::shard_of("isize::test_pow", 7), 3);
+    assert_eq!(helpers::shard::shard_of("sha1::test", 7), 2);
+}
+
+#[test]
+fn parse_shard_options() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--shard-index=1".to_string(),
+        "--shard-count=4".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.shard, Some(Shard { index: 1, count: 4 }));
+
+    for bad_args in [&["--shard-index=4", "--shard-count=4"][..], &["--shard-index=0"][..]] {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(bad_args.iter().map(|arg| arg.to_string()));
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+}
+
 #[test]
 pub fn shuffle_tests() {
     let mut opts = TestOpts::new();
@@ -809,6 +1041,8 @@ fn should_sort_failures_before_printing_them() {
         options: Options::new(),
         not_failures: Vec::new(),
         time_failures: Vec::new(),
+        flaky: 0,
+        flaky_tests: Vec::new(),
     };
 
     out.write_failures(&st).unwrap();
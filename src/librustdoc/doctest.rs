@@ -105,6 +105,7 @@ pub(crate) fn run(options: RustdocOptions) -> Result<(), ErrorGuaranteed> {
         parse_sess_created: None,
         register_lints: Some(Box::new(crate::lint::register_lints)),
         override_queries: None,
+        mir_pass_registry: Default::default(),
         make_codegen_backend: None,
         registry: rustc_driver::diagnostics_registry(),
     };
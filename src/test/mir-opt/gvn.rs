@@ -0,0 +1,37 @@
+// unit-test: GVN
+// compile-flags: -C overflow-checks=on
+
+// EMIT_MIR gvn.slice_len.GVN.diff
+fn slice_len(s: &[u8]) -> u8 {
+    let a = s[0];
+    let b = s[1];
+    a ^ b
+}
+
+// EMIT_MIR gvn.discriminant.GVN.diff
+fn discriminant(x: Option<u8>) -> u8 {
+    let a = match x { Some(_) => 1, _ => 0 };
+    let b = match x { Some(_) => 2, _ => 0 };
+    a | b
+}
+
+// EMIT_MIR gvn.cast.GVN.diff
+fn cast(x: u8) -> u64 {
+    let a = x as u64;
+    let b = x as u64;
+    a ^ b
+}
+
+// EMIT_MIR gvn.checked_add.GVN.diff
+fn checked_add(x: u8, y: u8) -> u8 {
+    let a = x + y;
+    let b = x + y;
+    a ^ b
+}
+
+fn main() {
+    slice_len(&[1, 2]);
+    discriminant(Some(1));
+    cast(1);
+    checked_add(1, 2);
+}
@@ -0,0 +1,14 @@
+// unit-test: GVN
+// compile-flags: -Zmir-enable-passes=+ConstProp
+
+// `1 | 2` is folded by const propagation before GVN runs, so both `BitAnd`s compute the same value.
+// EMIT_MIR gvn_const_prop.folded.GVN.diff
+fn folded(x: u8) -> (u8, u8) {
+    let a = x & (1 | 2);
+    let b = x & 3;
+    (a, b)
+}
+
+fn main() {
+    folded(1);
+}
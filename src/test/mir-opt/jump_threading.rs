@@ -0,0 +1,65 @@
+// unit-test: JumpThreading
+// ignore-wasm32-bare compiled with panic=abort by default
+
+// The cascade of discriminant switches left by `?` and `Option` combinators.
+// EMIT_MIR jump_threading.option_cascade.JumpThreading.diff
+fn option_cascade(o: Option<u8>) -> u8 {
+    let p = match o {
+        Some(v) => Some(v),
+        None => None,
+    };
+    match p {
+        Some(w) => w,
+        None => 0,
+    }
+}
+
+// `p` could be changed through `_r`, so its discriminant isn't tracked.
+// EMIT_MIR jump_threading.borrowed.JumpThreading.diff
+fn borrowed(o: Option<u8>) -> u8 {
+    let p = match o {
+        Some(v) => Some(v),
+        None => None,
+    };
+    let _r = &p;
+    match p {
+        Some(w) => w,
+        None => 0,
+    }
+}
+
+// The switch block overwrites the discriminant set by its predecessors.
+// EMIT_MIR jump_threading.write_in_switch_block.JumpThreading.diff
+fn write_in_switch_block(o: Option<u8>) -> u8 {
+    let mut p = match o {
+        Some(v) => Some(v),
+        None => None,
+    };
+    p = None;
+    match p {
+        Some(w) => w,
+        None => 0,
+    }
+}
+
+// The drop flag of `u` is set before the call to `drop`, but it is only switched over in the
+// cleanup block the call unwinds to, which must not be threaded.
+// EMIT_MIR jump_threading.cleanup.JumpThreading.diff
+fn cleanup() {
+    let u = S;
+    let mut v = S;
+    drop(v);
+    v = u;
+}
+
+struct S;
+impl Drop for S {
+    fn drop(&mut self) {}
+}
+
+fn main() {
+    option_cascade(Some(1));
+    borrowed(Some(1));
+    write_in_switch_block(Some(1));
+    cleanup();
+}
@@ -0,0 +1,24 @@
+// unit-test: ScalarReplacementOfAggregates
+
+struct Foo {
+    a: u8,
+    b: u32,
+}
+
+// EMIT_MIR sroa.structs.ScalarReplacementOfAggregates.diff
+fn structs(a: u8, b: u32) -> u32 {
+    let foo = Foo { a, b };
+    foo.b
+}
+
+// EMIT_MIR sroa.borrowed.ScalarReplacementOfAggregates.diff
+fn borrowed(a: u8, b: u32) -> u8 {
+    let foo = Foo { a, b };
+    let r = &foo.a;
+    *r
+}
+
+fn main() {
+    structs(1, 2);
+    borrowed(1, 2);
+}
@@ -0,0 +1,13 @@
+#![crate_type = "lib"]
+#![feature(const_eval_limit)]
+#![const_eval_limit = "1000"]
+
+const fn spin(n: u64) -> u64 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    i
+}
+
+pub const FOREVER: u64 = spin(u64::MAX);
@@ -0,0 +1,23 @@
+#![crate_type = "lib"]
+
+const fn step(x: u32) -> u32 {
+    let mut x = x;
+    let mut i = 0;
+    while i < 8 {
+        x = x * 3 % 1021 + 1;
+        i += 1;
+    }
+    x
+}
+
+const fn make_table() -> [u32; 16] {
+    let mut table = [0; 16];
+    let mut i = 0;
+    while i < 16 {
+        table[i] = step(i as u32);
+        i += 1;
+    }
+    table
+}
+
+pub const TABLE: [u32; 16] = make_table();
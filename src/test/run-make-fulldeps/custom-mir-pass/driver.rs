@@ -0,0 +1,48 @@
+#![feature(rustc_private)]
+
+//! This program implements a rustc driver that registers a MIR pass at two
+//! points of the MIR pipeline. The pass records the bodies it runs on, which
+//! are printed once the compilation has finished.
+
+extern crate rustc_driver;
+extern crate rustc_middle;
+
+use rustc_middle::mir::{Body, MirPass, MirPassPoint, MirPassRegistry};
+use rustc_middle::ty::TyCtxt;
+use std::sync::Mutex;
+
+static VISITED_BODIES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn main() {
+    let exit_code = rustc_driver::catch_with_exit_code(move || {
+        let rustc_args: Vec<_> = std::env::args().collect();
+        let mut callbacks = CompilerCalls;
+        rustc_driver::RunCompiler::new(&rustc_args, &mut callbacks).run()
+    });
+
+    let mut visited_bodies = VISITED_BODIES.lock().unwrap();
+    visited_bodies.sort();
+    for visited_body in visited_bodies.iter() {
+        println!("{}", visited_body);
+    }
+
+    std::process::exit(exit_code);
+}
+
+pub struct CompilerCalls;
+
+impl rustc_driver::Callbacks for CompilerCalls {
+    fn register_mir_passes(&mut self, registry: &mut MirPassRegistry) {
+        registry.register(MirPassPoint::DropsElaborated, RecordPass("DropsElaborated"));
+        registry.register(MirPassPoint::AfterOptimizations, RecordPass("AfterOptimizations"));
+    }
+}
+
+struct RecordPass(&'static str);
+
+impl<'tcx> MirPass<'tcx> for RecordPass {
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        let def_path = tcx.def_path_str(body.source.def_id());
+        VISITED_BODIES.lock().unwrap().push(format!("{}: {}", self.0, def_path));
+    }
+}
@@ -0,0 +1,14 @@
+fn double(x: u32) -> u32 {
+    x * 2
+}
+
+const fn triple(x: u32) -> u32 {
+    x * 3
+}
+
+const NINE: u32 = triple(3);
+
+fn main() {
+    assert_eq!(double(NINE), 18);
+    assert_eq!(triple(2), 6);
+}
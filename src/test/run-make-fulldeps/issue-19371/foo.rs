@@ -60,6 +60,7 @@ fn compile(code: String, output: PathBuf, sysroot: PathBuf) {
         parse_sess_created: None,
         register_lints: None,
         override_queries: None,
+        mir_pass_registry: Default::default(),
         make_codegen_backend: None,
         registry: rustc_driver::diagnostics_registry(),
     };
@@ -0,0 +1,35 @@
+#![feature(rustc_private)]
+
+//! This program implements a rustc driver that prints the items of a crate,
+//! their signatures and the functions they call, using only the stable MIR
+//! types of `rustc_smir`.
+
+extern crate rustc_smir;
+
+use rustc_smir::mir::{Operand, TerminatorKind};
+use rustc_smir::ty::Ty;
+use rustc_smir::very_unstable::driver::catch_with_exit_code;
+
+fn main() {
+    let exit_code = catch_with_exit_code(move || {
+        let rustc_args: Vec<_> = std::env::args().collect();
+        rustc_smir::run(&rustc_args, |cx, items| {
+            let mut items = items.to_vec();
+            items.sort_by(|a, b| a.name.cmp(&b.name));
+            for item in items {
+                let body = cx.mir_body(item.def_id).unwrap();
+                println!("{}: {} args, returns {:?}", item.name, body.arg_count, body.locals[0].ty);
+                for block in &body.blocks {
+                    if let TerminatorKind::Call { func: Operand::Constant(func), .. } =
+                        &block.terminator.kind
+                    {
+                        if let Ty::FnDef(def_id, _) = func.ty {
+                            println!("{} calls {}", item.name, cx.def_path(def_id));
+                        }
+                    }
+                }
+            }
+        })
+    });
+    std::process::exit(exit_code);
+}
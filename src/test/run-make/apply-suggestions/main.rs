@@ -0,0 +1,5 @@
+fn main() {
+    let mut x = 1;
+    let mut y = 2;
+    println!("{} {}", x, y);
+}
@@ -0,0 +1,6 @@
+fn main() {
+    let x = std::env::args().count();
+    if x > 1 {
+        println!("{}", x);
+    }
+}
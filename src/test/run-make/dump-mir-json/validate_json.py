@@ -0,0 +1,18 @@
+#!/usr/bin/env python
+
+import sys
+import json
+
+# Every dump is a single JSON object describing one body after one pass.
+for path in sys.argv[1:]:
+    with open(path) as f:
+        body = json.load(f)
+    assert body["def_path"] == "main", path
+    assert body["pass_name"], path
+    assert body["phase"], path
+    assert body["locals"][0]["local"] == "_0", path
+    for block in body["basic_blocks"]:
+        assert block["block"].startswith("bb"), path
+        for statement in block["statements"]:
+            assert statement["kind"] and statement["text"], path
+        assert block["terminator"]["kind"], path
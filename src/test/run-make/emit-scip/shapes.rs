@@ -0,0 +1,20 @@
+/// A shape with an area.
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+/// A circle of some radius.
+pub struct Circle {
+    pub radius: f64,
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        let r = self.radius;
+        std::f64::consts::PI * r * r
+    }
+}
+
+pub fn total_area(shapes: &[&dyn Shape]) -> f64 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
@@ -0,0 +1,7 @@
+pub async fn pick<'a, 'b>(_x: &'a str, y: &'b str) -> &'a str {
+    y
+}
+
+pub fn unrelated<'a>(x: &'a str) -> &'static str {
+    x
+}
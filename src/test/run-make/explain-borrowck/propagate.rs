@@ -0,0 +1,7 @@
+// The closure needs `'b: 'a`, which it can't prove, so it propagates the requirement to
+// `propagate`, where it is implied by the type of `x`: there is no error, so no constraint is
+// blamed.
+pub fn propagate<'a, 'b>(x: &'a &'b str) -> &'a str {
+    let choose = |y: &'b str| -> &'a str { y };
+    choose(*x)
+}
@@ -0,0 +1,11 @@
+fn unchanged() -> u32 {
+    2
+}
+
+fn changed() -> u8 {
+    1
+}
+
+fn main() {
+    println!("{} {}", unchanged(), changed());
+}
@@ -0,0 +1,4 @@
+pub fn two() -> u32 {
+    let unused_in_generated = 2;
+    2
+}
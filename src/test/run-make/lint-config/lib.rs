@@ -0,0 +1,12 @@
+//! A crate whose lint levels are set by a `--lint-config` file.
+
+#![allow(dead_code)]
+
+/// Code that isn't written by hand.
+pub mod generated;
+
+/// Returns one.
+pub fn one() -> u32 {
+    let unused_in_lib = 1;
+    1
+}
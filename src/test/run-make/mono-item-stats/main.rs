@@ -0,0 +1,7 @@
+extern crate mono_lib;
+
+fn main() {
+    println!("{}", mono_lib::describe(1u8));
+    println!("{}", mono_lib::describe("a"));
+    println!("{}", mono_lib::describe_twice(2u16));
+}
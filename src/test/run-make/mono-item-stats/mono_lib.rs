@@ -0,0 +1,11 @@
+#![crate_type = "rlib"]
+
+use std::fmt::Debug;
+
+pub fn describe<T: Debug>(value: T) -> String {
+    format!("{:?}", value)
+}
+
+pub fn describe_twice<T: Debug + Clone>(value: T) -> String {
+    describe(value.clone()) + &describe(value)
+}
@@ -0,0 +1,36 @@
+#!/usr/bin/env python
+
+import sys
+import json
+
+with open(sys.argv[1]) as f:
+    facts = json.load(f)
+
+assert facts["version"] == 1
+assert facts["def_path"] == "main"
+
+# Every atom the facts refer to is described.
+point_count = len(facts["points"])
+for (from_point, to_point) in facts["input_facts"]["cfg_edge"]:
+    assert from_point < point_count and to_point < point_count
+loan_count = len(facts["loans"])
+region_count = len(facts["regions"])
+for (region, loan, point) in facts["input_facts"]["loan_issued_at"]:
+    assert region < region_count and loan < loan_count and point < point_count
+
+# The shared borrow of `v` is on line 3, and its region is live until the `println!` on line 5.
+shared_loans = [loan for loan in facts["loans"] if loan["kind"] == "shared"]
+assert any(loan["span"]["start_line"] == 3 for loan in shared_loans)
+first_loan = [loan for loan in shared_loans if loan["span"]["start_line"] == 3][0]
+region = facts["regions"][first_loan["region"]]
+assert region["span"]["start_line"] == 3
+assert region["locations"]
+
+assert any(local["name"] == "first" for local in facts["locals"])
+
+# The error is reported on the call to `push`.
+[error] = facts["errors"]
+assert error["level"] == "error"
+assert error["code"] == "E0502"
+assert error["primary_spans"][0]["start_line"] == 4
+assert any(label["span"]["start_line"] == 3 for label in error["labels"])
@@ -0,0 +1,35 @@
+// normalize-stderr-test "alloc\d+" -> "allocN"
+#![feature(core_intrinsics)]
+#![feature(const_heap)]
+#![feature(const_mut_refs)]
+use std::intrinsics;
+
+const WRITE_AFTER: *const u8 = unsafe {
+    let ptr = intrinsics::const_allocate(1, 1);
+    let global = intrinsics::const_make_global(ptr);
+    *ptr = 1;
+    //~^ error: evaluation of constant value failed
+    global
+};
+
+const DEALLOC_AFTER: () = unsafe {
+    let ptr = intrinsics::const_allocate(1, 1);
+    intrinsics::const_make_global(ptr);
+    intrinsics::const_deallocate(ptr, 1, 1);
+    //~^ error: evaluation of constant value failed
+};
+
+const TWICE: *const u8 = unsafe {
+    let ptr = intrinsics::const_allocate(1, 1);
+    intrinsics::const_make_global(ptr);
+    intrinsics::const_make_global(ptr)
+    //~^ error: evaluation of constant value failed
+};
+
+const NOT_HEAP: *const u8 = unsafe {
+    let mut x = 0u8;
+    intrinsics::const_make_global(&mut x)
+    //~^ error: evaluation of constant value failed
+};
+
+fn main() {}
@@ -0,0 +1,67 @@
+// run-pass
+// Check that `Vec`-like and `String`-like values whose buffer is allocated with `const_allocate`
+// and made global with `const_make_global` can be the final value of a constant.
+#![feature(core_intrinsics)]
+#![feature(const_heap)]
+#![feature(const_mut_refs)]
+#![feature(const_ptr_write)]
+use std::intrinsics;
+
+struct ConstVec {
+    ptr: *const u32,
+    len: usize,
+}
+
+impl ConstVec {
+    fn as_slice(&self) -> &[u32] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+const fn squares(len: usize) -> ConstVec {
+    let ptr = unsafe { intrinsics::const_allocate(len * 4, 4) as *mut u32 };
+    let mut i = 0;
+    while i < len {
+        unsafe { ptr.add(i).write((i * i) as u32) };
+        i += 1;
+    }
+    let ptr = unsafe { intrinsics::const_make_global(ptr as *mut u8) as *const u32 };
+    ConstVec { ptr, len }
+}
+
+const SQUARES: ConstVec = squares(5);
+
+struct ConstString {
+    bytes: *const u8,
+    len: usize,
+}
+
+impl ConstString {
+    fn as_str(&self) -> &str {
+        unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.bytes, self.len)) }
+    }
+}
+
+const fn repeat(s: &str, times: usize) -> ConstString {
+    let len = s.len() * times;
+    let bytes = unsafe { intrinsics::const_allocate(len, 1) };
+    let mut i = 0;
+    while i < len {
+        unsafe { bytes.add(i).write(s.as_bytes()[i % s.len()]) };
+        i += 1;
+    }
+    let bytes = unsafe { intrinsics::const_make_global(bytes) };
+    ConstString { bytes, len }
+}
+
+const GREETING: ConstString = repeat("hi ", 3);
+
+// Each element of the array keeps its own buffer.
+const NESTED: [ConstVec; 2] = [squares(2), squares(3)];
+
+fn main() {
+    assert_eq!(SQUARES.as_slice(), &[0, 1, 4, 9, 16]);
+    assert_eq!(GREETING.as_str(), "hi hi hi ");
+    assert_eq!(NESTED[0].as_slice(), &[0, 1]);
+    assert_eq!(NESTED[1].as_slice(), &[0, 1, 4]);
+}
@@ -0,0 +1,46 @@
+// Check that computations replaced by global value numbering keep their values.
+// run-pass
+// compile-flags: -Zmir-opt-level=2 -Zvalidate-mir
+
+#[inline(never)]
+fn lengths(a: &[u8], b: &mut [u8]) -> usize {
+    let first = a.len() + b.len();
+    b[0] = 3;
+    first + a.len() + b.len()
+}
+
+#[inline(never)]
+fn discriminants(x: Option<u8>) -> u8 {
+    let a = if let Some(v) = x { v } else { 0 };
+    let b = match x {
+        Some(v) => v + 1,
+        None => 1,
+    };
+    a + b
+}
+
+#[inline(never)]
+fn arithmetic(x: u32, y: u32) -> u64 {
+    let a = (x + y) as u64;
+    let b = (x + y) as u64;
+    let c = (x as u64) * (y as u64);
+    a + b + c
+}
+
+#[inline(never)]
+fn in_loop(v: &[u32]) -> u32 {
+    let mut sum = 0;
+    for i in 0..v.len() {
+        let x = v[i];
+        sum += x * 2 + x * 2;
+    }
+    sum
+}
+
+fn main() {
+    assert_eq!(lengths(&[1, 2], &mut [0, 0, 0]), 10);
+    assert_eq!(discriminants(Some(3)), 7);
+    assert_eq!(discriminants(None), 1);
+    assert_eq!(arithmetic(2, 3), 16);
+    assert_eq!(in_loop(&[1, 2, 3]), 24);
+}
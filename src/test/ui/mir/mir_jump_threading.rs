@@ -0,0 +1,33 @@
+// Check that threading the jumps to discriminant switches keeps the behavior of `?` and of
+// `Option` combinators.
+// run-pass
+// compile-flags: -Zmir-opt-level=2 -Zvalidate-mir
+
+#[inline(never)]
+fn parse_sum(a: &str, b: &str) -> Result<u32, std::num::ParseIntError> {
+    let a: u32 = a.parse()?;
+    let b: u32 = b.parse()?;
+    Ok(a + b)
+}
+
+#[inline(never)]
+fn first_even_doubled(v: &[u32]) -> Option<u32> {
+    let x = v.iter().copied().find(|x| x % 2 == 0)?;
+    Some(x).map(|x| x * 2).filter(|x| *x > 2)
+}
+
+#[inline(never)]
+fn flag(x: u8) -> &'static str {
+    let small = if x < 10 { true } else { false };
+    if small { "small" } else { "large" }
+}
+
+fn main() {
+    assert_eq!(parse_sum("1", "2"), Ok(3));
+    assert!(parse_sum("1", "x").is_err());
+    assert_eq!(first_even_doubled(&[1, 3, 4]), Some(8));
+    assert_eq!(first_even_doubled(&[1, 2]), None);
+    assert_eq!(first_even_doubled(&[1, 3]), None);
+    assert_eq!(flag(3), "small");
+    assert_eq!(flag(30), "large");
+}
@@ -0,0 +1,49 @@
+// Check that locals split into their fields by scalar replacement of aggregates keep their values.
+// run-pass
+// compile-flags: -Zmir-opt-level=2 -Zvalidate-mir -Cdebuginfo=2
+
+struct Point {
+    x: u32,
+    y: u64,
+}
+
+struct Line {
+    start: Point,
+    end: Point,
+}
+
+#[inline(never)]
+fn length_squared(x0: u32, y0: u64, x1: u32, y1: u64) -> u64 {
+    let line = Line { start: Point { x: x0, y: y0 }, end: Point { x: x1, y: y1 } };
+    let dx = (line.end.x - line.start.x) as u64;
+    let dy = line.end.y - line.start.y;
+    dx * dx + dy * dy
+}
+
+#[inline(never)]
+fn swap(pair: (u8, char)) -> (char, u8) {
+    let mut tmp = pair;
+    tmp.0 += 1;
+    (tmp.1, tmp.0)
+}
+
+#[inline(never)]
+fn sum_with_closure(a: u32, b: u32) -> u32 {
+    let add = |c: u32| a + b + c;
+    add(3)
+}
+
+#[inline(never)]
+fn borrowed() -> u32 {
+    let mut point = Point { x: 1, y: 2 };
+    let x = &mut point.x;
+    *x += 1;
+    point.x + point.y as u32
+}
+
+fn main() {
+    assert_eq!(length_squared(1, 2, 4, 6), 25);
+    assert_eq!(swap((1, 'a')), ('a', 2));
+    assert_eq!(sum_with_closure(1, 2), 6);
+    assert_eq!(borrowed(), 4);
+}
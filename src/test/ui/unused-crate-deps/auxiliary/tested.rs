@@ -0,0 +1 @@
+pub const TESTED: &str = "tested";
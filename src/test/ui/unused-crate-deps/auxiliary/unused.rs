@@ -0,0 +1 @@
+pub const UNUSED: &str = "unused";
@@ -0,0 +1,24 @@
+// Check the report of how each dependency is used, as a json event
+
+// edition:2018
+// check-pass
+// compile-flags: --test -Zunstable-options --json dependency-usage --error-format=json
+// aux-crate:bar=bar.rs
+// aux-crate:foo=foo.rs
+// aux-crate:macros=macros.rs
+// aux-crate:tested=tested.rs
+// aux-crate:unused=unused.rs
+
+fn main() {
+    // `bar` is only reached through the re-export of `foo`.
+    let _ = foo::BAR;
+    let _ = macros::answer!();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn tested() {
+        assert_eq!(tested::TESTED, "tested");
+    }
+}
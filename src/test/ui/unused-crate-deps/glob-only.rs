@@ -0,0 +1,16 @@
+// Check that dependencies only named by glob imports are linted
+
+// edition:2018
+// check-pass
+// aux-crate:bar=bar.rs
+// aux-crate:foo=foo.rs
+
+#![warn(glob_only_crate_dependencies)]
+
+use bar::*;
+//~^ WARNING external crate `bar` is only used through a glob import
+
+fn main() {
+    let _ = BAR;
+    let _ = foo::FOO;
+}
@@ -510,9 +510,12 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         shuffle_seed: None,
         test_threads: None,
         skip: config.skip.clone(),
+        shard: None,
         list: false,
         options: test::Options::new(),
         time_options: None,
+        test_timeout: None,
+        retries: 0,
         force_run_in_process: false,
     }
 }
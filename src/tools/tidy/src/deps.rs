@@ -224,6 +224,7 @@
     "time",
     "tinystr",
     "tinyvec",
+    "toml",
     "tracing",
     "tracing-attributes",
     "tracing-core",